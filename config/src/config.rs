@@ -11,7 +11,8 @@ use crate::font::{
 };
 use crate::frontend::FrontEndSelection;
 use crate::keyassignment::{
-    KeyAssignment, KeyTable, KeyTableEntry, KeyTables, MouseEventTrigger, SpawnCommand,
+    FuzzyCaseMatching, FuzzyScrollbackSort, KeyAssignment, KeyTable, KeyTableEntry, KeyTables,
+    MouseEventTrigger, QuickSelectPatternWrap, SpawnCommand,
 };
 use crate::keys::{Key, LeaderKey, Mouse};
 use crate::lua::make_lua_context;
@@ -161,6 +162,18 @@ pub struct Config {
     #[dynamic(default = "default_command_palette_bg_color")]
     pub command_palette_bg_color: RgbaColor,
 
+    #[dynamic(default = "default_fuzzy_scrollback_preview_context_lines")]
+    pub fuzzy_scrollback_preview_context_lines: usize,
+
+    #[dynamic(default = "default_fuzzy_scrollback_max_results")]
+    pub fuzzy_scrollback_max_results: usize,
+
+    #[dynamic(default)]
+    pub fuzzy_scrollback_case_matching: FuzzyCaseMatching,
+
+    #[dynamic(default)]
+    pub fuzzy_scrollback_sort: FuzzyScrollbackSort,
+
     #[dynamic(default = "default_pane_select_font_size")]
     pub pane_select_font_size: f64,
 
@@ -414,7 +427,7 @@ pub struct Config {
     #[dynamic(default)]
     pub disable_default_quick_select_patterns: bool,
     #[dynamic(default)]
-    pub quick_select_patterns: Vec<String>,
+    pub quick_select_patterns: Vec<QuickSelectPatternWrap>,
     #[dynamic(default = "default_alphabet")]
     pub quick_select_alphabet: String,
 
@@ -486,6 +499,20 @@ pub struct Config {
     #[dynamic(default)]
     pub enable_scroll_bar: bool,
 
+    /// If true, the time at which lines arrived is shown
+    /// along the right hand edge of each pane
+    #[dynamic(default)]
+    pub show_line_timestamps: bool,
+
+    /// The strftime format used to show line timestamps
+    #[dynamic(default = "default_line_timestamp_format")]
+    pub line_timestamp_format: String,
+
+    /// If true, a toggle is shown in the gutter alongside the prompt
+    /// of each completed command that folds away its output
+    #[dynamic(default = "default_true")]
+    pub show_output_fold_toggles: bool,
+
     #[dynamic(try_from = "crate::units::PixelUnit", default = "default_half_cell")]
     pub min_scroll_bar_height: Dimension,
 
@@ -1597,6 +1624,14 @@ fn default_command_palette_bg_color() -> RgbaColor {
     (0x33, 0x33, 0x33).into()
 }
 
+fn default_fuzzy_scrollback_preview_context_lines() -> usize {
+    3
+}
+
+fn default_fuzzy_scrollback_max_results() -> usize {
+    100
+}
+
 fn default_swallow_mouse_click_on_window_focus() -> bool {
     cfg!(target_os = "macos")
 }
@@ -1779,6 +1814,10 @@ fn default_enq_answerback() -> String {
     "".to_string()
 }
 
+fn default_line_timestamp_format() -> String {
+    "%H:%M:%S".to_string()
+}
+
 fn default_tab_max_width() -> usize {
     16
 }
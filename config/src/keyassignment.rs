@@ -436,6 +436,96 @@ impl Default for CharSelectArguments {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, FromDynamic, ToDynamic)]
+pub struct QuickSelectPattern {
+    pub regex: String,
+    /// Performed in place of the default action when the selected
+    /// text was matched by this pattern
+    #[dynamic(default)]
+    pub action: Option<Box<KeyAssignment>>,
+    /// Label to use in place of "copy" when `action` is set
+    #[dynamic(default)]
+    pub label: String,
+    /// The capture group whose text is selected, in place
+    /// of the default choice of group
+    #[dynamic(default)]
+    pub group: Option<CaptureGroup>,
+}
+
+/// Identifies a regex capture group by its number or its name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureGroup {
+    Index(usize),
+    Name(String),
+}
+
+impl FromDynamic for CaptureGroup {
+    fn from_dynamic(
+        value: &Value,
+        _options: FromDynamicOptions,
+    ) -> Result<Self, wezterm_dynamic::Error> {
+        match value {
+            Value::U64(n) => Ok(Self::Index(*n as usize)),
+            Value::I64(n) if *n >= 0 => Ok(Self::Index(*n as usize)),
+            Value::String(s) => Ok(Self::Name(s.to_string())),
+            _ => Err(wezterm_dynamic::Error::Message(
+                "capture group must be either a group number or a group name".to_string(),
+            )),
+        }
+    }
+}
+
+impl ToDynamic for CaptureGroup {
+    fn to_dynamic(&self) -> Value {
+        match self {
+            Self::Index(n) => (*n as u64).to_dynamic(),
+            Self::Name(s) => s.to_dynamic(),
+        }
+    }
+}
+
+/// A quick select pattern is either a table or just the regex
+#[derive(Debug, Clone, PartialEq, ToDynamic)]
+pub struct QuickSelectPatternWrap {
+    #[dynamic(flatten)]
+    inner: QuickSelectPattern,
+}
+
+impl std::ops::Deref for QuickSelectPatternWrap {
+    type Target = QuickSelectPattern;
+    fn deref(&self) -> &QuickSelectPattern {
+        &self.inner
+    }
+}
+
+impl From<QuickSelectPattern> for QuickSelectPatternWrap {
+    fn from(inner: QuickSelectPattern) -> Self {
+        Self { inner }
+    }
+}
+
+impl FromDynamic for QuickSelectPatternWrap {
+    fn from_dynamic(
+        value: &Value,
+        options: FromDynamicOptions,
+    ) -> Result<Self, wezterm_dynamic::Error> {
+        match value {
+            Value::String(regex) => Ok(Self {
+                inner: QuickSelectPattern {
+                    regex: regex.to_string(),
+                    action: None,
+                    label: String::new(),
+                    group: None,
+                },
+            }),
+            _ => {
+                let inner = QuickSelectPattern::from_dynamic(value, options)?;
+                Ok(Self { inner })
+            }
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, FromDynamic, ToDynamic)]
 pub struct QuickSelectArguments {
     /// Overrides the main quick_select_alphabet config
@@ -443,7 +533,7 @@ pub struct QuickSelectArguments {
     pub alphabet: String,
     /// Overrides the main quick_select_patterns config
     #[dynamic(default)]
-    pub patterns: Vec<String>,
+    pub patterns: Vec<QuickSelectPatternWrap>,
     #[dynamic(default)]
     pub action: Option<Box<KeyAssignment>>,
     /// Label to use in place of "copy" when `action` is set
@@ -452,6 +542,152 @@ pub struct QuickSelectArguments {
     /// How man lines before and how many lines after the viewport to
     /// search to produce the quickselect results
     pub scope_lines: Option<usize>,
+    /// Label the matches in all of the visible panes in the active tab
+    #[dynamic(default)]
+    pub all_panes: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum FuzzyScrollbackScope {
+    /// Only the active pane
+    Pane,
+    /// All panes in the active tab
+    Tab,
+    /// All panes in all tabs of the current window
+    Window,
+    /// All panes in all windows of the active workspace
+    Workspace,
+}
+
+impl Default for FuzzyScrollbackScope {
+    fn default() -> Self {
+        Self::Pane
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum FuzzyScrollbackFilter {
+    /// Match against every line
+    All,
+    /// Match only against text in `SemanticType::Input` zones, which
+    /// makes the search act as a picker for the command history
+    Input,
+}
+
+impl Default for FuzzyScrollbackFilter {
+    fn default() -> Self {
+        Self::All
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum FuzzyCaseMatching {
+    /// Case insensitive unless the query contains an uppercase letter
+    Smart,
+    Ignore,
+    Respect,
+}
+
+impl Default for FuzzyCaseMatching {
+    fn default() -> Self {
+        Self::Smart
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum FuzzyScrollbackSort {
+    /// Best score first; equal scores are listed in scrollback order
+    Score,
+    /// Best score first; equal scores are listed newest first
+    ScoreThenRecency,
+    /// Newest first, regardless of score
+    Recency,
+}
+
+impl Default for FuzzyScrollbackSort {
+    fn default() -> Self {
+        Self::Score
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub struct FuzzyScrollbackSearchArguments {
+    /// Text to pre-populate the search input with
+    #[dynamic(default)]
+    pub initial_query: String,
+    /// Which panes contribute lines to the search
+    #[dynamic(default)]
+    pub scope: FuzzyScrollbackScope,
+    /// Which text within those panes is matched
+    #[dynamic(default)]
+    pub filter: FuzzyScrollbackFilter,
+    /// Overrides the fuzzy_scrollback_max_results config option
+    #[dynamic(default)]
+    pub max_results: Option<usize>,
+    /// Overrides the fuzzy_scrollback_case_matching config option
+    #[dynamic(default)]
+    pub case_matching: Option<FuzzyCaseMatching>,
+    /// Overrides the fuzzy_scrollback_sort config option
+    #[dynamic(default)]
+    pub sort: Option<FuzzyScrollbackSort>,
+}
+impl_lua_conversion_dynamic!(FuzzyScrollbackSearchArguments);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum ExportFormat {
+    /// A standalone HTML document
+    Html,
+    /// A standalone SVG image
+    Svg,
+    /// Text with the escape sequences that color and style it
+    Ansi,
+}
+impl_lua_conversion_dynamic!(ExportFormat);
+
+impl Default for ExportFormat {
+    fn default() -> Self {
+        Self::Html
+    }
+}
+
+impl ExportFormat {
+    /// The file name extension for files in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Html => "html",
+            Self::Svg => "svg",
+            Self::Ansi => "ans",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum ExportScope {
+    /// The selection, or the viewport if nothing is selected
+    Selection,
+    /// The lines in the viewport
+    Viewport,
+    /// The scrollback and the lines in the viewport
+    Scrollback,
+}
+
+impl Default for ExportScope {
+    fn default() -> Self {
+        Self::Selection
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub struct ExportTextArguments {
+    #[dynamic(default)]
+    pub format: ExportFormat,
+    /// Which lines of the pane are exported
+    #[dynamic(default)]
+    pub scope: ExportScope,
+    /// Where to write the file.  Relative paths are taken relative
+    /// to the home directory.  If omitted, a name is generated.
+    #[dynamic(default)]
+    pub path: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, FromDynamic, ToDynamic)]
@@ -610,6 +846,10 @@ pub enum KeyAssignment {
     ResetTerminal,
     OpenUri(String),
     ActivateCommandPalette,
+    FuzzyScrollbackSearch(FuzzyScrollbackSearchArguments),
+    ExportText(ExportTextArguments),
+    CollapsePreviousOutputs,
+    ExpandAllOutputs,
     ActivateWindow(usize),
     ActivateWindowRelative(isize),
     ActivateWindowRelativeNoWrap(isize),
@@ -647,6 +887,42 @@ pub enum RotationDirection {
     CounterClockwise,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub struct CopyColumnsArguments {
+    /// The columns to copy, numbered from 1, in the order that they
+    /// are to be joined. Negative numbers count back from the last
+    /// column. When empty, all columns are copied.
+    #[dynamic(default)]
+    pub columns: Vec<i64>,
+    /// A regex that matches the text between columns
+    #[dynamic(default = "default_column_delimiter")]
+    pub delimiter: String,
+    /// Joins the copied columns of each row
+    #[dynamic(default = "default_column_separator")]
+    pub separator: String,
+    #[dynamic(default)]
+    pub destination: ClipboardCopyDestination,
+}
+
+impl Default for CopyColumnsArguments {
+    fn default() -> Self {
+        Self {
+            columns: vec![],
+            delimiter: default_column_delimiter(),
+            separator: default_column_separator(),
+            destination: ClipboardCopyDestination::default(),
+        }
+    }
+}
+
+fn default_column_delimiter() -> String {
+    r"\s+".to_string()
+}
+
+fn default_column_separator() -> String {
+    " ".to_string()
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
 pub enum CopyModeAssignment {
     MoveToViewportBottom,
@@ -689,6 +965,16 @@ pub enum CopyModeAssignment {
     JumpBackward { prev_char: bool },
     JumpAgain,
     JumpReverse,
+    SetMark,
+    MoveToMark,
+    JumpListBackward,
+    JumpListForward,
+    Yank,
+    CopyColumnsFromSelection(CopyColumnsArguments),
+    CopyColumnsFromMatches(CopyColumnsArguments),
+    PriorSearchHistory,
+    NextSearchHistory,
+    MoveToTime(String),
 }
 
 pub type KeyTable = HashMap<(KeyCode, Modifiers), KeyTableEntry>;
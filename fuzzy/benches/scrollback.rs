@@ -0,0 +1,92 @@
+//! Measures how long each keystroke takes when fuzzy searching a
+//! synthetic pane with a million lines of scrollback.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fuzzy::{is_refinement, score_chunks, CaseMatching, Pattern};
+
+const LINES: usize = 1_000_000;
+
+fn synthetic_scrollback() -> Vec<String> {
+    // A simple LCG keeps the lines varied but reproducible
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) as usize
+    };
+    (0..LINES)
+        .map(|i| {
+            let mut line = format!("{i:>7} ");
+            for _ in 0..6 {
+                let len = 3 + next() % 6;
+                line.extend((0..len).map(|_| (b'a' + (next() % 26) as u8) as char));
+                line.push(' ');
+            }
+            line
+        })
+        .collect()
+}
+
+/// Types `query` one char at a time, re-scoring only the previous
+/// matches whenever the query refines the last one, as the fuzzy
+/// scrollback search does.  Each candidate is paired with whether it
+/// matched the last query.
+fn type_query(candidates: &mut [(String, bool)], query: &str) -> usize {
+    let mut last = String::new();
+    let mut count = 0;
+    for (idx, _) in query.char_indices().skip(1).chain([(query.len(), ' ')]) {
+        let typed = &query[..idx];
+        let pattern = Pattern::parse(typed, CaseMatching::Smart);
+        let refine = !last.is_empty() && is_refinement(&last, typed);
+        let scored = score_chunks(
+            &pattern,
+            candidates,
+            |(text, matched)| (!refine || *matched).then_some(text.as_str()),
+            || false,
+        )
+        .unwrap();
+        candidates.iter_mut().for_each(|(_, matched)| *matched = false);
+        for (idx, _) in &scored {
+            candidates[*idx].1 = true;
+        }
+        count = scored.len();
+        last = typed.to_string();
+    }
+    count
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let lines = synthetic_scrollback();
+
+    let mut group = c.benchmark_group("scrollback 1000000");
+    group.sample_size(10);
+
+    group.bench_function("full scan, one keystroke", |b| {
+        let pattern = Pattern::parse("cargo", CaseMatching::Smart);
+        b.iter(|| black_box(score_chunks(&pattern, &lines, |l| Some(l.as_str()), || false)))
+    });
+
+    group.bench_function("typing with refinement", |b| {
+        let mut candidates: Vec<(String, bool)> =
+            lines.iter().map(|line| (line.clone(), false)).collect();
+        b.iter(|| black_box(type_query(&mut candidates, "cargo build")))
+    });
+
+    group.bench_function("cancelled after the first chunk", |b| {
+        let pattern = Pattern::parse("cargo", CaseMatching::Smart);
+        b.iter(|| {
+            let started = std::sync::atomic::AtomicBool::new(false);
+            black_box(score_chunks(
+                &pattern,
+                &lines,
+                |l| Some(l.as_str()),
+                || started.swap(true, std::sync::atomic::Ordering::SeqCst),
+            ))
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,673 @@
+//! The individual matching algorithms used by the terms of a `Pattern`.
+//! These are a port of the classic fzf "v2" scoring scheme.
+//! All indices are expressed in chars (unicode scalar values) rather
+//! than bytes.
+
+const SCORE_MATCH: i16 = 16;
+const SCORE_GAP_START: i16 = -3;
+const SCORE_GAP_EXTENSION: i16 = -1;
+const BONUS_BOUNDARY: i16 = SCORE_MATCH / 2;
+const BONUS_NON_WORD: i16 = SCORE_MATCH / 2;
+const BONUS_CAMEL_123: i16 = BONUS_BOUNDARY + SCORE_GAP_EXTENSION;
+const BONUS_CONSECUTIVE: i16 = -(SCORE_GAP_START + SCORE_GAP_EXTENSION);
+const BONUS_FIRST_CHAR_MULTIPLIER: i16 = 2;
+
+/// The v2 algorithm needs a `text.len() * pattern.len()` matrix.
+/// Beyond this size we fall back to the greedy v1 algorithm rather
+/// than allocating an unbounded amount of memory for a single line.
+const MAX_MATRIX_SIZE: usize = 100 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    NonWord,
+    Lower,
+    Upper,
+    Letter,
+    Number,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_ascii() {
+        if c.is_ascii_lowercase() {
+            CharClass::Lower
+        } else if c.is_ascii_uppercase() {
+            CharClass::Upper
+        } else if c.is_ascii_digit() {
+            CharClass::Number
+        } else {
+            CharClass::NonWord
+        }
+    } else if c.is_lowercase() {
+        CharClass::Lower
+    } else if c.is_uppercase() {
+        CharClass::Upper
+    } else if c.is_numeric() {
+        CharClass::Number
+    } else if c.is_alphabetic() {
+        CharClass::Letter
+    } else {
+        CharClass::NonWord
+    }
+}
+
+fn bonus_for(prev_class: CharClass, class: CharClass) -> i16 {
+    if prev_class == CharClass::NonWord && class != CharClass::NonWord {
+        BONUS_BOUNDARY
+    } else if (prev_class == CharClass::Lower && class == CharClass::Upper)
+        || (prev_class != CharClass::Number && class == CharClass::Number)
+    {
+        BONUS_CAMEL_123
+    } else if class == CharClass::NonWord {
+        BONUS_NON_WORD
+    } else {
+        0
+    }
+}
+
+fn bonus_at(text: &[char], idx: usize) -> i16 {
+    if idx == 0 {
+        BONUS_BOUNDARY
+    } else {
+        bonus_for(char_class(text[idx - 1]), char_class(text[idx]))
+    }
+}
+
+/// Lowercases `c`; the pattern is already lowercased when matching
+/// case insensitively, so only the text needs to be folded.
+pub(crate) fn fold_case(c: char) -> char {
+    if c.is_ascii() {
+        c.to_ascii_lowercase()
+    } else if c.is_uppercase() {
+        c.to_lowercase().next().unwrap_or(c)
+    } else {
+        c
+    }
+}
+
+fn maybe_fold(case_sensitive: bool, c: char) -> char {
+    if case_sensitive {
+        c
+    } else {
+        fold_case(c)
+    }
+}
+
+/// The result of successfully matching a single term
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TermMatch {
+    pub start: usize,
+    pub end: usize,
+    pub score: i32,
+}
+
+/// The algorithm used to match a term against the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Algorithm {
+    FuzzyV2,
+    Exact,
+    Prefix,
+    Suffix,
+    Equal,
+}
+
+/// Scratch space that is re-used between calls to avoid
+/// allocating for every line that is matched.
+#[derive(Default, Debug)]
+pub(crate) struct Slab {
+    h0: Vec<i16>,
+    c0: Vec<i16>,
+    bonus: Vec<i16>,
+    first: Vec<usize>,
+    text: Vec<char>,
+    h: Vec<i16>,
+    c: Vec<i16>,
+}
+
+fn reset<T: Default + Clone>(v: &mut Vec<T>, size: usize) {
+    v.clear();
+    v.resize(size, T::default());
+}
+
+impl Algorithm {
+    pub fn run(
+        self,
+        case_sensitive: bool,
+        text: &[char],
+        pattern: &[char],
+        pos: Option<&mut Vec<usize>>,
+        slab: &mut Slab,
+    ) -> Option<TermMatch> {
+        match self {
+            Self::FuzzyV2 => fuzzy_match_v2(case_sensitive, text, pattern, pos, slab),
+            Self::Exact => exact_match_naive(case_sensitive, text, pattern, pos),
+            Self::Prefix => prefix_match(case_sensitive, text, pattern, pos),
+            Self::Suffix => suffix_match(case_sensitive, text, pattern, pos),
+            Self::Equal => equal_match(case_sensitive, text, pattern, pos),
+        }
+    }
+}
+
+fn calculate_score(
+    case_sensitive: bool,
+    text: &[char],
+    pattern: &[char],
+    sidx: usize,
+    eidx: usize,
+    mut pos: Option<&mut Vec<usize>>,
+) -> i32 {
+    let mut pidx = 0;
+    let mut score: i32 = 0;
+    let mut in_gap = false;
+    let mut consecutive = 0;
+    let mut first_bonus: i16 = 0;
+    let mut prev_class = if sidx > 0 {
+        char_class(text[sidx - 1])
+    } else {
+        CharClass::NonWord
+    };
+
+    for (idx, &c) in text.iter().enumerate().take(eidx).skip(sidx) {
+        let class = char_class(c);
+        let c = maybe_fold(case_sensitive, c);
+        if pidx < pattern.len() && c == pattern[pidx] {
+            if let Some(pos) = pos.as_mut() {
+                pos.push(idx);
+            }
+            score += SCORE_MATCH as i32;
+            let mut bonus = bonus_for(prev_class, class);
+            if consecutive == 0 {
+                first_bonus = bonus;
+            } else {
+                if bonus == BONUS_BOUNDARY {
+                    first_bonus = bonus;
+                }
+                bonus = bonus.max(first_bonus).max(BONUS_CONSECUTIVE);
+            }
+            if pidx == 0 {
+                score += (bonus * BONUS_FIRST_CHAR_MULTIPLIER) as i32;
+            } else {
+                score += bonus as i32;
+            }
+            in_gap = false;
+            consecutive += 1;
+            pidx += 1;
+        } else {
+            score += if in_gap {
+                SCORE_GAP_EXTENSION
+            } else {
+                SCORE_GAP_START
+            } as i32;
+            in_gap = true;
+            consecutive = 0;
+            first_bonus = 0;
+        }
+        prev_class = class;
+    }
+    score
+}
+
+/// Quickly rejects text that doesn't contain the pattern as a
+/// subsequence.  Returns the index from which the v2 algorithm
+/// should start scanning: one before the first occurrence of the
+/// first pattern char, so that its bonus is computed correctly.
+fn fuzzy_index(case_sensitive: bool, text: &[char], pattern: &[char]) -> Option<usize> {
+    let mut first_idx = 0;
+    let mut idx = 0;
+    for (pidx, &p) in pattern.iter().enumerate() {
+        let found = text[idx..]
+            .iter()
+            .position(|&c| maybe_fold(case_sensitive, c) == p)?;
+        idx += found;
+        if pidx == 0 && idx > 0 {
+            first_idx = idx - 1;
+        }
+        idx += 1;
+    }
+    Some(first_idx)
+}
+
+fn fuzzy_match_v1(
+    case_sensitive: bool,
+    text: &[char],
+    pattern: &[char],
+    pos: Option<&mut Vec<usize>>,
+) -> Option<TermMatch> {
+    let m = pattern.len();
+    if m == 0 {
+        return Some(TermMatch {
+            start: 0,
+            end: 0,
+            score: 0,
+        });
+    }
+
+    let mut pidx = 0;
+    let mut sidx = None;
+    let mut eidx = None;
+    for (idx, &c) in text.iter().enumerate() {
+        if maybe_fold(case_sensitive, c) == pattern[pidx] {
+            if sidx.is_none() {
+                sidx = Some(idx);
+            }
+            pidx += 1;
+            if pidx == m {
+                eidx = Some(idx + 1);
+                break;
+            }
+        }
+    }
+
+    let (sidx, end) = match (sidx, eidx) {
+        (Some(s), Some(e)) => (s, e),
+        _ => return None,
+    };
+
+    // Walk backwards to find the tightest start position
+    let mut start = sidx;
+    let mut pidx = m;
+    for idx in (sidx..end).rev() {
+        if maybe_fold(case_sensitive, text[idx]) == pattern[pidx - 1] {
+            pidx -= 1;
+            if pidx == 0 {
+                start = idx;
+                break;
+            }
+        }
+    }
+
+    let score = calculate_score(case_sensitive, text, pattern, start, end, pos);
+    Some(TermMatch { start, end, score })
+}
+
+fn fuzzy_match_v2(
+    case_sensitive: bool,
+    text: &[char],
+    pattern: &[char],
+    pos: Option<&mut Vec<usize>>,
+    slab: &mut Slab,
+) -> Option<TermMatch> {
+    let m = pattern.len();
+    let n = text.len();
+    if m == 0 {
+        return Some(TermMatch {
+            start: 0,
+            end: 0,
+            score: 0,
+        });
+    }
+    if n * m > MAX_MATRIX_SIZE {
+        return fuzzy_match_v1(case_sensitive, text, pattern, pos);
+    }
+
+    // Phase 1. Check that the pattern is present as a subsequence
+    let idx = fuzzy_index(case_sensitive, text, pattern)?;
+
+    reset(&mut slab.h0, n);
+    reset(&mut slab.c0, n);
+    reset(&mut slab.bonus, n);
+    reset(&mut slab.first, m);
+    slab.text.clear();
+    slab.text.extend_from_slice(text);
+
+    // Phase 2. Calculate the bonus for each position, and the
+    // first occurrence of each pattern char
+    let mut max_score: i16 = 0;
+    let mut max_score_pos = 0;
+    let mut pidx = 0;
+    let mut last_idx = 0;
+    let pchar0 = pattern[0];
+    let mut pchar = pattern[0];
+    let mut prev_h0: i16 = 0;
+    let mut prev_class = CharClass::NonWord;
+    let mut in_gap = false;
+
+    for col in idx..n {
+        let c = slab.text[col];
+        let class = char_class(c);
+        let c = if !case_sensitive && class == CharClass::Upper {
+            fold_case(c)
+        } else {
+            c
+        };
+        slab.text[col] = c;
+        let bonus = bonus_for(prev_class, class);
+        slab.bonus[col] = bonus;
+        prev_class = class;
+
+        if c == pchar {
+            if pidx < m {
+                slab.first[pidx] = col;
+                pidx += 1;
+                pchar = pattern[pidx.min(m - 1)];
+            }
+            last_idx = col;
+        }
+
+        if c == pchar0 {
+            let score = SCORE_MATCH + bonus * BONUS_FIRST_CHAR_MULTIPLIER;
+            slab.h0[col] = score;
+            slab.c0[col] = 1;
+            if m == 1 && score > max_score {
+                max_score = score;
+                max_score_pos = col;
+                if bonus == BONUS_BOUNDARY {
+                    break;
+                }
+            }
+            in_gap = false;
+        } else {
+            let gap = if in_gap {
+                SCORE_GAP_EXTENSION
+            } else {
+                SCORE_GAP_START
+            };
+            slab.h0[col] = (prev_h0 + gap).max(0);
+            slab.c0[col] = 0;
+            in_gap = true;
+        }
+        prev_h0 = slab.h0[col];
+    }
+
+    if pidx != m {
+        return None;
+    }
+    if m == 1 {
+        if let Some(pos) = pos {
+            pos.push(max_score_pos);
+        }
+        return Some(TermMatch {
+            start: max_score_pos,
+            end: max_score_pos + 1,
+            score: max_score as i32,
+        });
+    }
+
+    // Phase 3. Fill in the score matrix
+    let f0 = slab.first[0];
+    let width = last_idx - f0 + 1;
+    reset(&mut slab.h, width * m);
+    reset(&mut slab.c, width * m);
+    slab.h[..width].copy_from_slice(&slab.h0[f0..=last_idx]);
+    slab.c[..width].copy_from_slice(&slab.c0[f0..=last_idx]);
+
+    for (pidx, &pchar) in pattern.iter().enumerate().skip(1) {
+        let f = slab.first[pidx];
+        let row = pidx * width;
+        let mut in_gap = false;
+        slab.h[row + f - f0 - 1] = 0;
+
+        for col in f..=last_idx {
+            let j = col - f0;
+            let c = slab.text[col];
+            let mut s1: i16 = 0;
+            let mut consecutive: i16 = 0;
+
+            let h_left = slab.h[row + j - 1];
+            let s2 = h_left
+                + if in_gap {
+                    SCORE_GAP_EXTENSION
+                } else {
+                    SCORE_GAP_START
+                };
+
+            if pchar == c {
+                s1 = slab.h[row - width + j - 1] + SCORE_MATCH;
+                let mut b = slab.bonus[col];
+                consecutive = slab.c[row - width + j - 1] + 1;
+                if b == BONUS_BOUNDARY {
+                    consecutive = 1;
+                } else if consecutive > 1 {
+                    b = b.max(
+                        BONUS_CONSECUTIVE.max(slab.bonus[col + 1 - consecutive as usize]),
+                    );
+                }
+                if s1 + b < s2 {
+                    s1 += slab.bonus[col];
+                    consecutive = 0;
+                } else {
+                    s1 += b;
+                }
+            }
+            slab.c[row + j] = consecutive;
+            in_gap = s1 < s2;
+            let score = s1.max(s2).max(0);
+            if pidx == m - 1 && score > max_score {
+                max_score = score;
+                max_score_pos = col;
+            }
+            slab.h[row + j] = score;
+        }
+    }
+
+    // Phase 4. Backtrace to find the matched positions
+    let mut j = max_score_pos;
+    if let Some(pos) = pos {
+        let start_len = pos.len();
+        let mut i = m - 1;
+        let mut prefer_match = true;
+        loop {
+            let ii = i * width;
+            let j0 = j - f0;
+            let s = slab.h[ii + j0];
+
+            let s1 = if i > 0 && j >= slab.first[i] {
+                slab.h[ii - width + j0 - 1]
+            } else {
+                0
+            };
+            let s2 = if j > slab.first[i] {
+                slab.h[ii + j0 - 1]
+            } else {
+                0
+            };
+
+            if s > s1 && (s > s2 || (s == s2 && prefer_match)) {
+                pos.push(j);
+                if i == 0 {
+                    break;
+                }
+                i -= 1;
+            }
+            prefer_match = slab.c[ii + j0] > 1
+                || (ii + width + j0 + 1 < slab.c.len() && slab.c[ii + width + j0 + 1] > 0);
+            j -= 1;
+        }
+        pos[start_len..].reverse();
+    } else {
+        j = f0;
+    }
+
+    Some(TermMatch {
+        start: j,
+        end: max_score_pos + 1,
+        score: max_score as i32,
+    })
+}
+
+fn exact_match_naive(
+    case_sensitive: bool,
+    text: &[char],
+    pattern: &[char],
+    pos: Option<&mut Vec<usize>>,
+) -> Option<TermMatch> {
+    let m = pattern.len();
+    let n = text.len();
+    if m == 0 {
+        return Some(TermMatch {
+            start: 0,
+            end: 0,
+            score: 0,
+        });
+    }
+    if n < m {
+        return None;
+    }
+
+    let mut pidx = 0;
+    let mut best_pos = None;
+    let mut bonus: i16 = 0;
+    let mut best_bonus: i16 = -1;
+    let mut idx = 0;
+    while idx < n {
+        let c = maybe_fold(case_sensitive, text[idx]);
+        if c == pattern[pidx] {
+            if pidx == 0 {
+                bonus = bonus_at(text, idx);
+            }
+            pidx += 1;
+            if pidx == m {
+                if bonus > best_bonus {
+                    best_pos = Some(idx);
+                    best_bonus = bonus;
+                }
+                if bonus == BONUS_BOUNDARY {
+                    break;
+                }
+                idx -= pidx - 1;
+                pidx = 0;
+                bonus = 0;
+            }
+        } else {
+            idx -= pidx;
+            pidx = 0;
+            bonus = 0;
+        }
+        idx += 1;
+    }
+
+    let best_pos = best_pos?;
+    let start = best_pos + 1 - m;
+    let end = best_pos + 1;
+    let score = calculate_score(case_sensitive, text, pattern, start, end, None);
+    if let Some(pos) = pos {
+        pos.extend(start..end);
+    }
+    Some(TermMatch { start, end, score })
+}
+
+fn leading_whitespace(text: &[char]) -> usize {
+    text.iter().take_while(|c| c.is_whitespace()).count()
+}
+
+fn trailing_whitespace(text: &[char]) -> usize {
+    text.iter().rev().take_while(|c| c.is_whitespace()).count()
+}
+
+fn prefix_match(
+    case_sensitive: bool,
+    text: &[char],
+    pattern: &[char],
+    pos: Option<&mut Vec<usize>>,
+) -> Option<TermMatch> {
+    let m = pattern.len();
+    if m == 0 {
+        return Some(TermMatch {
+            start: 0,
+            end: 0,
+            score: 0,
+        });
+    }
+    let trimmed = if pattern[0].is_whitespace() {
+        0
+    } else {
+        leading_whitespace(text)
+    };
+    if text.len() - trimmed < m {
+        return None;
+    }
+    let matched = text[trimmed..trimmed + m]
+        .iter()
+        .zip(pattern.iter())
+        .all(|(&c, &p)| maybe_fold(case_sensitive, c) == p);
+    if !matched {
+        return None;
+    }
+
+    let start = trimmed;
+    let end = trimmed + m;
+    let score = calculate_score(case_sensitive, text, pattern, start, end, None);
+    if let Some(pos) = pos {
+        pos.extend(start..end);
+    }
+    Some(TermMatch { start, end, score })
+}
+
+fn suffix_match(
+    case_sensitive: bool,
+    text: &[char],
+    pattern: &[char],
+    pos: Option<&mut Vec<usize>>,
+) -> Option<TermMatch> {
+    let m = pattern.len();
+    let mut trimmed_len = text.len();
+    if m == 0 || !pattern[m - 1].is_whitespace() {
+        trimmed_len -= trailing_whitespace(text);
+    }
+    if m == 0 {
+        return Some(TermMatch {
+            start: trimmed_len,
+            end: trimmed_len,
+            score: 0,
+        });
+    }
+    if trimmed_len < m {
+        return None;
+    }
+
+    let start = trimmed_len - m;
+    let end = trimmed_len;
+    let matched = text[start..end]
+        .iter()
+        .zip(pattern.iter())
+        .all(|(&c, &p)| maybe_fold(case_sensitive, c) == p);
+    if !matched {
+        return None;
+    }
+
+    let score = calculate_score(case_sensitive, text, pattern, start, end, None);
+    if let Some(pos) = pos {
+        pos.extend(start..end);
+    }
+    Some(TermMatch { start, end, score })
+}
+
+fn equal_match(
+    case_sensitive: bool,
+    text: &[char],
+    pattern: &[char],
+    pos: Option<&mut Vec<usize>>,
+) -> Option<TermMatch> {
+    let m = pattern.len();
+    if m == 0 {
+        return None;
+    }
+
+    let leading = leading_whitespace(text);
+    if leading == text.len() {
+        return None;
+    }
+    let trailing = trailing_whitespace(text);
+    if text.len() - leading - trailing != m {
+        return None;
+    }
+
+    let matched = text[leading..leading + m]
+        .iter()
+        .zip(pattern.iter())
+        .all(|(&c, &p)| maybe_fold(case_sensitive, c) == p);
+    if !matched {
+        return None;
+    }
+
+    let start = leading;
+    let end = leading + m;
+    if let Some(pos) = pos {
+        pos.extend(start..end);
+    }
+    Some(TermMatch {
+        start,
+        end,
+        score: (SCORE_MATCH + BONUS_BOUNDARY) as i32 * m as i32
+            + ((BONUS_FIRST_CHAR_MULTIPLIER - 1) * BONUS_BOUNDARY) as i32,
+    })
+}
@@ -0,0 +1,102 @@
+//! Scoring large candidate sets in parallel.
+//!
+//! The candidates are split into chunks of `CHUNK_SIZE` which are
+//! scored on the rayon thread pool, each with its own `Matcher`.
+//! Cancellation is checked before each chunk is scored, so abandoning
+//! a search that has been superseded costs at most one chunk per
+//! worker thread.
+use crate::{Matcher, Pattern};
+use rayon::prelude::*;
+
+/// The number of candidates that are scored by each task
+pub const CHUNK_SIZE: usize = 8192;
+
+/// Scores `candidates` against `pattern` in parallel.
+/// `text` returns the text of a candidate, or `None` to skip it.
+/// `is_cancelled` is called before each chunk is scored; once it has
+/// returned true the remaining chunks are abandoned and `None` is
+/// returned.
+/// Otherwise, returns the index and score of each matching candidate,
+/// in ascending index order.
+pub fn score_chunks<T, F, C>(
+    pattern: &Pattern,
+    candidates: &[T],
+    text: F,
+    is_cancelled: C,
+) -> Option<Vec<(usize, i32)>>
+where
+    T: Sync,
+    F: Fn(&T) -> Option<&str> + Sync,
+    C: Fn() -> bool + Sync,
+{
+    let chunks: Option<Vec<Vec<(usize, i32)>>> = candidates
+        .par_chunks(CHUNK_SIZE)
+        .enumerate()
+        .map_init(Matcher::new, |matcher, (chunk_idx, chunk)| {
+            if is_cancelled() {
+                return None;
+            }
+            let base = chunk_idx * CHUNK_SIZE;
+            Some(
+                chunk
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(idx, candidate)| {
+                        let text = text(candidate)?;
+                        matcher.score(pattern, text).map(|score| (base + idx, score))
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+    chunks.map(|chunks| chunks.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::CaseMatching;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn scores_all_chunks_in_order() {
+        let lines: Vec<String> = (0..CHUNK_SIZE * 3 + 7).map(|i| format!("line {i}")).collect();
+        let pattern = Pattern::parse("'99", CaseMatching::Smart);
+        let scored = score_chunks(&pattern, &lines, |l| Some(l.as_str()), || false).unwrap();
+
+        let expected: Vec<usize> = (0..lines.len())
+            .filter(|i| i.to_string().contains("99"))
+            .collect();
+        let indices: Vec<usize> = scored.iter().map(|(idx, _)| *idx).collect();
+        assert_eq!(indices, expected);
+    }
+
+    #[test]
+    fn skips_candidates() {
+        let candidates = vec![(true, "foo"), (false, "foo"), (true, "bar"), (true, "foo")];
+        let pattern = Pattern::parse("foo", CaseMatching::Smart);
+        let scored = score_chunks(
+            &pattern,
+            &candidates,
+            |(eligible, text)| eligible.then_some(*text),
+            || false,
+        )
+        .unwrap();
+        let indices: Vec<usize> = scored.iter().map(|(idx, _)| *idx).collect();
+        assert_eq!(indices, vec![0, 3]);
+    }
+
+    #[test]
+    fn cancellation() {
+        let lines: Vec<String> = (0..CHUNK_SIZE * 64).map(|i| format!("line {i}")).collect();
+        let pattern = Pattern::parse("line", CaseMatching::Smart);
+        let checks = AtomicUsize::new(0);
+        let scored = score_chunks(
+            &pattern,
+            &lines,
+            |l| Some(l.as_str()),
+            || checks.fetch_add(1, Ordering::SeqCst) >= 2,
+        );
+        assert!(scored.is_none());
+    }
+}
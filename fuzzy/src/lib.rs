@@ -0,0 +1,453 @@
+//! A fuzzy matcher implementing the fzf "v2" scoring algorithm and
+//! the fzf extended-search syntax.
+//!
+//! A query is parsed into a `Pattern` once, and then a `Matcher`
+//! is used to score candidate strings against it.  The `Matcher`
+//! holds scratch buffers that are re-used between calls, so it
+//! should be kept around while matching a large set of candidates;
+//! use one `Matcher` per thread.
+//!
+//! The extended-search syntax splits the query on spaces into terms,
+//! all of which must match:
+//!
+//! * `foo` - fuzzy match
+//! * `'foo` - exact (substring) match
+//! * `^foo` - prefix match
+//! * `foo$` - suffix match
+//! * `^foo$` - the whole (whitespace trimmed) text must equal `foo`
+//! * `!foo` - inverse exact match: the text must not contain `foo`
+//! * `foo | bar` - matches either `foo` or `bar`
+//!
+//! A literal space can be matched by escaping it as `\ `.
+use algo::{fold_case, Algorithm, Slab};
+
+mod algo;
+mod chunked;
+
+pub use chunked::{score_chunks, CHUNK_SIZE};
+
+/// Controls whether matching is sensitive to case
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseMatching {
+    /// Case insensitive, unless the term contains an uppercase char
+    #[default]
+    Smart,
+    /// Always case insensitive
+    Ignore,
+    /// Always case sensitive
+    Respect,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Term {
+    algorithm: Algorithm,
+    inverse: bool,
+    text: Vec<char>,
+    case_sensitive: bool,
+}
+
+/// A parsed query.
+/// A pattern is a list of term sets; every term set must match,
+/// and a term set matches if any one of its terms matches.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Pattern {
+    term_sets: Vec<Vec<Term>>,
+    only_inverse: bool,
+}
+
+/// The result of successfully matching a `Pattern` against some text
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MatchResult {
+    /// Higher scores are better matches
+    pub score: i32,
+    /// The sorted, de-duplicated char (not byte!) indices of
+    /// the matched chars in the text.
+    /// Only populated by `Matcher::fuzzy_match`.
+    pub positions: Vec<usize>,
+}
+
+impl Pattern {
+    /// Parses `query` using the extended-search syntax with fuzzy
+    /// matching as the default for terms without a prefix
+    pub fn parse(query: &str, case: CaseMatching) -> Self {
+        Self::parse_with_fuzzy(query, case, true)
+    }
+
+    /// Parses `query` using the extended-search syntax.
+    /// When `fuzzy` is false, plain terms are matched exactly and
+    /// the `'` prefix flips them to fuzzy matching instead.
+    pub fn parse_with_fuzzy(query: &str, case: CaseMatching, fuzzy: bool) -> Self {
+        let mut query = query.trim_start_matches(' ');
+        while query.ends_with(' ') && !query.ends_with("\\ ") {
+            query = &query[..query.len() - 1];
+        }
+
+        let mut term_sets = vec![];
+        let mut set: Vec<Term> = vec![];
+        let mut switch_set = false;
+        let mut after_bar = false;
+
+        let query = query.replace("\\ ", "\t");
+        for token in query.split(' ').filter(|t| !t.is_empty()) {
+            let token = token.replace('\t', " ");
+
+            let case_sensitive = match case {
+                CaseMatching::Respect => true,
+                CaseMatching::Ignore => false,
+                CaseMatching::Smart => token.chars().any(char::is_uppercase),
+            };
+            let token = if case_sensitive {
+                token
+            } else {
+                token.chars().map(fold_case).collect()
+            };
+            let mut text = token.as_str();
+
+            if !set.is_empty() && !after_bar && text == "|" {
+                switch_set = false;
+                after_bar = true;
+                continue;
+            }
+            after_bar = false;
+
+            let mut algorithm = if fuzzy {
+                Algorithm::FuzzyV2
+            } else {
+                Algorithm::Exact
+            };
+            let mut inverse = false;
+
+            if let Some(rest) = text.strip_prefix('!') {
+                inverse = true;
+                algorithm = Algorithm::Exact;
+                text = rest;
+            }
+
+            if text != "$" {
+                if let Some(rest) = text.strip_suffix('$') {
+                    algorithm = Algorithm::Suffix;
+                    text = rest;
+                }
+            }
+
+            if let Some(rest) = text.strip_prefix('\'') {
+                algorithm = if fuzzy && !inverse {
+                    Algorithm::Exact
+                } else {
+                    Algorithm::FuzzyV2
+                };
+                text = rest;
+            } else if let Some(rest) = text.strip_prefix('^') {
+                algorithm = if algorithm == Algorithm::Suffix {
+                    Algorithm::Equal
+                } else {
+                    Algorithm::Prefix
+                };
+                text = rest;
+            }
+
+            if text.is_empty() {
+                continue;
+            }
+
+            if switch_set {
+                term_sets.push(std::mem::take(&mut set));
+            }
+            set.push(Term {
+                algorithm,
+                inverse,
+                text: text.chars().collect(),
+                case_sensitive,
+            });
+            switch_set = true;
+        }
+
+        if !set.is_empty() {
+            term_sets.push(set);
+        }
+
+        let only_inverse = term_sets
+            .iter()
+            .all(|set| set.len() == 1 && set[0].inverse);
+
+        Self {
+            term_sets,
+            only_inverse,
+        }
+    }
+
+    /// Returns true if the pattern has no terms, and thus
+    /// matches everything
+    pub fn is_empty(&self) -> bool {
+        self.term_sets.is_empty()
+    }
+}
+
+/// Returns true if every text that matches `query` is known to also
+/// match `previous`, which allows the matches for `query` to be found
+/// by re-scoring just the matches for `previous`.
+/// This is the case when `query` extends `previous` by appending to
+/// its terms or adding new ones.  Inverse terms, alternatives, suffix
+/// anchors and escapes can widen the set of matches as they are typed,
+/// so queries that use them are never considered refinements.
+pub fn is_refinement(previous: &str, query: &str) -> bool {
+    query.starts_with(previous)
+        && !query
+            .chars()
+            .any(|c| matches!(c, '!' | '|' | '$' | '\\'))
+}
+
+/// Matches text against a `Pattern`
+#[derive(Debug, Default)]
+pub struct Matcher {
+    slab: Slab,
+    text: Vec<char>,
+}
+
+impl Matcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the score of `text` if it matches `pattern`,
+    /// without computing the matched positions
+    pub fn score(&mut self, pattern: &Pattern, text: &str) -> Option<i32> {
+        self.match_impl(pattern, text, false).map(|m| m.score)
+    }
+
+    /// Returns the score and matched positions of `text`
+    /// if it matches `pattern`
+    pub fn fuzzy_match(&mut self, pattern: &Pattern, text: &str) -> Option<MatchResult> {
+        self.match_impl(pattern, text, true)
+    }
+
+    fn match_impl(
+        &mut self,
+        pattern: &Pattern,
+        text: &str,
+        with_positions: bool,
+    ) -> Option<MatchResult> {
+        if pattern.is_empty() {
+            return Some(MatchResult::default());
+        }
+
+        self.text.clear();
+        self.text.extend(text.chars());
+        let text = &self.text;
+        let slab = &mut self.slab;
+
+        let mut result = MatchResult::default();
+
+        if pattern.only_inverse {
+            for set in &pattern.term_sets {
+                let term = &set[0];
+                if term
+                    .algorithm
+                    .run(term.case_sensitive, text, &term.text, None, slab)
+                    .is_some()
+                {
+                    return None;
+                }
+            }
+            return Some(result);
+        }
+
+        let mut positions = vec![];
+        for set in &pattern.term_sets {
+            let mut matched = false;
+            let mut set_score = 0;
+            for term in set {
+                let start_len = positions.len();
+                let pos = if with_positions && !term.inverse {
+                    Some(&mut positions)
+                } else {
+                    None
+                };
+                match term
+                    .algorithm
+                    .run(term.case_sensitive, text, &term.text, pos, slab)
+                {
+                    Some(m) => {
+                        if term.inverse {
+                            continue;
+                        }
+                        set_score = m.score;
+                        matched = true;
+                        break;
+                    }
+                    None => {
+                        positions.truncate(start_len);
+                        if term.inverse {
+                            set_score = 0;
+                            matched = true;
+                        }
+                    }
+                }
+            }
+            if !matched {
+                return None;
+            }
+            result.score += set_score;
+        }
+
+        if with_positions {
+            positions.sort_unstable();
+            positions.dedup();
+            result.positions = positions;
+        }
+
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn matches(query: &str, text: &str) -> Option<MatchResult> {
+        let pattern = Pattern::parse(query, CaseMatching::Smart);
+        Matcher::new().fuzzy_match(&pattern, text)
+    }
+
+    fn positions(query: &str, text: &str) -> Option<Vec<usize>> {
+        matches(query, text).map(|m| m.positions)
+    }
+
+    fn score(query: &str, text: &str) -> Option<i32> {
+        let pattern = Pattern::parse(query, CaseMatching::Smart);
+        Matcher::new().score(&pattern, text)
+    }
+
+    #[test]
+    fn refinement() {
+        assert!(is_refinement("", "foo"));
+        assert!(is_refinement("fo", "foo"));
+        assert!(is_refinement("foo", "foo bar"));
+        assert!(is_refinement("'fo", "'foo"));
+        assert!(is_refinement("^", "^foo"));
+        assert!(!is_refinement("foo", "fo"));
+        assert!(!is_refinement("foo", "bar"));
+        assert!(!is_refinement("!fo", "!foo"));
+        assert!(!is_refinement("foo", "foo | bar"));
+        assert!(!is_refinement("foo", "foo$"));
+        assert!(!is_refinement("foo$", "foo$x"));
+        assert!(!is_refinement("foo\\", "foo\\ bar"));
+    }
+
+    #[test]
+    fn empty_pattern_matches_everything() {
+        assert_eq!(score("", "anything"), Some(0));
+        assert_eq!(score("   ", "anything"), Some(0));
+        assert!(Pattern::parse("  ", CaseMatching::Smart).is_empty());
+    }
+
+    #[test]
+    fn fuzzy() {
+        assert_eq!(positions("oBZ", "fooBarbaz1"), None);
+        assert_eq!(positions("obz", "fooBarbaz1"), Some(vec![2, 3, 8]));
+        assert_eq!(positions("fbb", "foo bar baz"), Some(vec![0, 4, 8]));
+        assert_eq!(positions("zzz", "foo bar baz"), None);
+    }
+
+    #[test]
+    fn fuzzy_prefers_word_boundaries() {
+        // The v2 algorithm finds the optimal alignment rather than
+        // the first one; "fb" should match "foo_bar" at the boundary
+        // of "bar" rather than the "b" inside "fob".
+        assert_eq!(positions("fb", "fob foo_bar"), Some(vec![4, 8]));
+        assert!(score("fb", "foo_bar").unwrap() > score("fb", "fxxxxxb").unwrap());
+        assert!(score("ab", "aXb").unwrap() < score("ab", "ab").unwrap());
+    }
+
+    #[test]
+    fn fuzzy_scores() {
+        // Two consecutive matches at the start of the text:
+        // (16 + 8*2) + (16 + 8) with the boundary bonus carried over
+        assert_eq!(score("ab", "ab"), Some(56));
+        // A single char at a word boundary
+        assert_eq!(score("b", "a b"), Some(32));
+        // Gap start penalty between the matches
+        assert_eq!(score("ab", "a-b"), Some(16 + 16 + 16 + 8 - 3));
+    }
+
+    #[test]
+    fn smart_case() {
+        assert!(score("foo", "FOO").is_some());
+        assert!(score("Foo", "foo").is_none());
+        assert!(score("Foo", "Foo").is_some());
+
+        let pattern = Pattern::parse("Foo", CaseMatching::Ignore);
+        assert!(Matcher::new().score(&pattern, "foo").is_some());
+
+        let pattern = Pattern::parse("foo", CaseMatching::Respect);
+        assert!(Matcher::new().score(&pattern, "FOO").is_none());
+    }
+
+    #[test]
+    fn exact() {
+        assert_eq!(positions("'bar", "foo bar"), Some(vec![4, 5, 6]));
+        assert_eq!(positions("'bar", "b a r"), None);
+        // Prefer the occurrence at a word boundary
+        assert_eq!(positions("'bar", "foobar bar"), Some(vec![7, 8, 9]));
+    }
+
+    #[test]
+    fn prefix_and_suffix() {
+        assert_eq!(positions("^foo", "  foobar"), Some(vec![2, 3, 4]));
+        assert_eq!(positions("^foo", "barfoo"), None);
+        assert_eq!(positions("bar$", "foobar  "), Some(vec![3, 4, 5]));
+        assert_eq!(positions("bar$", "barfoo"), None);
+        assert_eq!(positions("^foo$", " foo "), Some(vec![1, 2, 3]));
+        assert_eq!(positions("^foo$", "foox"), None);
+    }
+
+    #[test]
+    fn inverse() {
+        assert!(score("!foo", "bar").is_some());
+        assert!(score("!foo", "foobar").is_none());
+        assert_eq!(positions("bar !foo", "bar baz"), Some(vec![0, 1, 2]));
+        assert_eq!(positions("bar !foo", "bar foo"), None);
+    }
+
+    #[test]
+    fn or_terms() {
+        assert!(score("foo | bar", "xbarx").is_some());
+        assert!(score("foo | bar", "xfoox").is_some());
+        assert!(score("foo | bar", "xbazx").is_none());
+        assert!(score("^x foo | bar", "xbar").is_some());
+        assert!(score("^x foo | bar", "ybar").is_none());
+        // A leading bar is a literal term
+        assert!(score("|", "a|b").is_some());
+    }
+
+    #[test]
+    fn escaped_space() {
+        assert_eq!(positions("'o\\ b", "foo bar"), Some(vec![2, 3, 4]));
+        assert!(score("'o\\ b", "foobar").is_none());
+    }
+
+    #[test]
+    fn multiple_terms_sum_scores() {
+        let a = score("foo", "foo bar").unwrap();
+        let b = score("bar", "foo bar").unwrap();
+        assert_eq!(score("foo bar", "foo bar"), Some(a + b));
+        assert_eq!(positions("bar foo", "foo bar"), Some(vec![0, 1, 2, 4, 5, 6]));
+    }
+
+    #[test]
+    fn positions_are_char_indices() {
+        assert_eq!(positions("über", "ein über"), Some(vec![4, 5, 6, 7]));
+        assert_eq!(positions("ü", "ÜBER"), Some(vec![0]));
+    }
+
+    #[test]
+    fn nul_bytes_are_fine() {
+        assert_eq!(positions("ab", "a\0b"), Some(vec![0, 2]));
+    }
+
+    #[test]
+    fn long_lines_fall_back_to_v1() {
+        let text = format!("{}foo{}", "x".repeat(60_000), "y".repeat(60_000));
+        assert_eq!(positions("fo", &text), Some(vec![60_000, 60_001]));
+    }
+}
@@ -1,10 +1,12 @@
 use super::*;
+use config::keyassignment::ExportFormat;
 use luahelper::{dynamic_to_lua_value, from_lua, to_lua};
 use mlua::Value;
 use mux::pane::CachePolicy;
 use std::cmp::Ordering;
 use std::sync::Arc;
 use termwiz::cell::SemanticType;
+use termwiz_funcs::export::export_lines;
 use termwiz_funcs::lines_to_escapes;
 use url_funcs::Url;
 use wezterm_term::{SemanticZone, StableRowIndex};
@@ -241,6 +243,25 @@ impl UserData for MuxPane {
             Ok(text)
         });
 
+        // Renders the last nlines lines of the terminal output, or the
+        // viewport if nlines is omitted, in the requested format,
+        // preserving their colors, attributes and hyperlinks.
+        methods.add_method(
+            "export_lines",
+            |_, this, (format, nlines): (ExportFormat, Option<usize>)| {
+                let mux = get_mux()?;
+                let pane = this.resolve(&mux)?;
+                let dims = pane.get_dimensions();
+                let nlines = nlines.unwrap_or(dims.viewport_rows);
+                let bottom_row = dims.physical_top + dims.viewport_rows as isize;
+                let top_row = bottom_row.saturating_sub(nlines as isize);
+                let (_first_row, lines) = pane.get_lines(top_row..bottom_row);
+                let text =
+                    export_lines(lines, &pane.palette(), format).map_err(mlua::Error::external)?;
+                Ok(text)
+            },
+        );
+
         methods.add_method(
             "get_logical_lines_as_text",
             |_, this, nlines: Option<usize>| {
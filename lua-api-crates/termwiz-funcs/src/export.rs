@@ -0,0 +1,407 @@
+//! Renders lines from a pane into standalone documents that
+//! preserve their colors, attributes and hyperlinks.
+use crate::lines_to_escapes;
+use config::keyassignment::ExportFormat;
+use std::fmt::Write;
+use termwiz::cell::{CellAttributes, Intensity, Underline};
+use termwiz::color::{ColorAttribute, SrgbaTuple};
+use termwiz::surface::Line;
+use wezterm_term::color::ColorPalette;
+
+/// Dimensions of a cell in the SVG output, in pixels
+const SVG_FONT_SIZE: f32 = 14.;
+const SVG_CELL_WIDTH: f32 = 8.4;
+const SVG_CELL_HEIGHT: f32 = 17.;
+/// Offset of the text baseline from the top of its row
+const SVG_BASELINE: f32 = 13.;
+
+/// Renders `lines` in the requested format, resolving their colors
+/// through `palette`
+pub fn export_lines(
+    lines: Vec<Line>,
+    palette: &ColorPalette,
+    format: ExportFormat,
+) -> anyhow::Result<String> {
+    match format {
+        ExportFormat::Html => Ok(lines_to_html(&lines, palette)),
+        ExportFormat::Svg => Ok(lines_to_svg(&lines, palette)),
+        ExportFormat::Ansi => lines_to_escapes(lines),
+    }
+}
+
+/// A span of cells in a line that share the same attributes
+struct Run {
+    attrs: CellAttributes,
+    text: String,
+    first_col: usize,
+    width: usize,
+}
+
+/// Returns the runs that make up `line`, omitting the blank cells
+/// at the end of the line that would not be visible
+fn line_runs(line: &Line) -> Vec<Run> {
+    let content_end = line
+        .visible_cells()
+        .filter(|cell| {
+            let attrs = cell.attrs();
+            cell.str() != " "
+                || attrs.background() != ColorAttribute::Default
+                || attrs.reverse()
+                || attrs.hyperlink().is_some()
+        })
+        .map(|cell| cell.cell_index() + cell.width())
+        .last()
+        .unwrap_or(0);
+
+    let mut runs: Vec<Run> = vec![];
+    for run in line
+        .cluster(None)
+        .into_iter()
+        .filter(|cluster| cluster.first_cell_idx < content_end)
+        .map(|cluster| {
+            let end = cluster.first_cell_idx + cluster.width;
+            let (text, width) = if end > content_end {
+                // Only blank cells lie beyond the content
+                (
+                    cluster.text.trim_end().to_string(),
+                    content_end - cluster.first_cell_idx,
+                )
+            } else {
+                (cluster.text, cluster.width)
+            };
+            Run {
+                attrs: cluster.attrs,
+                text,
+                first_col: cluster.first_cell_idx,
+                width,
+            }
+        })
+    {
+        // Clusters are broken up for shaping purposes; rejoin those
+        // that share attributes
+        match runs.last_mut() {
+            Some(prior) if prior.attrs == run.attrs => {
+                prior.text.push_str(&run.text);
+                prior.width += run.width;
+            }
+            _ => runs.push(run),
+        }
+    }
+    runs
+}
+
+/// The resolved presentation of a run
+struct RunStyle {
+    fg: SrgbaTuple,
+    bg: SrgbaTuple,
+    underline_color: Option<SrgbaTuple>,
+}
+
+impl RunStyle {
+    fn new(attrs: &CellAttributes, palette: &ColorPalette) -> Self {
+        let mut fg = palette.resolve_fg(attrs.foreground());
+        let mut bg = palette.resolve_bg(attrs.background());
+        if attrs.reverse() {
+            std::mem::swap(&mut fg, &mut bg);
+        }
+        if attrs.intensity() == Intensity::Half {
+            fg = fg.interpolate(bg, 0.5);
+        }
+        if attrs.invisible() {
+            fg = bg;
+        }
+        let underline_color = match attrs.underline_color() {
+            ColorAttribute::Default => None,
+            color => Some(palette.resolve_fg(color)),
+        };
+        Self {
+            fg,
+            bg,
+            underline_color,
+        }
+    }
+}
+
+/// Returns the value for the CSS `text-decoration-line` property,
+/// or None if the text is not decorated
+fn text_decoration_line(attrs: &CellAttributes) -> Option<String> {
+    let mut lines = vec![];
+    if attrs.underline() != Underline::None {
+        lines.push("underline");
+    }
+    if attrs.strikethrough() {
+        lines.push("line-through");
+    }
+    if attrs.overline() {
+        lines.push("overline");
+    }
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join(" "))
+    }
+}
+
+fn text_decoration_style(underline: Underline) -> Option<&'static str> {
+    match underline {
+        Underline::None | Underline::Single => None,
+        Underline::Double => Some("double"),
+        Underline::Curly => Some("wavy"),
+        Underline::Dotted => Some("dotted"),
+        Underline::Dashed => Some("dashed"),
+    }
+}
+
+/// Escapes text for inclusion in the content or attributes of
+/// an HTML or SVG document
+fn escape_xml(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// The schemes of the hyperlinks that are kept in an export
+const EXPORTABLE_SCHEMES: &[&str] = &["http", "https", "file", "mailto"];
+
+/// Returns `uri` if it is safe to make it a link in an exported
+/// document.  The uri comes from the output of the programs running
+/// in the terminal, so anything that the viewer could run as script,
+/// such as a `javascript:` uri, is dropped.
+fn exportable_uri(uri: &str) -> Option<&str> {
+    let (scheme, _) = uri.split_once(':')?;
+    if EXPORTABLE_SCHEMES
+        .iter()
+        .any(|s| s.eq_ignore_ascii_case(scheme))
+    {
+        Some(uri)
+    } else {
+        None
+    }
+}
+
+/// Renders `lines` as a standalone HTML document
+pub fn lines_to_html(lines: &[Line], palette: &ColorPalette) -> String {
+    let fg = palette.foreground.to_rgb_string();
+    let bg = palette.background.to_rgb_string();
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>wezterm</title>\n<style>\n");
+    writeln!(
+        html,
+        "body {{ margin: 0; background-color: {bg}; }}\n\
+         pre {{ margin: 0; padding: 0.5em; font-family: monospace; \
+         color: {fg}; background-color: {bg}; }}\n\
+         a {{ color: inherit; }}"
+    )
+    .ok();
+    html.push_str("</style>\n</head>\n<body>\n<pre>");
+
+    for line in lines {
+        for run in line_runs(line) {
+            let style = RunStyle::new(&run.attrs, palette);
+            let mut css = vec![];
+            if style.fg != palette.foreground {
+                css.push(format!("color: {}", style.fg.to_rgb_string()));
+            }
+            if style.bg != palette.background {
+                css.push(format!("background-color: {}", style.bg.to_rgb_string()));
+            }
+            if run.attrs.intensity() == Intensity::Bold {
+                css.push("font-weight: bold".to_string());
+            }
+            if run.attrs.italic() {
+                css.push("font-style: italic".to_string());
+            }
+            if let Some(decoration) = text_decoration_line(&run.attrs) {
+                css.push(format!("text-decoration-line: {decoration}"));
+                if let Some(style) = text_decoration_style(run.attrs.underline()) {
+                    css.push(format!("text-decoration-style: {style}"));
+                }
+                if let Some(color) = style.underline_color {
+                    css.push(format!("text-decoration-color: {}", color.to_rgb_string()));
+                }
+            }
+
+            let text = escape_xml(&run.text);
+            let text = if css.is_empty() {
+                text
+            } else {
+                format!("<span style=\"{}\">{text}</span>", css.join("; "))
+            };
+            let uri = run
+                .attrs
+                .hyperlink()
+                .and_then(|link| exportable_uri(link.uri()));
+            match uri {
+                Some(uri) => {
+                    write!(html, "<a href=\"{}\">{text}</a>", escape_xml(uri)).ok();
+                }
+                None => html.push_str(&text),
+            }
+        }
+        html.push('\n');
+    }
+
+    html.push_str("</pre>\n</body>\n</html>\n");
+    html
+}
+
+/// Renders `lines` as a standalone SVG image, with each cell laid
+/// out on a fixed grid
+pub fn lines_to_svg(lines: &[Line], palette: &ColorPalette) -> String {
+    let cols = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+    let width = cols as f32 * SVG_CELL_WIDTH;
+    let height = lines.len() as f32 * SVG_CELL_HEIGHT;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\" font-family=\"monospace\" \
+         font-size=\"{SVG_FONT_SIZE}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+        palette.background.to_rgb_string()
+    )
+    .ok();
+
+    for (row, line) in lines.iter().enumerate() {
+        let top = row as f32 * SVG_CELL_HEIGHT;
+        for run in line_runs(line) {
+            let style = RunStyle::new(&run.attrs, palette);
+            let x = run.first_col as f32 * SVG_CELL_WIDTH;
+            let run_width = run.width as f32 * SVG_CELL_WIDTH;
+
+            if style.bg != palette.background {
+                writeln!(
+                    svg,
+                    "<rect x=\"{x}\" y=\"{top}\" width=\"{run_width}\" \
+                     height=\"{SVG_CELL_HEIGHT}\" fill=\"{}\"/>",
+                    style.bg.to_rgb_string()
+                )
+                .ok();
+            }
+
+            if run.text.trim().is_empty() {
+                continue;
+            }
+
+            let mut text_attrs = format!(" fill=\"{}\"", style.fg.to_rgb_string());
+            if run.attrs.intensity() == Intensity::Bold {
+                text_attrs.push_str(" font-weight=\"bold\"");
+            }
+            if run.attrs.italic() {
+                text_attrs.push_str(" font-style=\"italic\"");
+            }
+            if let Some(decoration) = text_decoration_line(&run.attrs) {
+                write!(text_attrs, " text-decoration=\"{decoration}\"").ok();
+            }
+
+            let text = format!(
+                "<text x=\"{x}\" y=\"{}\" textLength=\"{run_width}\" \
+                 lengthAdjust=\"spacingAndGlyphs\" xml:space=\"preserve\"{text_attrs}>{}</text>",
+                top + SVG_BASELINE,
+                escape_xml(&run.text)
+            );
+            let uri = run
+                .attrs
+                .hyperlink()
+                .and_then(|link| exportable_uri(link.uri()));
+            match uri {
+                Some(uri) => {
+                    writeln!(svg, "<a href=\"{}\">{text}</a>", escape_xml(uri)).ok();
+                }
+                None => {
+                    svg.push_str(&text);
+                    svg.push('\n');
+                }
+            }
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+    use termwiz::hyperlink::Hyperlink;
+    use termwiz::surface::SEQ_ZERO;
+
+    #[test]
+    fn html_styles_and_links() {
+        let palette = ColorPalette::default();
+        let mut line = Line::from_text("plain ", &CellAttributes::blank(), SEQ_ZERO, None);
+
+        let mut bold = CellAttributes::blank();
+        bold.set_intensity(Intensity::Bold);
+        line.append_line(Line::from_text("<b>", &bold, SEQ_ZERO, None), SEQ_ZERO);
+
+        let mut link = CellAttributes::blank();
+        link.set_hyperlink(Some(Arc::new(Hyperlink::new("https://example.com/?a&b"))));
+        line.append_line(Line::from_text("link   ", &link, SEQ_ZERO, None), SEQ_ZERO);
+        line.append_line(
+            Line::from_text("    ", &CellAttributes::blank(), SEQ_ZERO, None),
+            SEQ_ZERO,
+        );
+
+        let html = lines_to_html(&[line], &palette);
+        assert!(html.contains(
+            "<pre>plain <span style=\"font-weight: bold\">&lt;b&gt;</span>\
+             <a href=\"https://example.com/?a&amp;b\">link   </a>\n</pre>"
+        ));
+    }
+
+    #[test]
+    fn unsafe_links_are_dropped() {
+        let palette = ColorPalette::default();
+        let mut line = Line::new(SEQ_ZERO);
+        for uri in [
+            "javascript:alert(1)",
+            " javascript:alert(1)",
+            "data:text/html,hi",
+        ] {
+            let mut link = CellAttributes::blank();
+            link.set_hyperlink(Some(Arc::new(Hyperlink::new(uri))));
+            line.append_line(Line::from_text("x", &link, SEQ_ZERO, None), SEQ_ZERO);
+        }
+        let mut link = CellAttributes::blank();
+        link.set_hyperlink(Some(Arc::new(Hyperlink::new("MAILTO:wez@example.com"))));
+        line.append_line(Line::from_text("y", &link, SEQ_ZERO, None), SEQ_ZERO);
+
+        let html = lines_to_html(&[line.clone()], &palette);
+        assert!(html.contains("<pre>xxx<a href=\"MAILTO:wez@example.com\">y</a>\n</pre>"));
+
+        let svg = lines_to_svg(&[line], &palette);
+        assert_eq!(svg.matches("<a href").count(), 1);
+    }
+
+    #[test]
+    fn svg_backgrounds() {
+        let palette = ColorPalette::default();
+        let mut attrs = CellAttributes::blank();
+        attrs.set_reverse(true);
+        let line = Line::from_text("ab", &attrs, SEQ_ZERO, None);
+
+        let svg = lines_to_svg(&[line], &palette);
+        assert!(svg.contains(&format!(
+            "<rect x=\"0\" y=\"0\" width=\"16.8\" height=\"17\" fill=\"{}\"/>",
+            palette.foreground.to_rgb_string()
+        )));
+        assert!(svg.contains(&format!(
+            "fill=\"{}\">ab</text>",
+            palette.background.to_rgb_string()
+        )));
+    }
+}
@@ -11,6 +11,8 @@ use termwiz::surface::change::Change;
 use termwiz::surface::Line;
 use wezterm_dynamic::{FromDynamic, ToDynamic};
 
+pub mod export;
+
 pub fn register(lua: &Lua) -> anyhow::Result<()> {
     let wezterm_mod = get_or_create_module(lua, "wezterm")?;
     wezterm_mod.set("nerdfonts", NerdFonts {})?;
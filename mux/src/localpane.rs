@@ -1,7 +1,7 @@
 use crate::domain::DomainId;
 use crate::pane::{
     CachePolicy, CloseReason, ForEachPaneLogicalLine, LogicalLine, Pane, PaneId, Pattern,
-    SearchResult, WithPaneLines,
+    SearchCapture, SearchResult, WithPaneLines,
 };
 use crate::renderable::*;
 use crate::tmux::{TmuxDomain, TmuxDomainState};
@@ -28,13 +28,14 @@ use termwiz::escape::{Action, DeviceControlMode};
 use termwiz::input::KeyboardEncoding;
 use termwiz::surface::{Line, SequenceNo};
 use url::Url;
-use wezterm_dynamic::Value;
+use wezterm_dynamic::{ToDynamic, Value};
 use wezterm_term::color::ColorPalette;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 use wezterm_term::{
-    Alert, AlertHandler, Clipboard, DownloadHandler, KeyCode, KeyModifiers, MouseEvent,
-    SemanticZone, StableRowIndex, Terminal, TerminalConfiguration, TerminalSize,
+    Alert, AlertHandler, Clipboard, DownloadHandler, KeyCode, KeyModifiers, LineTimestamps,
+    MouseEvent, PointerShape, SemanticZone, StableRowIndex, Terminal, TerminalConfiguration,
+    TerminalSize,
 };
 
 const PROC_INFO_CACHE_TTL: Duration = Duration::from_millis(300);
@@ -144,7 +145,6 @@ impl Pane for LocalPane {
     }
 
     fn get_metadata(&self) -> Value {
-        #[allow(unused_mut)]
         let mut map: BTreeMap<Value, Value> = BTreeMap::new();
 
         #[cfg(unix)]
@@ -162,6 +162,11 @@ impl Pane for LocalPane {
             );
         }
 
+        map.insert(
+            Value::String("progress".to_string()),
+            self.terminal.lock().progress().to_dynamic(),
+        );
+
         Value::Object(map.into())
     }
 
@@ -213,6 +218,14 @@ impl Pane for LocalPane {
         crate::pane::impl_get_logical_lines_via_get_lines(self, lines)
     }
 
+    fn get_line_timestamps(&self, lines: Range<StableRowIndex>) -> LineTimestamps {
+        self.terminal
+            .lock()
+            .screen()
+            .line_timestamps()
+            .subset(lines)
+    }
+
     fn get_dimensions(&self) -> RenderableDimensions {
         terminal_get_dimensions(&mut self.terminal.lock())
     }
@@ -503,6 +516,14 @@ impl Pane for LocalPane {
         }
     }
 
+    fn get_pointer_shape(&self) -> Option<PointerShape> {
+        if self.tmux_domain.lock().is_some() {
+            None
+        } else {
+            self.terminal.lock().pointer_shape()
+        }
+    }
+
     fn get_current_working_dir(&self, policy: CachePolicy) -> Option<Url> {
         self.terminal
             .lock()
@@ -722,6 +743,7 @@ impl Pane for LocalPane {
                         found_match(
                             s,
                             idx,
+                            &[],
                             lines,
                             stable_idx,
                             &mut uniq_matches,
@@ -734,22 +756,27 @@ impl Pane for LocalPane {
                     // Allow for the regex to contain captures
                     for capture_res in re.captures_iter(&haystack) {
                         if let Ok(c) = capture_res {
-                            // Look for the captures in reverse order, as index==0 is
-                            // the whole matched string.  We can't just call
-                            // `c.iter().rev()` as the capture iterator isn't double-ended.
-                            for idx in (0..c.len()).rev() {
-                                if let Some(m) = c.get(idx) {
-                                    found_match(
-                                        m.as_str(),
-                                        m.start(),
-                                        lines,
-                                        stable_idx,
-                                        &mut uniq_matches,
-                                        &mut coords,
-                                        &mut results,
-                                    );
-                                    break;
-                                }
+                            let captures: Vec<Option<Range<usize>>> = if c.len() > 1 {
+                                (0..c.len())
+                                    .map(|idx| c.get(idx).map(|m| m.start()..m.end()))
+                                    .collect()
+                            } else {
+                                vec![]
+                            };
+
+                            // Callers that want a particular group can
+                            // find it in `captures`
+                            if let Some(m) = last_participating_group(&c) {
+                                found_match(
+                                    m.as_str(),
+                                    m.start(),
+                                    &captures,
+                                    lines,
+                                    stable_idx,
+                                    &mut uniq_matches,
+                                    &mut coords,
+                                    &mut results,
+                                );
                             }
                         }
                     }
@@ -770,6 +797,7 @@ impl Pane for LocalPane {
         fn found_match(
             text: &str,
             byte_idx: usize,
+            captures: &[Option<Range<usize>>],
             lines: &[&Line],
             stable_idx: StableRowIndex,
             uniq_matches: &mut HashMap<String, usize>,
@@ -791,12 +819,28 @@ impl Pane for LocalPane {
             };
             let (start_x, start_y) = haystack_idx_to_coord(byte_idx, coords);
             let (end_x, end_y) = haystack_idx_to_coord(byte_idx + text.len(), coords);
+            let captures = captures
+                .iter()
+                .map(|range| {
+                    range.as_ref().map(|range| {
+                        let (start_x, start_y) = haystack_idx_to_coord(range.start, coords);
+                        let (end_x, end_y) = haystack_idx_to_coord(range.end, coords);
+                        SearchCapture {
+                            start_x,
+                            start_y,
+                            end_x,
+                            end_y,
+                        }
+                    })
+                })
+                .collect();
             results.push(SearchResult {
                 start_x,
                 start_y,
                 end_x,
                 end_y,
                 match_id,
+                captures,
             });
         }
 
@@ -1146,6 +1190,14 @@ impl LocalPane {
     }
 }
 
+/// Returns the last capture group that participated in the match.
+/// The captures are looked at in reverse order, as index==0 is
+/// the whole matched string.  We can't just call
+/// `c.iter().rev()` as the capture iterator isn't double-ended.
+fn last_participating_group<'t>(c: &fancy_regex::Captures<'t>) -> Option<fancy_regex::Match<'t>> {
+    (0..c.len()).rev().find_map(|idx| c.get(idx))
+}
+
 impl Drop for LocalPane {
     fn drop(&mut self) {
         // Avoid lingering zombies if we can, but don't block forever.
@@ -1155,3 +1207,35 @@ impl Drop for LocalPane {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn chosen_text(pattern: &str, text: &str) -> String {
+        let re = Regex::new(pattern).unwrap();
+        let c = re.captures(text).unwrap().unwrap();
+        last_participating_group(&c).unwrap().as_str().to_string()
+    }
+
+    #[test]
+    fn search_capture_group() {
+        assert_eq!(
+            chosen_text("[0-9a-f]{7,40}", "commit deadbeef1"),
+            "deadbeef1"
+        );
+        assert_eq!(
+            chosen_text("commit ([0-9a-f]{7,40})", "commit deadbeef1"),
+            "deadbeef1"
+        );
+        // Named groups are chosen the same way as numbered groups
+        assert_eq!(
+            chosen_text(r"(?P<file>[\w./]+):(?P<line>\d+)", "src/main.rs:12"),
+            "12"
+        );
+        assert_eq!(
+            chosen_text(r"(?P<file>[\w./]+):(\d+)(?::(\d+))?", "src/main.rs:12"),
+            "12"
+        );
+    }
+}
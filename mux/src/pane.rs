@@ -10,6 +10,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::ops::Range;
 use std::sync::Arc;
+use std::time::SystemTime;
 use termwiz::hyperlink::Rule;
 use termwiz::input::KeyboardEncoding;
 use termwiz::surface::{Line, SequenceNo};
@@ -17,8 +18,8 @@ use url::Url;
 use wezterm_dynamic::Value;
 use wezterm_term::color::ColorPalette;
 use wezterm_term::{
-    Clipboard, DownloadHandler, KeyCode, KeyModifiers, MouseEvent, SemanticZone, StableRowIndex,
-    TerminalConfiguration, TerminalSize,
+    Clipboard, DownloadHandler, KeyCode, KeyModifiers, LineTimestamps, MouseEvent, PointerShape,
+    SemanticZone, StableRowIndex, TerminalConfiguration, TerminalSize,
 };
 
 static PANE_ID: ::std::sync::atomic::AtomicUsize = ::std::sync::atomic::AtomicUsize::new(0);
@@ -40,7 +41,7 @@ pub enum PerformAssignmentResult {
     BlockAssignmentAndRouteToKeyDown,
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct SearchResult {
     pub start_y: StableRowIndex,
     /// The cell index into the line of the start of the match
@@ -51,6 +52,22 @@ pub struct SearchResult {
     /// An identifier that can be used to group results that have
     /// the same textual content
     pub match_id: usize,
+    /// When searching with a regex that has capture groups, the range
+    /// of each group, indexed by group number, with the entire match
+    /// at index 0.  Groups that did not participate in the match are None.
+    /// Empty for string searches and for regexes without groups.
+    pub captures: Vec<Option<SearchCapture>>,
+}
+
+/// The range of text matched by a capture group in a regex search
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SearchCapture {
+    pub start_y: StableRowIndex,
+    /// The cell index into the line of the start of the group
+    pub start_x: usize,
+    pub end_y: StableRowIndex,
+    /// The cell index into the line of the end of the group
+    pub end_x: usize,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -103,6 +120,8 @@ pub struct LogicalLine {
     pub physical_lines: Vec<Line>,
     pub logical: Line,
     pub first_row: StableRowIndex,
+    /// The time at which the first row of the line arrived, if known
+    pub timestamp: Option<SystemTime>,
 }
 
 impl LogicalLine {
@@ -192,6 +211,11 @@ pub trait Pane: Downcast + Send + Sync {
 
     fn get_logical_lines(&self, lines: Range<StableRowIndex>) -> Vec<LogicalLine>;
 
+    /// Returns the times at which the lines in the specified range arrived
+    fn get_line_timestamps(&self, _lines: Range<StableRowIndex>) -> LineTimestamps {
+        LineTimestamps::default()
+    }
+
     fn apply_hyperlinks(&self, lines: Range<StableRowIndex>, rules: &[Rule]) {
         struct ApplyHyperLinks<'a> {
             rules: &'a [Rule],
@@ -291,6 +315,12 @@ pub trait Pane: Downcast + Send + Sync {
     fn is_mouse_grabbed(&self) -> bool;
     fn is_alt_screen_active(&self) -> bool;
 
+    /// Returns the mouse pointer shape requested by the application,
+    /// or None if the gui should pick one itself
+    fn get_pointer_shape(&self) -> Option<PointerShape> {
+        None
+    }
+
     fn set_clipboard(&self, _clipboard: &Arc<dyn Clipboard>) {}
     fn set_download_handler(&self, _handler: &Arc<dyn DownloadHandler>) {}
     fn set_config(&self, _config: Arc<dyn TerminalConfiguration>) {}
@@ -457,16 +487,20 @@ pub fn impl_get_logical_lines_via_get_lines<P: Pane + ?Sized>(
         phys.append(&mut ahead);
     }
 
+    let timestamps = pane.get_line_timestamps(first..first + phys.len() as StableRowIndex);
+
     // Now process this stuff into logical lines
     let mut lines = vec![];
     for (idx, line) in phys.into_iter().enumerate() {
         match lines.last_mut() {
             None => {
                 let logical = line.clone();
+                let first_row = first + idx as StableRowIndex;
                 lines.push(LogicalLine {
                     physical_lines: vec![line],
                     logical,
-                    first_row: first + idx as StableRowIndex,
+                    first_row,
+                    timestamp: timestamps.get(first_row),
                 });
             }
             Some(prior) => {
@@ -479,10 +513,12 @@ pub fn impl_get_logical_lines_via_get_lines<P: Pane + ?Sized>(
                     prior.physical_lines.push(line);
                 } else {
                     let logical = line.clone();
+                    let first_row = first + idx as StableRowIndex;
                     lines.push(LogicalLine {
                         physical_lines: vec![line],
                         logical,
-                        first_row: first + idx as StableRowIndex,
+                        first_row,
+                        timestamp: timestamps.get(first_row),
                     });
                 }
             }
@@ -1043,6 +1079,7 @@ mod test {
             ],
             logical: Line::from_text("helloyo", &attr, SEQ_ZERO, None),
             first_row: 0,
+            timestamp: None,
         };
 
         assert_eq!(logical.xy_to_logical_x(2, -1), 0);
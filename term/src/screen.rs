@@ -4,9 +4,113 @@ use crate::config::BidiMode;
 use log::debug;
 use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::SystemTime;
 use termwiz::input::KeyboardEncoding;
 use termwiz::surface::SequenceNo;
 
+/// Records the time at which lines arrived in a screen.
+/// Rows are stamped once, in order, as output moves down the screen.
+/// Each stamping covers the whole batch of rows that arrived together,
+/// so the times are held as runs of rows that share a timestamp rather
+/// than as a timestamp per line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineTimestamps {
+    /// The first row of each run and the time shared by its rows,
+    /// ordered by row.  A run extends up to the start of the next
+    /// run, or to `end` for the last run.
+    runs: VecDeque<(StableRowIndex, SystemTime)>,
+    /// The rows before this one have been stamped
+    end: StableRowIndex,
+}
+
+impl LineTimestamps {
+    /// Stamps the rows up to, but not including, `end` that have not
+    /// yet been stamped with `time`
+    pub fn stamp_through(&mut self, end: StableRowIndex, time: SystemTime) {
+        if end <= self.end {
+            return;
+        }
+        match self.runs.back() {
+            Some((_, last)) if *last == time => {}
+            _ => self.runs.push_back((self.end, time)),
+        }
+        self.end = end;
+    }
+
+    /// Returns the time at which `row` arrived
+    pub fn get(&self, row: StableRowIndex) -> Option<SystemTime> {
+        if row >= self.end {
+            return None;
+        }
+        let idx = self.runs.partition_point(|(start, _)| *start <= row);
+        idx.checked_sub(1).map(|idx| self.runs[idx].1)
+    }
+
+    /// Returns the first row that arrived at or after `time`
+    pub fn first_row_at_or_after(&self, time: SystemTime) -> Option<StableRowIndex> {
+        self.runs
+            .iter()
+            .find(|(_, t)| *t >= time)
+            .map(|(row, _)| *row)
+    }
+
+    /// Returns the runs that overlap `range`, with the first of them
+    /// starting no earlier than `range.start`
+    pub fn subset(&self, range: Range<StableRowIndex>) -> Self {
+        let mut result = Self::default();
+        for (idx, (start, time)) in self.runs.iter().enumerate() {
+            let run_end = self
+                .runs
+                .get(idx + 1)
+                .map(|(next, _)| *next)
+                .unwrap_or(self.end);
+            let start = (*start).max(range.start);
+            let run_end = run_end.min(range.end);
+            if start < run_end {
+                result.runs.push_back((start, *time));
+                result.end = run_end;
+            }
+        }
+        result
+    }
+
+    /// Iterates the runs as the range of rows that they cover and
+    /// the time at which those rows arrived
+    pub fn iter(&self) -> impl Iterator<Item = (Range<StableRowIndex>, SystemTime)> + '_ {
+        self.runs
+            .iter()
+            .enumerate()
+            .map(move |(idx, (start, time))| {
+                let end = self
+                    .runs
+                    .get(idx + 1)
+                    .map(|(next, _)| *next)
+                    .unwrap_or(self.end);
+                (*start..end, *time)
+            })
+    }
+
+    /// Discards the runs that lie entirely before `row`
+    fn prune_before(&mut self, row: StableRowIndex) {
+        while self.runs.len() > 1 && self.runs[1].0 <= row {
+            self.runs.pop_front();
+        }
+    }
+
+    /// Forgets the stamps of `row` and the rows that follow it
+    fn truncate(&mut self, row: StableRowIndex) {
+        while self
+            .runs
+            .back()
+            .map(|(start, _)| *start >= row)
+            .unwrap_or(false)
+        {
+            self.runs.pop_back();
+        }
+        self.end = self.end.min(row);
+    }
+}
+
 /// Holds the model of a screen.  This can either be the primary screen
 /// which includes lines of scrollback text, or the alternate screen
 /// which holds no scrollback.  The intent is to have one instance of
@@ -37,6 +141,9 @@ pub struct Screen {
 
     pub(crate) keyboard_stack: Vec<KeyboardEncoding>,
 
+    /// When each of the lines arrived
+    line_timestamps: LineTimestamps,
+
     /// Physical, visible height of the screen (not including scrollback)
     pub physical_rows: usize,
     /// Physical, visible width of the screen
@@ -83,6 +190,7 @@ impl Screen {
             stable_row_index_offset: 0,
             dpi: size.dpi,
             keyboard_stack: vec![],
+            line_timestamps: LineTimestamps::default(),
         }
     }
 
@@ -94,6 +202,22 @@ impl Screen {
         scrollback_size(&self.config, self.allow_scrollback)
     }
 
+    /// Returns the times at which the lines arrived
+    pub fn line_timestamps(&self) -> &LineTimestamps {
+        &self.line_timestamps
+    }
+
+    /// Stamps the lines that have arrived since the last call with `time`.
+    /// The lines above the cursor have arrived, along with the line
+    /// holding the cursor if any text has been written to it.
+    pub fn stamp_line_timestamps(&mut self, cursor: &CursorPosition, time: SystemTime) {
+        let mut end = self.visible_row_to_stable_row(cursor.y);
+        if cursor.x > 0 {
+            end += 1;
+        }
+        self.line_timestamps.stamp_through(end, time);
+    }
+
     fn rewrap_lines(
         &mut self,
         physical_cols: usize,
@@ -106,6 +230,14 @@ impl Screen {
         let mut logical_line: Option<Line> = None;
         let mut logical_cursor_x: Option<usize> = None;
         let mut adjusted_cursor = (cursor_x, cursor_y);
+        // The stable row at which the current logical line started
+        // prior to rewrapping, used to carry over its timestamp
+        let offset = self.stable_row_index_offset as StableRowIndex;
+        let mut logical_start = offset;
+        let mut line_timestamps = LineTimestamps {
+            runs: VecDeque::new(),
+            end: offset,
+        };
 
         for (phys_idx, mut line) in self.lines.drain(..).enumerate() {
             line.update_last_change_seqno(seqno);
@@ -120,6 +252,7 @@ impl Screen {
                     if phys_idx == cursor_y {
                         logical_cursor_x = Some(cursor_x);
                     }
+                    logical_start = offset + phys_idx as StableRowIndex;
                     line
                 }
                 Some(mut prior) => {
@@ -168,8 +301,14 @@ impl Screen {
                     rewrapped.push_back(line);
                 }
             }
+            if let Some(time) = self.line_timestamps.get(logical_start) {
+                // Runs are contiguous, so this is equivalent to stamping
+                // from rewrapped_start
+                line_timestamps.stamp_through(offset + rewrapped.len() as StableRowIndex, time);
+            }
         }
         self.lines = rewrapped;
+        self.line_timestamps = line_timestamps;
 
         // If we resized narrower and generated additional lines,
         // we may need to scroll the lines to make room.  However,
@@ -311,6 +450,11 @@ impl Screen {
                 - (self.lines.len() as VisibleRowIndex - physical_rows as VisibleRowIndex);
         }
 
+        // Rows that were pruned from the bottom may be replaced by
+        // new rows, which must be stamped when they arrive
+        let end = self.phys_to_stable_row_index(self.lines.len());
+        self.line_timestamps.truncate(end);
+
         self.physical_rows = physical_rows;
         self.physical_cols = physical_cols;
         CursorPosition {
@@ -726,6 +870,8 @@ impl Screen {
 
         if remove_idx == 0 {
             self.stable_row_index_offset += lines_removed;
+            self.line_timestamps
+                .prune_before(self.stable_row_index_offset as StableRowIndex);
         }
 
         // It's cheaper to push() than it is insert() at the end
@@ -756,6 +902,8 @@ impl Screen {
             self.lines.pop_front();
             self.stable_row_index_offset += 1;
         }
+        self.line_timestamps
+            .prune_before(self.stable_row_index_offset as StableRowIndex);
     }
 
     /// ```text
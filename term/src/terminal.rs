@@ -1,6 +1,7 @@
 use super::*;
 use crate::terminalstate::performer::Performer;
 use std::sync::Arc;
+use termwiz::escape::osc::Progress;
 use termwiz::escape::parser::Parser;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -56,6 +57,8 @@ pub enum Alert {
         name: String,
         value: String,
     },
+    /// The progress reported via `OSC 9;4` has changed
+    Progress(Progress),
     /// When something bumps the seqno in the terminal model and
     /// the terminal is not focused
     OutputSinceFocusLost,
@@ -158,6 +161,7 @@ impl Terminal {
 
             self.parser.parse(bytes, |action| performer.perform(action));
         }
+        self.state.stamp_line_timestamps();
         self.trigger_unseen_output_notif();
     }
 
@@ -169,6 +173,7 @@ impl Terminal {
                 performer.perform(action);
             }
         }
+        self.state.stamp_line_timestamps();
         self.trigger_unseen_output_notif();
     }
 }
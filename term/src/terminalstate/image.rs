@@ -54,6 +54,30 @@ pub struct ImageAttachParams {
     pub data: Arc<ImageData>,
 }
 
+/// A kitty image placement that is not attached to the cells at
+/// the cursor, but is instead displayed wherever Unicode placeholder
+/// cells that refer to it appear.
+#[derive(Debug, Clone)]
+pub struct VirtualPlacement {
+    /// Dimensions of the underlying ImageData, in pixels
+    pub image_width: u32,
+    pub image_height: u32,
+
+    /// The area of the image to be displayed, in pixels
+    pub source_origin_x: u32,
+    pub source_origin_y: u32,
+    pub source_width: u32,
+    pub source_height: u32,
+
+    /// The number of cells spanned by the placement.  The image is
+    /// scaled to fit within them, preserving its aspect ratio.
+    pub columns: usize,
+    pub rows: usize,
+
+    pub z_index: i32,
+    pub data: Arc<ImageData>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ImageAttachStyle {
     Sixel,
@@ -253,6 +277,82 @@ impl TerminalState {
         })
     }
 
+    /// Computes the portion of a virtual placement that is displayed
+    /// in the cell at `row` and `col` of the placement.  Returns None
+    /// if the image doesn't cover that cell, which happens when the
+    /// cell lies outside the placement, or in the margin left over
+    /// after the image is centered within it.
+    pub(crate) fn placeholder_image_cell(
+        &self,
+        placement: &VirtualPlacement,
+        image_id: u32,
+        placement_id: Option<u32>,
+        row: usize,
+        col: usize,
+    ) -> Option<ImageCell> {
+        let cell_pixel_width = (self.pixel_width / self.screen().physical_cols) as f32;
+        let cell_pixel_height = (self.pixel_height / self.screen().physical_rows) as f32;
+        if row >= placement.rows
+            || col >= placement.columns
+            || placement.source_width == 0
+            || placement.source_height == 0
+            || cell_pixel_width < 1.
+            || cell_pixel_height < 1.
+        {
+            return None;
+        }
+
+        // Fit the source area into the placement and center it
+        let box_width = placement.columns as f32 * cell_pixel_width;
+        let box_height = placement.rows as f32 * cell_pixel_height;
+        let scale = (box_width / placement.source_width as f32)
+            .min(box_height / placement.source_height as f32);
+        let draw_width = placement.source_width as f32 * scale;
+        let draw_height = placement.source_height as f32 * scale;
+        let offset_x = (box_width - draw_width) / 2.;
+        let offset_y = (box_height - draw_height) / 2.;
+
+        // The part of the cell that the image covers, in pixels
+        // relative to the top left of the placement
+        let cell_left = col as f32 * cell_pixel_width;
+        let cell_top = row as f32 * cell_pixel_height;
+        let left = cell_left.max(offset_x);
+        let right = (cell_left + cell_pixel_width).min(offset_x + draw_width);
+        let top = cell_top.max(offset_y);
+        let bottom = (cell_top + cell_pixel_height).min(offset_y + draw_height);
+        if left >= right || top >= bottom {
+            return None;
+        }
+
+        let texture_x = |x: f32| {
+            NotNan::new(
+                (placement.source_origin_x as f32 + (x - offset_x) / scale)
+                    / placement.image_width as f32,
+            )
+            .ok()
+        };
+        let texture_y = |y: f32| {
+            NotNan::new(
+                (placement.source_origin_y as f32 + (y - offset_y) / scale)
+                    / placement.image_height as f32,
+            )
+            .ok()
+        };
+
+        Some(ImageCell::with_z_index(
+            TextureCoordinate::new(texture_x(left)?, texture_y(top)?),
+            TextureCoordinate::new(texture_x(right)?, texture_y(bottom)?),
+            placement.data.clone(),
+            placement.z_index,
+            (left - cell_left).round() as u16,
+            (top - cell_top).round() as u16,
+            (cell_left + cell_pixel_width - right).round() as u16,
+            (cell_top + cell_pixel_height - bottom).round() as u16,
+            Some(image_id),
+            placement_id,
+        ))
+    }
+
     /// cache recent images and avoid assigning a new id for repeated data!
     pub(crate) fn raw_image_to_image_data(
         &mut self,
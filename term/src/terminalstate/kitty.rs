@@ -1,6 +1,6 @@
 use crate::terminalstate::image::*;
 use crate::terminalstate::{ImageAttachParams, PlacementInfo};
-use crate::{StableRowIndex, TerminalState};
+use crate::{StableRowIndex, TerminalState, VisibleRowIndex};
 use ::image::{
     DynamicImage, GenericImage, GenericImageView, ImageBuffer, RgbImage, Rgba, RgbaImage,
 };
@@ -9,6 +9,8 @@ use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::sync::Arc;
 use std::time::Duration;
+use termwiz::cell::CellAttributes;
+use termwiz::color::ColorAttribute;
 use termwiz::escape::apc::{
     KittyFrameCompositionMode, KittyImage, KittyImageCompression, KittyImageData, KittyImageDelete,
     KittyImageFormat, KittyImageFrame, KittyImageFrameCompose, KittyImagePlacement,
@@ -17,6 +19,119 @@ use termwiz::escape::apc::{
 use termwiz::image::ImageDataType;
 use termwiz::surface::change::ImageData;
 
+/// The character that marks a cell as a placeholder for part of a
+/// virtual placement
+pub(crate) const KITTY_PLACEHOLDER: char = '\u{10EEEE}';
+
+/// The combining diacritics that encode the row, the column and the
+/// most significant byte of the image id of a placeholder cell.  Each
+/// encodes its position in this list.
+const PLACEHOLDER_DIACRITICS: &str =
+    "\u{0305}\u{030D}\u{030E}\u{0310}\u{0312}\u{033D}\u{033E}\u{033F}\u{0346}\u{034A}\u{034B}\
+     \u{034C}\u{0350}\u{0351}\u{0352}\u{0357}\u{035B}\u{0363}\u{0364}\u{0365}\u{0366}\u{0367}\
+     \u{0368}\u{0369}\u{036A}\u{036B}\u{036C}\u{036D}\u{036E}\u{036F}\u{0483}\u{0484}\u{0485}\
+     \u{0486}\u{0487}\u{0592}\u{0593}\u{0594}\u{0595}\u{0597}\u{0598}\u{0599}\u{059C}\u{059D}\
+     \u{059E}\u{059F}\u{05A0}\u{05A1}\u{05A8}\u{05A9}\u{05AB}\u{05AC}\u{05AF}\u{05C4}\u{0610}\
+     \u{0611}\u{0612}\u{0613}\u{0614}\u{0615}\u{0616}\u{0617}\u{0657}\u{0658}\u{0659}\u{065A}\
+     \u{065B}\u{065D}\u{065E}\u{06D6}\u{06D7}\u{06D8}\u{06D9}\u{06DA}\u{06DB}\u{06DC}\u{06DF}\
+     \u{06E0}\u{06E1}\u{06E2}\u{06E4}\u{06E7}\u{06E8}\u{06EB}\u{06EC}\u{0730}\u{0732}\u{0733}\
+     \u{0735}\u{0736}\u{073A}\u{073D}\u{073F}\u{0740}\u{0741}\u{0743}\u{0745}\u{0747}\u{0749}\
+     \u{074A}\u{07EB}\u{07EC}\u{07ED}\u{07EE}\u{07EF}\u{07F0}\u{07F1}\u{07F3}\u{0816}\u{0817}\
+     \u{0818}\u{0819}\u{081B}\u{081C}\u{081D}\u{081E}\u{081F}\u{0820}\u{0821}\u{0822}\u{0823}\
+     \u{0825}\u{0826}\u{0827}\u{0829}\u{082A}\u{082B}\u{082C}\u{082D}\u{0951}\u{0953}\u{0954}\
+     \u{0F82}\u{0F83}\u{0F86}\u{0F87}\u{135D}\u{135E}\u{135F}\u{17DD}\u{193A}\u{1A17}\u{1A75}\
+     \u{1A76}\u{1A77}\u{1A78}\u{1A79}\u{1A7A}\u{1A7B}\u{1A7C}\u{1B6B}\u{1B6D}\u{1B6E}\u{1B6F}\
+     \u{1B70}\u{1B71}\u{1B72}\u{1B73}\u{1CD0}\u{1CD1}\u{1CD2}\u{1CDA}\u{1CDB}\u{1CE0}\u{1DC0}\
+     \u{1DC1}\u{1DC3}\u{1DC4}\u{1DC5}\u{1DC6}\u{1DC7}\u{1DC8}\u{1DC9}\u{1DCB}\u{1DCC}\u{1DD1}\
+     \u{1DD2}\u{1DD3}\u{1DD4}\u{1DD5}\u{1DD6}\u{1DD7}\u{1DD8}\u{1DD9}\u{1DDA}\u{1DDB}\u{1DDC}\
+     \u{1DDD}\u{1DDE}\u{1DDF}\u{1DE0}\u{1DE1}\u{1DE2}\u{1DE3}\u{1DE4}\u{1DE5}\u{1DE6}\u{1DFE}\
+     \u{20D0}\u{20D1}\u{20D4}\u{20D5}\u{20D6}\u{20D7}\u{20DB}\u{20DC}\u{20E1}\u{20E7}\u{20E9}\
+     \u{20F0}\u{2CEF}\u{2CF0}\u{2CF1}\u{2DE0}\u{2DE1}\u{2DE2}\u{2DE3}\u{2DE4}\u{2DE5}\u{2DE6}\
+     \u{2DE7}\u{2DE8}\u{2DE9}\u{2DEA}\u{2DEB}\u{2DEC}\u{2DED}\u{2DEE}\u{2DEF}\u{2DF0}\u{2DF1}\
+     \u{2DF2}\u{2DF3}\u{2DF4}\u{2DF5}\u{2DF6}\u{2DF7}\u{2DF8}\u{2DF9}\u{2DFA}\u{2DFB}\u{2DFC}\
+     \u{2DFD}\u{2DFE}\u{2DFF}\u{A66F}\u{A67C}\u{A67D}\u{A6F0}\u{A6F1}\u{A8E0}\u{A8E1}\u{A8E2}\
+     \u{A8E3}\u{A8E4}\u{A8E5}\u{A8E6}\u{A8E7}\u{A8E8}\u{A8E9}\u{A8EA}\u{A8EB}\u{A8EC}\u{A8ED}\
+     \u{A8EE}\u{A8EF}\u{A8F0}\u{A8F1}\u{AAB0}\u{AAB2}\u{AAB3}\u{AAB7}\u{AAB8}\u{AABE}\u{AABF}\
+     \u{AAC1}\u{FE20}\u{FE21}\u{FE22}\u{FE23}\u{FE24}\u{FE25}\u{FE26}\u{10A0F}\u{10A38}\
+     \u{1D185}\u{1D186}\u{1D187}\u{1D188}\u{1D189}\u{1D1AA}\u{1D1AB}\u{1D1AC}\u{1D1AD}\u{1D242}\
+     \u{1D243}\u{1D244}";
+
+/// The part of a virtual placement that a placeholder cell displays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PlaceholderCell {
+    pub image_id: u32,
+    /// 0 if the placeholder doesn't specify a placement
+    pub placement_id: u32,
+    pub row: u32,
+    pub col: u32,
+}
+
+impl PlaceholderCell {
+    /// Decodes the placeholder in a cell with the text `text`.
+    /// The image id is encoded in the foreground color and the
+    /// placement id in the underline color.  Diacritics that are
+    /// omitted are inferred from `prior`, the placeholder in the cell
+    /// immediately to the left, if it refers to the same placement.
+    pub fn decode(text: &str, attrs: &CellAttributes, prior: Option<&Self>) -> Option<Self> {
+        let mut chars = text.chars();
+        if chars.next()? != KITTY_PLACEHOLDER {
+            return None;
+        }
+        let mut diacritics = chars.map_while(|c| {
+            PLACEHOLDER_DIACRITICS
+                .chars()
+                .position(|d| d == c)
+                .map(|idx| idx as u32)
+        });
+        let row = diacritics.next();
+        let col = diacritics.next();
+        let msb = diacritics.next();
+
+        let image_id = color_to_id(attrs.foreground());
+        let placement_id = color_to_id(attrs.underline_color());
+        let prior =
+            prior.filter(|p| p.image_id & 0xff_ffff == image_id && p.placement_id == placement_id);
+        let prior_msb = |p: &Self| p.image_id >> 24;
+
+        let (row, col, msb) = match (row, col, msb) {
+            (None, _, _) => match prior {
+                Some(p) => (p.row, p.col + 1, prior_msb(p)),
+                None => (0, 0, 0),
+            },
+            (Some(row), None, _) => match prior {
+                Some(p) if p.row == row => (row, p.col + 1, prior_msb(p)),
+                _ => (row, 0, 0),
+            },
+            (Some(row), Some(col), None) => match prior {
+                Some(p) if p.row == row && p.col + 1 == col => (row, col, prior_msb(p)),
+                _ => (row, col, 0),
+            },
+            (Some(row), Some(col), Some(msb)) => (row, col, msb),
+        };
+
+        Some(Self {
+            image_id: (msb << 24) | image_id,
+            placement_id,
+            row,
+            col,
+        })
+    }
+}
+
+/// Returns the 24-bit id encoded in a color, which is either the
+/// palette index or the RGB value
+fn color_to_id(color: ColorAttribute) -> u32 {
+    match color {
+        ColorAttribute::Default => 0,
+        ColorAttribute::PaletteIndex(idx) => idx as u32,
+        ColorAttribute::TrueColorWithPaletteFallback(color, _)
+        | ColorAttribute::TrueColorWithDefaultFallback(color) => {
+            let (r, g, b, _) = color.to_srgb_u8();
+            (r as u32) << 16 | (g as u32) << 8 | b as u32
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct KittyImageState {
     accumulator: Vec<KittyImage>,
@@ -24,6 +139,10 @@ pub struct KittyImageState {
     number_to_id: HashMap<u32, u32>,
     id_to_data: HashMap<u32, Arc<ImageData>>,
     placements: HashMap<(u32, Option<u32>), PlacementInfo>,
+    virtual_placements: HashMap<(u32, Option<u32>), VirtualPlacement>,
+    /// The most recently printed placeholder and its position, from
+    /// which the diacritics omitted from the next one are inferred
+    last_placeholder: Option<(usize, VisibleRowIndex, PlaceholderCell)>,
     used_memory: usize,
 }
 
@@ -46,7 +165,12 @@ impl KittyImageState {
     fn prune_unreferenced(&mut self) {
         let budget = 320 * 1024 * 1024; // FIXME: make this configurable
         if self.used_memory > budget {
-            let referenced: HashSet<u32> = self.placements.keys().map(|(k, _)| *k).collect();
+            let referenced: HashSet<u32> = self
+                .placements
+                .keys()
+                .chain(self.virtual_placements.keys())
+                .map(|(k, _)| *k)
+                .collect();
             let target = self.used_memory - budget;
             let mut freed = 0;
             self.id_to_data.retain(|id, data| {
@@ -113,6 +237,22 @@ impl TerminalState {
 
         let (image_width, image_height) = img.data().dimensions()?;
 
+        if placement.virtual_placement {
+            let placement_id = placement.placement_id;
+            let placement = self.kitty_virtual_placement(image_width, image_height, placement, img);
+            self.kitty_img
+                .virtual_placements
+                .insert((image_id, placement_id), placement);
+            log::trace!(
+                "record virtual placement for {} (image_number {:?}) {:?}",
+                image_id,
+                image_number,
+                placement_id
+            );
+            self.kitty_refresh_placeholders();
+            return Ok(());
+        }
+
         let info = self.assign_image_to_cells(ImageAttachParams {
             image_width,
             image_height,
@@ -145,6 +285,152 @@ impl TerminalState {
         Ok(())
     }
 
+    /// Resolves the area of the image to display and the number of
+    /// cells that a virtual placement spans
+    fn kitty_virtual_placement(
+        &self,
+        image_width: u32,
+        image_height: u32,
+        placement: KittyImagePlacement,
+        data: Arc<ImageData>,
+    ) -> VirtualPlacement {
+        let source_origin_x = placement.x.unwrap_or(0).min(image_width);
+        let source_origin_y = placement.y.unwrap_or(0).min(image_height);
+        let max_width = image_width - source_origin_x;
+        let max_height = image_height - source_origin_y;
+        let source_width = placement.w.unwrap_or(max_width).min(max_width);
+        let source_height = placement.h.unwrap_or(max_height).min(max_height);
+
+        let cell_pixel_width = (self.pixel_width / self.screen().physical_cols).max(1) as f32;
+        let cell_pixel_height = (self.pixel_height / self.screen().physical_rows).max(1) as f32;
+        // When only one of the dimensions is specified, the other is
+        // chosen to preserve the aspect ratio of the image
+        let aspect = source_width as f32 / source_height.max(1) as f32;
+        let (columns, rows) = match (placement.columns, placement.rows) {
+            (Some(columns), Some(rows)) => (columns as f32, rows as f32),
+            (Some(columns), None) => (
+                columns as f32,
+                (columns as f32 * cell_pixel_width / aspect / cell_pixel_height).ceil(),
+            ),
+            (None, Some(rows)) => (
+                (rows as f32 * cell_pixel_height * aspect / cell_pixel_width).ceil(),
+                rows as f32,
+            ),
+            (None, None) => (
+                (source_width as f32 / cell_pixel_width).ceil(),
+                (source_height as f32 / cell_pixel_height).ceil(),
+            ),
+        };
+
+        VirtualPlacement {
+            image_width,
+            image_height,
+            source_origin_x,
+            source_origin_y,
+            source_width,
+            source_height,
+            columns: columns.max(1.) as usize,
+            rows: rows.max(1.) as usize,
+            z_index: placement.z_index.unwrap_or(0),
+            data,
+        }
+    }
+
+    /// Returns the virtual placement of `image_id` that a placeholder
+    /// refers to, along with its placement id.  A placeholder that
+    /// doesn't specify a placement refers to any placement of the image.
+    fn kitty_find_virtual_placement(
+        &self,
+        image_id: u32,
+        placement_id: u32,
+    ) -> Option<(Option<u32>, &VirtualPlacement)> {
+        if placement_id != 0 {
+            let key = (image_id, Some(placement_id));
+            return self
+                .kitty_img
+                .virtual_placements
+                .get(&key)
+                .map(|placement| (key.1, placement));
+        }
+        self.kitty_img
+            .virtual_placements
+            .iter()
+            .filter(|((id, _), _)| *id == image_id)
+            .min_by_key(|((_, p), _)| *p)
+            .map(|((_, p), placement)| (*p, placement))
+    }
+
+    /// Called after a placeholder character has been printed at
+    /// `x`, `y` to attach the image that it represents
+    pub(crate) fn kitty_placeholder_printed(&mut self, x: usize, y: VisibleRowIndex) {
+        let prior = match self.kitty_img.last_placeholder {
+            Some((prior_x, prior_y, prior)) if prior_y == y && prior_x + 1 == x => Some(prior),
+            _ => None,
+        };
+        self.kitty_img.last_placeholder = self
+            .kitty_attach_placeholder(x, y, prior.as_ref())
+            .map(|placeholder| (x, y, placeholder));
+    }
+
+    /// Attaches the image to the placeholder cell at `x`, `y`, or
+    /// removes it if the cell refers to a placement that doesn't
+    /// exist.  Returns None if the cell isn't a placeholder.
+    fn kitty_attach_placeholder(
+        &mut self,
+        x: usize,
+        y: VisibleRowIndex,
+        prior: Option<&PlaceholderCell>,
+    ) -> Option<PlaceholderCell> {
+        let seqno = self.seqno;
+        let mut cell = self.screen_mut().get_cell(x, y)?.clone();
+        let placeholder = PlaceholderCell::decode(cell.str(), cell.attrs(), prior)?;
+
+        let image = self
+            .kitty_find_virtual_placement(placeholder.image_id, placeholder.placement_id)
+            .and_then(|(placement_id, placement)| {
+                self.placeholder_image_cell(
+                    placement,
+                    placeholder.image_id,
+                    placement_id,
+                    placeholder.row as usize,
+                    placeholder.col as usize,
+                )
+            });
+        match image {
+            Some(image) => cell.attrs_mut().set_image(Box::new(image)),
+            None => cell.attrs_mut().clear_images(),
+        };
+        // The placeholder character is a stand-in for the image;
+        // it isn't meant to be seen
+        cell.attrs_mut().set_invisible(true);
+
+        self.screen_mut().set_cell(x, y, &cell, seqno);
+        Some(placeholder)
+    }
+
+    /// Re-attaches images to the placeholder cells on the screen
+    /// after the virtual placements have changed.  Placeholders that
+    /// have scrolled into the scrollback keep their current image.
+    fn kitty_refresh_placeholders(&mut self) {
+        let physical_rows = self.screen().physical_rows as VisibleRowIndex;
+        let physical_cols = self.screen().physical_cols;
+        for y in 0..physical_rows {
+            let mut prior = None;
+            for x in 0..physical_cols {
+                let is_placeholder = self
+                    .screen_mut()
+                    .get_cell(x, y)
+                    .map(|cell| cell.str().starts_with(KITTY_PLACEHOLDER))
+                    .unwrap_or(false);
+                prior = if is_placeholder {
+                    self.kitty_attach_placeholder(x, y, prior.as_ref())
+                } else {
+                    None
+                };
+            }
+        }
+    }
+
     fn kitty_img_inner(&mut self, img: KittyImage) -> anyhow::Result<()> {
         match self
             .coalesce_kitty_accumulation(img)
@@ -310,6 +596,14 @@ impl TerminalState {
     }
 
     fn kitty_remove_placement(&mut self, image_id: u32, placement_id: Option<u32>) {
+        let num_virtual = self.kitty_img.virtual_placements.len();
+        self.kitty_img
+            .virtual_placements
+            .retain(|(id, p), _| *id != image_id || (placement_id.is_some() && *p != placement_id));
+        if self.kitty_img.virtual_placements.len() != num_virtual {
+            self.kitty_refresh_placeholders();
+        }
+
         if placement_id.is_some() {
             if let Some(info) = self.kitty_img.placements.remove(&(image_id, placement_id)) {
                 log::trace!("removed placement {} {:?}", image_id, placement_id);
@@ -341,6 +635,10 @@ impl TerminalState {
         for ((image_id, p), info) in std::mem::take(&mut self.kitty_img.placements).into_iter() {
             self.kitty_remove_placement_from_model(image_id, p, info);
         }
+        if !self.kitty_img.virtual_placements.is_empty() {
+            self.kitty_img.virtual_placements.clear();
+            self.kitty_refresh_placeholders();
+        }
         if delete {
             self.kitty_img.id_to_data.clear();
             self.kitty_img.used_memory = 0;
@@ -13,10 +13,12 @@ use std::sync::Arc;
 use terminfo::{Database, Value};
 use termwiz::cell::UnicodeVersion;
 use termwiz::escape::csi::{
-    Cursor, CursorStyle, DecPrivateMode, DecPrivateModeCode, Device, Edit, EraseInDisplay,
-    EraseInLine, Mode, Sgr, TabulationClear, TerminalMode, TerminalModeCode, Window, XtSmGraphics,
-    XtSmGraphicsAction, XtSmGraphicsItem, XtSmGraphicsStatus, XtermKeyModifierResource,
+    AttributeChangeExtent, Cursor, CursorStyle, DecPrivateMode, DecPrivateModeCode, Device, Edit,
+    EraseInDisplay, EraseInLine, Mode, Sgr, TabulationClear, TerminalMode, TerminalModeCode,
+    Window, XtSmGraphics, XtSmGraphicsAction, XtSmGraphicsItem, XtSmGraphicsStatus,
+    XtermKeyModifierResource,
 };
+use termwiz::escape::osc::Progress;
 use termwiz::escape::{OneBased, OperatingSystemCommand, CSI};
 use termwiz::image::ImageData;
 use termwiz::input::KeyboardEncoding;
@@ -30,9 +32,13 @@ mod keyboard;
 mod kitty;
 mod mouse;
 pub(crate) mod performer;
+mod pointer;
+mod rectangle;
 mod sixel;
+mod textsize;
 use crate::terminalstate::image::*;
 use crate::terminalstate::kitty::*;
+pub use crate::terminalstate::pointer::PointerShape;
 
 lazy_static::lazy_static! {
     static ref DB: Database = {
@@ -137,7 +143,7 @@ struct SavedCursor {
     dec_origin_mode: bool,
     g0_charset: CharSet,
     g1_charset: CharSet,
-    // TODO: selective_erase when supported
+    // The DECSCA protection attribute is saved as part of pen
 }
 
 struct ScreenOrAlt {
@@ -362,6 +368,17 @@ pub struct TerminalState {
 
     user_vars: HashMap<String, String>,
 
+    /// The progress reported by the application via `OSC 9;4`
+    progress: Progress,
+
+    /// The stack of pointer shapes requested via `OSC 22`, with the
+    /// current shape at the top
+    pointer_shapes: Vec<PointerShape>,
+
+    /// Set by DECSACE; whether DECCARA changes the attributes of a
+    /// stream of characters or of a rectangle
+    attribute_change_extent: AttributeChangeExtent,
+
     kitty_img: KittyImageState,
     seqno: SequenceNo,
 
@@ -570,6 +587,9 @@ impl TerminalState {
             writer,
             image_cache: lru::LruCache::new(16),
             user_vars: HashMap::new(),
+            progress: Progress::default(),
+            pointer_shapes: vec![],
+            attribute_change_extent: AttributeChangeExtent::Stream,
             kitty_img: Default::default(),
             seqno,
             unicode_version,
@@ -598,6 +618,14 @@ impl TerminalState {
         self.seqno += 1;
     }
 
+    /// Stamps the lines that arrived in the most recent batch of
+    /// output with the current time
+    pub fn stamp_line_timestamps(&mut self) {
+        let cursor = self.cursor;
+        self.screen_mut()
+            .stamp_line_timestamps(&cursor, std::time::SystemTime::now());
+    }
+
     pub fn set_config(&mut self, config: Arc<dyn TerminalConfiguration>) {
         self.config = config;
     }
@@ -960,6 +988,11 @@ impl TerminalState {
         &self.user_vars
     }
 
+    /// Returns the progress most recently reported by the application
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
     fn clear_semantic_attribute_due_to_movement(&mut self) {
         if self.clear_semantic_attribute_on_newline {
             self.clear_semantic_attribute_on_newline = false;
@@ -1285,6 +1318,7 @@ impl TerminalState {
                 ident.push_str(";6"); // Selective erase
                 ident.push_str(";18"); // windowing extensions
                 ident.push_str(";22"); // ANSI color, vt525
+                ident.push_str(";28"); // Rectangular editing
                 ident.push('c');
 
                 self.writer.write(ident.as_bytes()).ok();
@@ -2250,6 +2284,8 @@ impl TerminalState {
             Edit::ScrollDown(n) => self.scroll_down(n as usize),
             Edit::ScrollUp(n) => self.scroll_up(n as usize),
             Edit::EraseInDisplay(erase) => self.erase_in_display(erase),
+            Edit::SelectiveEraseInDisplay(erase) => self.selective_erase_in_display(erase),
+            Edit::SelectiveEraseInLine(erase) => self.selective_erase_in_line(erase),
             Edit::Repeat(n) => {
                 let mut y = self.cursor.y;
                 let mut x = self.cursor.x;
@@ -2648,9 +2684,11 @@ impl TerminalState {
             Sgr::Reset => {
                 let link = self.pen.hyperlink().map(Arc::clone);
                 let semantic_type = self.pen.semantic_type();
+                let protected = self.pen.protected();
                 self.pen = CellAttributes::default();
                 self.pen.set_hyperlink(link);
                 self.pen.set_semantic_type(semantic_type);
+                self.pen.set_protected(protected);
             }
             Sgr::Intensity(intensity) => {
                 self.pen.set_intensity(intensity);
@@ -1,4 +1,5 @@
 use crate::terminal::Alert;
+use crate::terminalstate::kitty::KITTY_PLACEHOLDER;
 use crate::terminalstate::{
     default_color_map, CharSet, MouseEncoding, TabStop, UnicodeVersionStackEntry,
 };
@@ -12,11 +13,12 @@ use std::io::Write as _;
 use std::ops::{Deref, DerefMut};
 use termwiz::cell::{grapheme_column_width, Cell, CellAttributes, SemanticType};
 use termwiz::escape::csi::{
-    CharacterPath, EraseInDisplay, Keyboard, KittyKeyboardFlags, KittyKeyboardMode,
+    AttributeChangeExtent, CharacterPath, EraseInDisplay, Keyboard, KittyKeyboardFlags,
+    KittyKeyboardMode,
 };
 use termwiz::escape::osc::{
     ChangeColorPair, ColorOrQuery, FinalTermSemanticPrompt, ITermProprietary,
-    ITermUnicodeVersionOp, Selection,
+    ITermUnicodeVersionOp, Progress, Selection,
 };
 use termwiz::escape::{
     Action, ControlCode, DeviceControlMode, Esc, EscCode, OperatingSystemCommand, CSI,
@@ -204,6 +206,10 @@ impl<'a> Performer<'a> {
             self.screen_mut()
                 .set_cell_grapheme(x, y, g, print_width, pen, seqno);
 
+            if g.starts_with(KITTY_PLACEHOLDER) && self.config.enable_kitty_graphics() {
+                self.kitty_placeholder_printed(x, y);
+            }
+
             if !wrappable {
                 self.cursor.x += print_width;
                 self.wrap_next = false;
@@ -501,6 +507,10 @@ impl<'a> Performer<'a> {
                     .bidi_hint
                     .replace(ParagraphDirectionHint::RightToLeft);
             }
+            CSI::SelectCharacterProtection(protection) => {
+                self.state.select_character_protection(protection)
+            }
+            CSI::RectangularArea(area) => self.state.perform_rectangular_area(*area),
             CSI::Keyboard(Keyboard::SetKittyState { flags, mode }) => {
                 if self.config.enable_kitty_keyboard() {
                     let current_flags = match self.screen().keyboard_stack.last() {
@@ -696,6 +706,14 @@ impl<'a> Performer<'a> {
                 self.unicode_version_stack.clear();
                 self.suppress_initial_title_change = false;
                 self.accumulating_title.take();
+                self.pointer_shapes.clear();
+                self.attribute_change_extent = AttributeChangeExtent::Stream;
+                if self.progress != Progress::None {
+                    self.progress = Progress::None;
+                    if let Some(handler) = self.alert_handler.as_mut() {
+                        handler.alert(Alert::Progress(Progress::None));
+                    }
+                }
 
                 self.screen.full_reset();
                 self.screen.activate_alt_screen(seqno);
@@ -892,6 +910,20 @@ impl<'a> Performer<'a> {
                     log::info!("Application sends SystemNotification: {}", message);
                 }
             }
+            OperatingSystemCommand::ConEmuProgress(progress) => {
+                if progress != self.progress {
+                    self.progress = progress;
+                    if let Some(handler) = self.alert_handler.as_mut() {
+                        handler.alert(Alert::Progress(progress));
+                    }
+                }
+            }
+            OperatingSystemCommand::SetPointerShape(request) => {
+                self.pointer_shape_request(request);
+            }
+            OperatingSystemCommand::TextSizing(size, text) => {
+                self.print_text_sized(size, &text);
+            }
             OperatingSystemCommand::RxvtExtension(params) => {
                 if let Some("notify") = params.get(0).map(String::as_str) {
                     let title = params.get(1);
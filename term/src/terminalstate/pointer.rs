@@ -0,0 +1,125 @@
+//! Mouse pointer shapes requested by applications via OSC 22.
+//! See <https://sw.kovidgoyal.net/kitty/pointer-shapes/>
+use crate::TerminalState;
+use std::io::Write;
+use termwiz::escape::osc::PointerShapeRequest;
+use termwiz::escape::OperatingSystemCommand;
+
+/// Limits the size of the stack of pushed shapes, so that a misbehaving
+/// application cannot make it grow without bound
+const MAX_POINTER_SHAPE_STACK: usize = 16;
+
+/// A mouse pointer shape that an application may request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerShape {
+    /// The regular arrow pointer
+    Default,
+    /// The hand that indicates a link or button
+    Pointer,
+    /// The I-beam that indicates selectable text
+    Text,
+    /// Indicates that something can be resized vertically
+    RowResize,
+    /// Indicates that something can be resized horizontally
+    ColumnResize,
+}
+
+impl PointerShape {
+    /// Resolves a CSS cursor name or the name of an X11 cursor font
+    /// glyph to the shape that it describes
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "default" | "arrow" | "left_ptr" | "top_left_arrow" => Some(Self::Default),
+            "pointer" | "hand" | "hand1" | "hand2" | "pointing_hand" => Some(Self::Pointer),
+            "text" | "xterm" | "ibeam" => Some(Self::Text),
+            "ns-resize" | "row-resize" | "sb_v_double_arrow" | "v_double_arrow" => {
+                Some(Self::RowResize)
+            }
+            "ew-resize" | "col-resize" | "sb_h_double_arrow" | "h_double_arrow" => {
+                Some(Self::ColumnResize)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the CSS cursor name of the shape
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Pointer => "pointer",
+            Self::Text => "text",
+            Self::RowResize => "ns-resize",
+            Self::ColumnResize => "ew-resize",
+        }
+    }
+
+    /// Returns the first of `names` that is supported
+    fn first_supported(names: &[String]) -> Option<Self> {
+        names.iter().find_map(|name| Self::from_name(name))
+    }
+}
+
+impl TerminalState {
+    /// Returns the pointer shape requested by the application, or None
+    /// if it hasn't requested one and the terminal should pick a shape
+    /// that suits what is under the pointer
+    pub fn pointer_shape(&self) -> Option<PointerShape> {
+        self.pointer_shapes.last().copied()
+    }
+
+    /// Returns the shape used when the application hasn't requested one
+    fn default_pointer_shape(&self) -> PointerShape {
+        if self.is_mouse_grabbed() {
+            PointerShape::Default
+        } else {
+            PointerShape::Text
+        }
+    }
+
+    pub(crate) fn pointer_shape_request(&mut self, request: PointerShapeRequest) {
+        match request {
+            PointerShapeRequest::Set(names) if names.is_empty() => {
+                self.pointer_shapes.clear();
+            }
+            PointerShapeRequest::Set(names) => {
+                if let Some(shape) = PointerShape::first_supported(&names) {
+                    self.pointer_shapes.pop();
+                    self.pointer_shapes.push(shape);
+                }
+            }
+            PointerShapeRequest::Push(names) => {
+                if let Some(shape) = PointerShape::first_supported(&names) {
+                    if self.pointer_shapes.len() >= MAX_POINTER_SHAPE_STACK {
+                        self.pointer_shapes.remove(0);
+                    }
+                    self.pointer_shapes.push(shape);
+                }
+            }
+            PointerShapeRequest::Pop => {
+                self.pointer_shapes.pop();
+            }
+            PointerShapeRequest::Query(names) => {
+                let results = names
+                    .iter()
+                    .map(|name| match name.as_str() {
+                        "__current__" => self
+                            .pointer_shape()
+                            .unwrap_or_else(|| self.default_pointer_shape())
+                            .name()
+                            .to_string(),
+                        "__default__" => PointerShape::Text.name().to_string(),
+                        "__grabbed__" => PointerShape::Default.name().to_string(),
+                        name => match PointerShape::from_name(name) {
+                            Some(_) => "1".to_string(),
+                            None => "0".to_string(),
+                        },
+                    })
+                    .collect();
+                let response =
+                    OperatingSystemCommand::SetPointerShape(PointerShapeRequest::Set(results));
+                write!(self.writer, "{}", response).ok();
+                self.writer.flush().ok();
+            }
+        }
+    }
+}
@@ -0,0 +1,303 @@
+//! The DEC rectangular area operations (DECCRA, DECFRA, DECERA,
+//! DECSERA and DECCARA) and the DECSCA protection attribute that
+//! selective erase (DECSED, DECSEL and DECSERA) respects.
+//! See <https://vt100.net/docs/vt510-rm/chapter5.html#S5.10>
+use crate::{TerminalState, VisibleRowIndex};
+use std::ops::Range;
+use termwiz::cell::{grapheme_column_width, Blink, Cell, CellAttributes, Intensity, Underline};
+use termwiz::escape::csi::{
+    AttributeChangeExtent, CharacterProtection, EraseInDisplay, EraseInLine, Rectangle,
+    RectangularArea, Sgr,
+};
+
+impl TerminalState {
+    pub(crate) fn select_character_protection(&mut self, protection: CharacterProtection) {
+        self.pen
+            .set_protected(protection == CharacterProtection::Protected);
+    }
+
+    pub(crate) fn perform_rectangular_area(&mut self, area: RectangularArea) {
+        match area {
+            RectangularArea::Copy {
+                source,
+                dest_top,
+                dest_left,
+                ..
+            } => self.copy_rectangle(&source, dest_top.as_zero_based(), dest_left.as_zero_based()),
+            RectangularArea::Fill { character, area } => self.fill_rectangle(character, &area),
+            RectangularArea::Erase(area) => self.erase_rectangle(&area),
+            RectangularArea::SelectiveErase(area) => self.selective_erase_rectangle(&area),
+            RectangularArea::ChangeAttributes { area, attributes } => {
+                self.change_attributes_in_rectangle(&area, &attributes)
+            }
+            RectangularArea::SelectAttributeChangeExtent(extent) => {
+                self.attribute_change_extent = extent;
+            }
+        }
+    }
+
+    /// Returns the rows and columns that an operation may affect.
+    /// When DECOM is set these are the margins, otherwise the whole screen.
+    fn rectangle_limits(&self) -> (Range<VisibleRowIndex>, Range<usize>) {
+        if self.dec_origin_mode {
+            (
+                self.top_and_bottom_margins.clone(),
+                self.left_and_right_margins.clone(),
+            )
+        } else {
+            let screen = self.screen();
+            (
+                0..screen.physical_rows as VisibleRowIndex,
+                0..screen.physical_cols,
+            )
+        }
+    }
+
+    /// Translates `rect` into screen coordinates, relative to the
+    /// origin established by DECOM and clipped to the limits of the
+    /// operation.  Returns None if the rectangle is empty.
+    fn rectangle_bounds(&self, rect: &Rectangle) -> Option<(Range<VisibleRowIndex>, Range<usize>)> {
+        let (rows, cols) = self.rectangle_limits();
+        let top = rows.start + VisibleRowIndex::from(rect.top.as_zero_based());
+        let bottom =
+            (rows.start + VisibleRowIndex::from(rect.bottom.as_zero_based())).min(rows.end - 1);
+        let left = cols
+            .start
+            .saturating_add(rect.left.as_zero_based() as usize);
+        let right = cols
+            .start
+            .saturating_add(rect.right.as_zero_based() as usize)
+            .min(cols.end - 1);
+        if top > bottom || left > right {
+            return None;
+        }
+        Some((top..bottom + 1, left..right + 1))
+    }
+
+    fn copy_rectangle(&mut self, source: &Rectangle, dest_top: u32, dest_left: u32) {
+        let (rows, cols) = match self.rectangle_bounds(source) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let (row_limits, col_limits) = self.rectangle_limits();
+        let dest_top = row_limits.start + VisibleRowIndex::from(dest_top);
+        let dest_left = col_limits.start.saturating_add(dest_left as usize);
+        if dest_top >= row_limits.end || dest_left >= col_limits.end {
+            return;
+        }
+        let height = (rows.end - rows.start).min(row_limits.end - dest_top);
+        let width = (cols.end - cols.start).min(col_limits.end - dest_left);
+
+        // Take a copy of the whole source area before writing any of
+        // it, as the source and destination may overlap
+        let seqno = self.seqno;
+        let screen = self.screen_mut();
+        let mut copied = vec![];
+        for y in rows.start..rows.start + height {
+            let line = screen.line_mut(screen.phys_row(y));
+            let mut row = vec![Cell::blank(); width];
+            for cell in line.visible_cells() {
+                let idx = cell.cell_index();
+                if idx >= cols.start + width {
+                    break;
+                }
+                if idx >= cols.start {
+                    let offset = idx - cols.start;
+                    row[offset] = if offset + cell.width() > width {
+                        // A wide character that doesn't fit is clipped
+                        Cell::blank_with_attrs(cell.attrs().clone_sgr_only())
+                    } else {
+                        cell.as_cell()
+                    };
+                }
+            }
+            copied.push(row);
+        }
+
+        for (y, row) in (dest_top..).zip(copied) {
+            let line = screen.line_mut(screen.phys_row(y));
+            let mut offset = 0;
+            while offset < row.len() {
+                let cell = row[offset].clone();
+                let width = cell.width().max(1);
+                line.set_cell(dest_left + offset, cell, seqno);
+                offset += width;
+            }
+        }
+    }
+
+    fn fill_rectangle(&mut self, character: char, area: &Rectangle) {
+        let mut text = [0u8; 4];
+        let text = character.encode_utf8(&mut text);
+        if character.is_control() || grapheme_column_width(text, Some(self.unicode_version)) != 1 {
+            log::debug!("DECFRA with unsuitable fill character {character:?}; ignoring it");
+            return;
+        }
+        let (rows, cols) = match self.rectangle_bounds(area) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let seqno = self.seqno;
+        let cell = Cell::new_grapheme(text, self.pen.clone(), None);
+        let screen = self.screen_mut();
+        for y in rows {
+            let line_idx = screen.phys_row(y);
+            screen
+                .line_mut(line_idx)
+                .fill_range(cols.clone(), &cell, seqno);
+        }
+    }
+
+    fn erase_rectangle(&mut self, area: &Rectangle) {
+        let (rows, cols) = match self.rectangle_bounds(area) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let seqno = self.seqno;
+        let blank = Cell::blank_with_attrs(self.pen.clone_sgr_only());
+        let screen = self.screen_mut();
+        for y in rows {
+            let line_idx = screen.phys_row(y);
+            screen
+                .line_mut(line_idx)
+                .fill_range(cols.clone(), &blank, seqno);
+        }
+    }
+
+    /// DECSED: like ED, but leaves the characters protected by DECSCA
+    pub(crate) fn selective_erase_in_display(&mut self, erase: EraseInDisplay) {
+        let cy = self.cursor.y;
+        let rows = self.screen().physical_rows as VisibleRowIndex;
+        let cols = 0..self.screen().physical_cols;
+        let row_range = match erase {
+            EraseInDisplay::EraseToEndOfDisplay => {
+                self.selective_erase_in_line(EraseInLine::EraseToEndOfLine);
+                cy + 1..rows
+            }
+            EraseInDisplay::EraseToStartOfDisplay => {
+                self.selective_erase_in_line(EraseInLine::EraseToStartOfLine);
+                0..cy
+            }
+            EraseInDisplay::EraseDisplay => 0..rows,
+            // There is no selective form of erasing the scrollback
+            EraseInDisplay::EraseScrollback => return,
+        };
+        for y in row_range {
+            self.selective_erase_cells(y, cols.clone());
+        }
+    }
+
+    /// DECSEL: like EL, but leaves the characters protected by DECSCA
+    pub(crate) fn selective_erase_in_line(&mut self, erase: EraseInLine) {
+        let cx = self.cursor.x;
+        let cols = self.screen().physical_cols;
+        let range = match erase {
+            // See the comment on EL about wrap_next
+            EraseInLine::EraseToEndOfLine => cx + if self.wrap_next { 1 } else { 0 }..cols,
+            EraseInLine::EraseToStartOfLine => 0..cx + 1,
+            EraseInLine::EraseLine => 0..cols,
+        };
+        self.selective_erase_cells(self.cursor.y, range);
+    }
+
+    /// Erases the characters that are not protected by DECSCA.
+    /// The erased cells keep their colors.
+    fn selective_erase_rectangle(&mut self, area: &Rectangle) {
+        let (rows, cols) = match self.rectangle_bounds(area) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        for y in rows {
+            self.selective_erase_cells(y, cols.clone());
+        }
+    }
+
+    /// Erases the unprotected characters in `cols` of row `y`,
+    /// keeping their colors.
+    fn selective_erase_cells(&mut self, y: VisibleRowIndex, cols: Range<usize>) {
+        let seqno = self.seqno;
+        let screen = self.screen_mut();
+        let line = screen.line_mut(screen.phys_row(y));
+        let erase: Vec<(usize, CellAttributes)> = line
+            .visible_cells()
+            .filter(|cell| cols.contains(&cell.cell_index()) && !cell.attrs().protected())
+            .map(|cell| (cell.cell_index(), cell.attrs().clone_sgr_only()))
+            .collect();
+        for (idx, attrs) in erase {
+            line.set_cell(idx, Cell::blank_with_attrs(attrs), seqno);
+        }
+    }
+
+    fn change_attributes_in_rectangle(&mut self, area: &Rectangle, attributes: &[Sgr]) {
+        let (rows, cols) = match self.rectangle_bounds(area) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let (_, col_limits) = self.rectangle_limits();
+        let extent = self.attribute_change_extent;
+        let seqno = self.seqno;
+        let screen = self.screen_mut();
+        for y in rows.clone() {
+            // In stream mode the area runs from the top left position
+            // to the bottom right position in the order that text flows
+            let cols = match extent {
+                AttributeChangeExtent::Rectangle => cols.clone(),
+                AttributeChangeExtent::Stream => {
+                    let start = if y == rows.start {
+                        cols.start
+                    } else {
+                        col_limits.start
+                    };
+                    let end = if y == rows.end - 1 {
+                        cols.end
+                    } else {
+                        col_limits.end
+                    };
+                    start..end
+                }
+            };
+
+            let line = screen.line_mut(screen.phys_row(y));
+            if line.len() < cols.end {
+                line.resize(cols.end, seqno);
+            }
+            for cell in &mut line.cells_mut_for_attr_changes_only()[cols] {
+                let attrs = cell.attrs_mut();
+                for sgr in attributes {
+                    apply_rectangle_attribute(attrs, sgr);
+                }
+            }
+            line.update_last_change_seqno(seqno);
+        }
+    }
+}
+
+/// Applies one of the attributes that DECCARA is able to change
+fn apply_rectangle_attribute(attrs: &mut CellAttributes, sgr: &Sgr) {
+    match sgr {
+        Sgr::Reset => {
+            attrs
+                .set_intensity(Intensity::Normal)
+                .set_underline(Underline::None)
+                .set_blink(Blink::None)
+                .set_reverse(false)
+                .set_invisible(false);
+        }
+        Sgr::Intensity(intensity) => {
+            attrs.set_intensity(*intensity);
+        }
+        Sgr::Underline(underline) => {
+            attrs.set_underline(*underline);
+        }
+        Sgr::Blink(blink) => {
+            attrs.set_blink(*blink);
+        }
+        Sgr::Inverse(inverse) => {
+            attrs.set_reverse(*inverse);
+        }
+        Sgr::Invisible(invisible) => {
+            attrs.set_invisible(*invisible);
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,91 @@
+//! Placement of text sized via the kitty text sizing protocol (OSC 66).
+//! See <https://sw.kovidgoyal.net/kitty/text-sizing-protocol/>
+use crate::{TerminalState, VisibleRowIndex};
+use finl_unicode::grapheme_clusters::Graphemes;
+use termwiz::cell::{grapheme_column_width, ScaledText, TextSize};
+
+impl TerminalState {
+    /// Places `text` at the cursor, in one or more blocks that span
+    /// `size.scale` rows, and advances the cursor past them.
+    /// The cells of the rows below the cursor are reserved so that
+    /// the scaled text can be drawn over them.
+    pub(crate) fn print_text_sized(&mut self, size: TextSize, text: &str) {
+        let scale = size.scale.max(1) as usize;
+        let blocks: Vec<(&str, usize)> = if size.width > 0 {
+            if text.is_empty() {
+                vec![]
+            } else {
+                vec![(text, size.width as usize * scale)]
+            }
+        } else {
+            Graphemes::new(text)
+                .map(|g| {
+                    (
+                        g,
+                        grapheme_column_width(g, Some(self.unicode_version)) * scale,
+                    )
+                })
+                .filter(|(_, width)| *width > 0)
+                .collect()
+        };
+
+        for (text, width) in blocks {
+            self.place_text_sized_block(size, text, width);
+        }
+    }
+
+    fn place_text_sized_block(&mut self, size: TextSize, text: &str, width: usize) {
+        let seqno = self.seqno;
+        let scale = size.scale.max(1) as usize;
+        let margins = self.left_and_right_margins.clone();
+        let region = self.top_and_bottom_margins.clone();
+        if width > margins.end - margins.start
+            || scale as VisibleRowIndex > region.end - region.start
+        {
+            log::debug!("text sized block {width}x{scale} doesn't fit; ignoring it");
+            return;
+        }
+
+        if self.wrap_next || self.cursor.x + width > margins.end {
+            if self.dec_auto_wrap {
+                self.new_line(true);
+            } else {
+                self.cursor.x = margins.end - width;
+            }
+        }
+        self.wrap_next = false;
+
+        // Scroll up so that the rows below the cursor can hold the block
+        let overflow = self.cursor.y + scale as VisibleRowIndex - region.end;
+        if overflow > 0 && region.contains(&self.cursor.y) {
+            self.scroll_up(overflow as usize);
+            self.cursor.y -= overflow;
+        }
+        if self.cursor.y + scale as VisibleRowIndex > self.screen().physical_rows as VisibleRowIndex
+        {
+            log::debug!("text sized block {width}x{scale} extends below the screen; ignoring it");
+            return;
+        }
+
+        let x = self.cursor.x;
+        let y = self.cursor.y;
+        let pen = self.pen.clone();
+        let screen = self.screen_mut();
+        for row in 0..scale {
+            let mut attrs = pen.clone();
+            attrs.set_scaled_text(Some(ScaledText {
+                size,
+                row: row as u8,
+            }));
+            let text = if row == 0 { text } else { " " };
+            screen.set_cell_grapheme(x, y + row as VisibleRowIndex, text, width, attrs, seqno);
+        }
+
+        if x + width >= margins.end {
+            self.cursor.x = margins.end - 1;
+            self.wrap_next = self.dec_auto_wrap;
+        } else {
+            self.cursor.x = x + width;
+        }
+    }
+}
@@ -401,3 +401,193 @@ fn test_ed_erase_scrollback() {
     term.print("b");
     assert_all_contents(&term, file!(), line!(), &["111", "222", "ab"]);
 }
+
+#[test]
+fn test_deccra() {
+    let mut term = TestTerm::new(4, 5, 0);
+    term.print("abcde\r\nfghij\r\nklmno\r\npqrst");
+
+    // Copy the top left corner to the bottom right corner
+    term.print("\x1b[1;1;2;2;1;3;4;1$v");
+    assert_visible_contents(
+        &term,
+        file!(),
+        line!(),
+        &["abcde", "fghij", "klmab", "pqrfg"],
+    );
+
+    // The source is copied as it was before the copy started,
+    // even though it overlaps the destination
+    term.print("\x1b[1;1;2;4;1;1;2;1$v");
+    assert_visible_contents(
+        &term,
+        file!(),
+        line!(),
+        &["aabcd", "ffghi", "klmab", "pqrfg"],
+    );
+
+    // The part that doesn't fit on the screen is clipped
+    term.print("\x1b[1;1;;;1;4;5;1$v");
+    assert_visible_contents(
+        &term,
+        file!(),
+        line!(),
+        &["aabcd", "ffghi", "klmab", "pqrfa"],
+    );
+
+    // With DECOM, the coordinates are relative to the margins
+    // and the copy is clipped to them
+    term.print("\x1b[?69h");
+    term.set_left_and_right_margins(1, 3);
+    term.set_scroll_region(1, 2);
+    term.print("\x1b[?6h");
+    term.print("\x1b[1;1;1;3;1;2;2;1$v");
+    assert_visible_contents(
+        &term,
+        file!(),
+        line!(),
+        &["aabcd", "ffghi", "klfgb", "pqrfa"],
+    );
+}
+
+#[test]
+fn test_decfra() {
+    let mut term = TestTerm::new(3, 4, 0);
+    term.print("\x1b[88;2;2;3;3$x");
+    assert_visible_contents(&term, file!(), line!(), &["", " XX", " XX"]);
+
+    // The area defaults to the whole screen
+    term.print("\x1b[42$x");
+    assert_visible_contents(&term, file!(), line!(), &["****", "****", "****"]);
+
+    // Control characters cannot be used to fill the area
+    term.print("\x1b[10$x");
+    assert_visible_contents(&term, file!(), line!(), &["****", "****", "****"]);
+
+    // The current rendition is used for the filled cells
+    term.print("\x1b[1m\x1b[79;1;1;1;1$x\x1b[0m");
+    assert_eq!(
+        term.screen().visible_lines()[0]
+            .get_cell(0)
+            .unwrap()
+            .attrs()
+            .intensity(),
+        Intensity::Bold
+    );
+
+    // With DECOM, the area is relative to and clipped to the margins
+    term.print("\x1b[?69h");
+    term.set_left_and_right_margins(1, 2);
+    term.set_scroll_region(1, 2);
+    term.print("\x1b[?6h");
+    term.print("\x1b[45;1;1;9;9$x");
+    assert_visible_contents(&term, file!(), line!(), &["O***", "*--*", "*--*"]);
+}
+
+#[test]
+fn test_decera() {
+    let mut term = TestTerm::new(3, 4, 0);
+    term.print("abcd\r\nefgh\r\nijkl");
+    term.print("\x1b[2;2;3;3$z");
+    assert_visible_contents(&term, file!(), line!(), &["abcd", "e  h", "i  l"]);
+
+    // With DECOM, the area is relative to and clipped to the margins
+    term.set_scroll_region(1, 2);
+    term.print("\x1b[?6h");
+    term.print("\x1b[1;1;1;2$z");
+    assert_visible_contents(&term, file!(), line!(), &["abcd", "   h", "i  l"]);
+    term.print("\x1b[2;4;9;9$z");
+    assert_visible_contents(&term, file!(), line!(), &["abcd", "   h", "i"]);
+
+    // An empty area is ignored
+    term.print("\x1b[?6l");
+    term.print("\x1b[1;3;1;2$z");
+    assert_visible_contents(&term, file!(), line!(), &["abcd", "   h", "i"]);
+}
+
+#[test]
+fn test_decsera() {
+    let mut term = TestTerm::new(2, 8, 0);
+    // cd and gh are protected; SGR 0 doesn't change the protection
+    term.print("ab\x1b[1\"qcd\x1b[0\"qef\x1b[1\"q\x1b[0mgh\x1b[2\"q");
+    term.print("\r\nijkl");
+
+    // Only the unprotected characters are erased
+    term.print("\x1b[${");
+    assert_visible_contents(&term, file!(), line!(), &["  cd  gh", "    "]);
+
+    // DECERA erases protected characters too
+    term.print("\x1b[1;1;1;6$z");
+    assert_visible_contents(&term, file!(), line!(), &["      gh", "    "]);
+
+    // The blanks left behind by an erase are never protected
+    term.print("\x1b[1\"q\x1b[H\x1b[K");
+    assert!(!term.screen().visible_lines()[0]
+        .get_cell(0)
+        .unwrap()
+        .attrs()
+        .protected());
+}
+
+#[test]
+fn test_decsed_decsel() {
+    let mut term = TestTerm::new(3, 4, 0);
+    // b, f and j are protected
+    term.print("a\x1b[1\"qb\x1b[0\"qcd\r\n");
+    term.print("e\x1b[1\"qf\x1b[0\"qgh\r\n");
+    term.print("i\x1b[1\"qj\x1b[0\"qkl");
+
+    // DECSEL from the cursor to the end of the line
+    term.cup(1, 1);
+    term.print("\x1b[?K");
+    assert_visible_contents(&term, file!(), line!(), &["abcd", "ef  ", "ijkl"]);
+
+    // DECSED from the start of the display to the cursor
+    term.cup(0, 2);
+    term.print("\x1b[?1J");
+    assert_visible_contents(&term, file!(), line!(), &[" b  ", " f  ", " jkl"]);
+
+    // DECSED for the whole display
+    term.print("\x1b[?2J");
+    assert_visible_contents(&term, file!(), line!(), &[" b  ", " f  ", " j  "]);
+}
+
+#[test]
+fn test_deccara() {
+    fn bold_cells(term: &TestTerm) -> Vec<String> {
+        term.screen()
+            .visible_lines()
+            .iter()
+            .map(|line| {
+                line.visible_cells()
+                    .map(|cell| match cell.attrs().intensity() {
+                        Intensity::Bold => 'B',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    let mut term = TestTerm::new(3, 4, 0);
+    term.print("abcd\r\nefgh\r\nijkl");
+
+    // Select the rectangle extent
+    term.print("\x1b[2*x");
+    term.print("\x1b[1;2;2;3;1$r");
+    assert_eq!(bold_cells(&term), vec![".BB.", ".BB.", "...."]);
+
+    term.print("\x1b[1;1;3;4;22$r");
+    assert_eq!(bold_cells(&term), vec!["....", "....", "...."]);
+
+    // The stream extent runs from the start position to the end
+    // position in the order that the text flows
+    term.print("\x1b[1*x");
+    term.print("\x1b[1;3;2;3;1$r");
+    assert_eq!(bold_cells(&term), vec!["..BB", "BBB.", "...."]);
+
+    // 0 turns off all of the attributes
+    term.print("\x1b[$r");
+    assert_eq!(bold_cells(&term), vec!["....", "....", "...."]);
+    assert_visible_contents(&term, file!(), line!(), &["abcd", "efgh", "ijkl"]);
+}
@@ -0,0 +1,109 @@
+//! Testing kitty image protocol unicode placeholders
+
+use super::*;
+
+/// Transmits a 2x1 pixel RGB image and creates a virtual placement
+/// for it that spans `columns` x `rows` cells
+fn transmit_virtual(term: &mut TestTerm, image_id: u32, columns: usize, rows: usize) {
+    term.print(format!(
+        "\x1b_Ga=T,U=1,q=2,f=24,s=2,v=1,i={image_id},c={columns},r={rows};AAAAAAAA\x1b\\"
+    ));
+}
+
+/// Returns the image id, texture coordinates and padding of the
+/// image attached to each of the cells that have been written in
+/// row `y`
+fn placeholder_images(
+    term: &TestTerm,
+    y: usize,
+) -> Vec<Option<(Option<u32>, (f32, f32), (f32, f32), (u16, u16, u16, u16))>> {
+    let line = &term.screen().visible_lines()[y];
+    line.visible_cells()
+        .map(|cell| {
+            cell.attrs().images().and_then(|images| {
+                images.first().map(|image| {
+                    let top_left = image.top_left();
+                    let bottom_right = image.bottom_right();
+                    (
+                        image.image_id(),
+                        (*top_left.x, *top_left.y),
+                        (*bottom_right.x, *bottom_right.y),
+                        image.padding(),
+                    )
+                })
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn test_placeholder_displays_virtual_placement() {
+    let mut term = TestTerm::new(2, 4, 0);
+    transmit_virtual(&mut term, 1, 2, 1);
+    // A virtual placement doesn't draw anything by itself
+    term.assert_cursor_pos(0, 0, None, None);
+    assert_eq!(placeholder_images(&term, 0), vec![]);
+
+    // The first placeholder has row and column diacritics, the second
+    // infers them from the first, and the third lies outside of the
+    // placement
+    term.print("\x1b[38;5;1m\u{10EEEE}\u{305}\u{305}\u{10EEEE}\u{10EEEE}");
+    term.assert_cursor_pos(3, 0, None, None);
+
+    // The image is 2x1 pixels, so it is scaled to fill the 16x16 pixels
+    // of the placement horizontally and centered vertically
+    assert_eq!(
+        placeholder_images(&term, 0),
+        vec![
+            Some((Some(1), (0., 0.), (0.5, 1.), (0, 4, 0, 4))),
+            Some((Some(1), (0.5, 0.), (1., 1.), (0, 4, 0, 4))),
+            None,
+        ]
+    );
+
+    // The placeholder characters themselves are not shown
+    let line = &term.screen().visible_lines()[0];
+    assert!(line.visible_cells().all(|c| c.attrs().invisible()));
+}
+
+#[test]
+fn test_placeholder_refreshed_by_placement() {
+    let mut term = TestTerm::new(2, 4, 0);
+    // The placeholders refer to an image that doesn't exist yet
+    term.print("\x1b[38;5;7m\u{10EEEE}\u{305}\u{305}\u{10EEEE}\r\n");
+    term.print("\u{10EEEE}\u{30D}\u{305}\u{10EEEE}\u{30D}");
+    assert_eq!(placeholder_images(&term, 0), vec![None; 2]);
+
+    transmit_virtual(&mut term, 7, 2, 2);
+    assert_eq!(
+        placeholder_images(&term, 1),
+        vec![
+            Some((Some(7), (0., 0.5), (0.5, 1.), (0, 0, 0, 12))),
+            Some((Some(7), (0.5, 0.5), (1., 1.), (0, 0, 0, 12))),
+        ]
+    );
+
+    // Deleting the placement removes the image from the placeholders
+    term.print("\x1b_Ga=d,d=i,i=7,q=2\x1b\\");
+    assert_eq!(placeholder_images(&term, 0), vec![None; 2]);
+    assert_eq!(placeholder_images(&term, 1), vec![None; 2]);
+}
+
+#[test]
+fn test_placeholder_image_id_from_rgb_and_third_diacritic() {
+    let mut term = TestTerm::new(2, 4, 0);
+    let image_id = (1 << 24) | 0x0102;
+    transmit_virtual(&mut term, image_id, 1, 1);
+
+    // The lower 24 bits of the id are in the foreground color and the
+    // most significant byte is in the third diacritic
+    term.print("\x1b[38;2;0;1;2m\u{10EEEE}\u{305}\u{305}\u{30D}");
+    assert_eq!(
+        placeholder_images(&term, 0)[0].map(|(id, ..)| id),
+        Some(Some(image_id))
+    );
+
+    // Without the third diacritic, the id refers to a different image
+    term.print("\r\n\u{10EEEE}\u{305}\u{305}");
+    assert_eq!(placeholder_images(&term, 1)[0], None);
+}
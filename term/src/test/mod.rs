@@ -6,11 +6,13 @@ mod c0;
 use bitflags::bitflags;
 mod c1;
 mod csi;
+mod kitty;
 // mod selection; FIXME: port to render layer
 use crate::color::ColorPalette;
 use k9::assert_equal as assert_eq;
 use std::sync::{Arc, Mutex};
 use termwiz::escape::csi::{Edit, EraseInDisplay, EraseInLine};
+use termwiz::escape::osc::Progress;
 use termwiz::escape::{OneBased, OperatingSystemCommand, CSI};
 use termwiz::surface::{CursorShape, CursorVisibility, SequenceNo, SEQ_ZERO};
 
@@ -54,6 +56,10 @@ impl TerminalConfiguration for TestTermConfig {
     fn color_palette(&self) -> ColorPalette {
         ColorPalette::default()
     }
+
+    fn enable_kitty_graphics(&self) -> bool {
+        true
+    }
 }
 
 impl TestTerm {
@@ -528,6 +534,51 @@ fn basic_output() {
     assert_visible_contents(&term, file!(), line!(), &["", "          ", "   ", "", ""]);
 }
 
+#[test]
+fn line_timestamps() {
+    let mut term = TestTerm::new(5, 10, 0);
+    term.print("one\r\ntwo\r\n");
+
+    let timestamps = term.screen().line_timestamps();
+    let first = timestamps.get(0).expect("first line is stamped");
+    assert!(timestamps.get(1).is_some());
+    // The cursor has yet to write to the third line
+    assert_eq!(timestamps.get(2), None);
+
+    term.print("three");
+    let timestamps = term.screen().line_timestamps();
+    let third = timestamps.get(2).expect("third line is stamped");
+    assert!(third >= first);
+    assert_eq!(timestamps.first_row_at_or_after(first), Some(0));
+}
+
+#[test]
+fn line_timestamp_runs() {
+    use std::time::{Duration, SystemTime};
+    let t0 = SystemTime::UNIX_EPOCH;
+    let t1 = t0 + Duration::from_secs(1);
+
+    let mut timestamps = LineTimestamps::default();
+    timestamps.stamp_through(2, t0);
+    timestamps.stamp_through(3, t0);
+    timestamps.stamp_through(5, t1);
+    // Rows are only stamped once
+    timestamps.stamp_through(4, t0);
+
+    assert_eq!(
+        timestamps.iter().collect::<Vec<_>>(),
+        vec![(0..3, t0), (3..5, t1)]
+    );
+    assert_eq!(timestamps.get(2), Some(t0));
+    assert_eq!(timestamps.get(4), Some(t1));
+    assert_eq!(timestamps.get(5), None);
+    assert_eq!(timestamps.first_row_at_or_after(t1), Some(3));
+    assert_eq!(
+        timestamps.subset(2..4).iter().collect::<Vec<_>>(),
+        vec![(2..3, t0), (3..4, t1)]
+    );
+}
+
 /// Ensure that we dirty lines as the cursor is moved around, otherwise
 /// the renderer won't draw the cursor in the right place
 #[test]
@@ -1265,3 +1316,81 @@ fn test_hyperlinks() {
         Compare::TEXT | Compare::ATTRS,
     );
 }
+
+#[test]
+fn test_conemu_progress() {
+    let mut term = TestTerm::new(3, 5, 0);
+    assert_eq!(term.progress(), Progress::None);
+
+    term.print("\x1b]9;4;1;42\x1b\\");
+    assert_eq!(term.progress(), Progress::SetPercentage(42));
+
+    term.print("\x1b]9;4;3\x1b\\");
+    assert_eq!(term.progress(), Progress::SetIndeterminate);
+
+    // A full reset clears any progress left behind by an application
+    term.print("\x1bc");
+    assert_eq!(term.progress(), Progress::None);
+}
+
+#[test]
+fn test_pointer_shape() {
+    let mut term = TestTerm::new(3, 5, 0);
+    assert_eq!(term.pointer_shape(), None);
+
+    // The first supported name is used
+    term.print("\x1b]22;wait,hand2\x1b\\");
+    assert_eq!(term.pointer_shape(), Some(PointerShape::Pointer));
+
+    term.print("\x1b]22;>text\x1b\\");
+    assert_eq!(term.pointer_shape(), Some(PointerShape::Text));
+
+    // Unsupported shapes are ignored
+    term.print("\x1b]22;>wait\x1b\\");
+    assert_eq!(term.pointer_shape(), Some(PointerShape::Text));
+
+    term.print("\x1b]22;<\x1b\\");
+    assert_eq!(term.pointer_shape(), Some(PointerShape::Pointer));
+
+    term.print("\x1b]22;=ew-resize\x1b\\");
+    assert_eq!(term.pointer_shape(), Some(PointerShape::ColumnResize));
+
+    // An empty name resets the shape
+    term.print("\x1b]22;\x1b\\");
+    assert_eq!(term.pointer_shape(), None);
+}
+
+#[test]
+fn test_text_sizing() {
+    /// Returns the scale and block row of the sized text at x, y
+    fn scaled(term: &TestTerm, x: usize, y: usize) -> Option<(u8, u8)> {
+        term.screen().visible_lines()[y]
+            .get_cell(x)
+            .and_then(|cell| cell.attrs().scaled_text())
+            .map(|scaled| (scaled.size.scale, scaled.row))
+    }
+
+    let mut term = TestTerm::new(4, 8, 0);
+    term.print("a\x1b]66;s=2;bc\x1b\\d");
+    // Each grapheme gets a 2x2 block and the cursor lands after them
+    term.assert_cursor_pos(6, 0, None, None);
+    assert_eq!(term.screen().visible_lines()[0].as_str(), "abcd");
+    assert_eq!(scaled(&term, 0, 0), None);
+    assert_eq!(scaled(&term, 1, 0), Some((2, 0)));
+    assert_eq!(scaled(&term, 3, 0), Some((2, 0)));
+    // The cells below the text are reserved for it
+    assert_eq!(scaled(&term, 1, 1), Some((2, 1)));
+    assert_eq!(scaled(&term, 3, 1), Some((2, 1)));
+    assert_eq!(scaled(&term, 5, 1), None);
+
+    // A block with an explicit width holds all of its text,
+    // and the screen scrolls so that the block fits
+    term.print("\r\n\n\n\x1b]66;s=3:w=1;xy\x1b\\");
+    term.assert_cursor_pos(3, 1, None, None);
+    assert_eq!(scaled(&term, 0, 1), Some((3, 0)));
+    assert_eq!(scaled(&term, 0, 3), Some((3, 2)));
+    assert_eq!(
+        term.screen().visible_lines()[1].get_cell(0).unwrap().str(),
+        "xy"
+    );
+}
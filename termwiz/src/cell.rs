@@ -87,6 +87,8 @@ struct FatAttributes {
     underline_color: ColorAttribute,
     foreground: ColorAttribute,
     background: ColorAttribute,
+    /// Set for cells that hold text sized via `OSC 66`
+    scaled_text: Option<ScaledText>,
 }
 
 impl FatAttributes {
@@ -100,6 +102,7 @@ impl FatAttributes {
         self.underline_color.hash(hasher);
         self.foreground.hash(hasher);
         self.background.hash(hasher);
+        self.scaled_text.hash(hasher);
     }
 }
 
@@ -258,6 +261,87 @@ pub enum VerticalAlign {
     SubScript = 2,
 }
 
+/// How text that is smaller than its block is positioned vertically
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+pub enum TextSizeVerticalAlign {
+    #[default]
+    Top = 0,
+    Bottom = 1,
+    Center = 2,
+}
+
+/// How text that is narrower than its block is positioned horizontally
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+pub enum TextSizeHorizontalAlign {
+    #[default]
+    Left = 0,
+    Right = 1,
+    Center = 2,
+}
+
+/// The size of text drawn using the kitty text sizing protocol.
+/// The text occupies a block of `scale` rows and `scale * width`
+/// columns, and its font is scaled by `scale` and, optionally,
+/// by the fraction `numerator / denominator`.
+/// See <https://sw.kovidgoyal.net/kitty/text-sizing-protocol/>
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextSize {
+    /// The number of rows in the block, in the range 1-7
+    pub scale: u8,
+    /// The width of the text in scaled cells, in the range 0-7.
+    /// When zero, each grapheme is given a block whose width
+    /// is derived from its unicode width.
+    pub width: u8,
+    /// The fraction is only applied when the denominator is
+    /// larger than the numerator.  Both are in the range 0-15.
+    pub numerator: u8,
+    pub denominator: u8,
+    pub vertical_align: TextSizeVerticalAlign,
+    pub horizontal_align: TextSizeHorizontalAlign,
+}
+
+impl Default for TextSize {
+    fn default() -> Self {
+        Self {
+            scale: 1,
+            width: 0,
+            numerator: 0,
+            denominator: 0,
+            vertical_align: TextSizeVerticalAlign::default(),
+            horizontal_align: TextSizeHorizontalAlign::default(),
+        }
+    }
+}
+
+impl TextSize {
+    /// Returns the factor by which the font size is scaled
+    pub fn font_scale(&self) -> f32 {
+        let fraction = if self.numerator > 0 && self.denominator > self.numerator {
+            self.numerator as f32 / self.denominator as f32
+        } else {
+            1.0
+        };
+        self.scale as f32 * fraction
+    }
+}
+
+/// Marks a cell as belonging to a block of sized text.
+/// The text is held by the cell in the first row of the block;
+/// the cells in the rows below it are blank and reserve the
+/// space that the text extends over.
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScaledText {
+    pub size: TextSize,
+    /// The row of the block that the cell is in
+    pub row: u8,
+}
+
 impl Default for CellAttributes {
     fn default() -> Self {
         Self::blank()
@@ -276,6 +360,8 @@ impl CellAttributes {
     bitfield!(overline, set_overline, 12);
     bitfield!(semantic_type, set_semantic_type, SemanticType, 0b11, 13);
     bitfield!(vertical_align, set_vertical_align, VerticalAlign, 0b11, 15);
+    // Set by DECSCA; protected cells are skipped by selective erase
+    bitfield!(protected, set_protected, 17);
 
     pub const fn blank() -> Self {
         Self {
@@ -383,6 +469,7 @@ impl CellAttributes {
                 underline_color: ColorAttribute::Default,
                 foreground: ColorAttribute::Default,
                 background: ColorAttribute::Default,
+                scaled_text: None,
             }));
         }
     }
@@ -397,6 +484,7 @@ impl CellAttributes {
                     && fat.underline_color == ColorAttribute::Default
                     && fat.foreground == ColorAttribute::Default
                     && fat.background == ColorAttribute::Default
+                    && fat.scaled_text.is_none()
             })
             .unwrap_or(false);
         if deallocate {
@@ -469,6 +557,17 @@ impl CellAttributes {
         }
     }
 
+    pub fn set_scaled_text(&mut self, scaled_text: Option<ScaledText>) -> &mut Self {
+        if scaled_text.is_none() && self.fat.is_none() {
+            self
+        } else {
+            self.allocate_fat_attributes();
+            self.fat.as_mut().unwrap().scaled_text = scaled_text;
+            self.deallocate_fat_attributes_if_none();
+            self
+        }
+    }
+
     /// Clone the attributes, but exclude fancy extras such
     /// as hyperlinks or future sprite things
     pub fn clone_sgr_only(&self) -> Self {
@@ -494,6 +593,8 @@ impl CellAttributes {
         // easier time in get_semantic_zones.
         res.set_semantic_type(SemanticType::default());
         res.set_underline_color(self.underline_color());
+        // Erased cells are never protected from selective erase
+        res.set_protected(false);
 
         // Turn off underline because it can have surprising results
         // if underline is on, then we get CRLF and then SGR reset:
@@ -533,6 +634,12 @@ impl CellAttributes {
             .unwrap_or(ColorAttribute::Default)
     }
 
+    /// Returns the size of the text in the cell, if it was sized
+    /// via `OSC 66`
+    pub fn scaled_text(&self) -> Option<ScaledText> {
+        self.fat.as_ref().and_then(|fat| fat.scaled_text)
+    }
+
     pub fn apply_change(&mut self, change: &AttributeChange) {
         use AttributeChange::*;
         match change {
@@ -79,7 +79,12 @@ impl CellCluster {
                     ))
                 }
                 Some(mut last) => {
-                    if last.attrs != *normalized_attr || last.presentation != presentation {
+                    // Each block of text sized via OSC 66 is laid out
+                    // independently, so it gets a cluster of its own
+                    if last.attrs != *normalized_attr
+                        || last.presentation != presentation
+                        || normalized_attr.scaled_text().is_some()
+                    {
                         // Flush pending cluster and start a new one
                         clusters.push(last);
 
@@ -612,6 +612,11 @@ pub struct KittyImagePlacement {
     pub placement_id: Option<u32>,
     /// z=...
     pub z_index: Option<i32>,
+    /// Create a virtual placement, which is displayed wherever
+    /// Unicode placeholder characters that refer to it are printed,
+    /// rather than at the cursor position.
+    /// U=0, U=1
+    pub virtual_placement: bool,
 }
 
 impl KittyImagePlacement {
@@ -632,6 +637,11 @@ impl KittyImagePlacement {
                 _ => return None,
             },
             z_index: geti(keys, "z"),
+            virtual_placement: match get(keys, "U") {
+                None | Some("0") => false,
+                Some("1") => true,
+                _ => return None,
+            },
         })
     }
 
@@ -651,6 +661,10 @@ impl KittyImagePlacement {
         }
 
         set(keys, "z", &self.z_index);
+
+        if self.virtual_placement {
+            keys.insert("U", "1".to_string());
+        }
     }
 }
 
@@ -1268,5 +1282,28 @@ mod test {
                 },
             }
         );
+
+        assert_eq!(
+            KittyImage::parse_apc("Ga=p,U=1,i=5,c=4,r=2,q=2".as_bytes()).unwrap(),
+            KittyImage::Display {
+                image_id: Some(5),
+                image_number: None,
+                placement: KittyImagePlacement {
+                    x: None,
+                    y: None,
+                    w: None,
+                    h: None,
+                    x_offset: None,
+                    y_offset: None,
+                    columns: Some(4),
+                    rows: Some(2),
+                    do_not_move_cursor: false,
+                    placement_id: None,
+                    z_index: None,
+                    virtual_placement: true,
+                },
+                verbosity: KittyImageVerbosity::Quiet,
+            }
+        );
     }
 }
@@ -33,6 +33,12 @@ pub enum CSI {
     /// ECMA-48 SCP
     SelectCharacterPath(CharacterPath, i64),
 
+    /// DECSCA
+    SelectCharacterProtection(CharacterProtection),
+
+    /// DECCRA, DECFRA, DECERA, DECSERA, DECCARA and DECSACE
+    RectangularArea(Box<RectangularArea>),
+
     /// Unknown or unspecified; should be rare and is rather
     /// large, so it is boxed and kept outside of the enum
     /// body to help reduce space usage in the common cases.
@@ -87,6 +93,183 @@ pub enum CharacterPath {
     RightToLeftOrBottomToTop,
 }
 
+/// DECSCA - Select Character Protection Attribute.
+/// Characters written while the protection attribute is set
+/// cannot be erased by the selective erase operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+pub enum CharacterProtection {
+    /// 0; characters can be erased
+    Default = 0,
+    /// 1; characters cannot be erased by a selective erase
+    Protected = 1,
+    /// 2; characters can be erased
+    Unprotected = 2,
+}
+
+/// A rectangular area of the screen, as used by the DEC rectangular
+/// area operations.  The coordinates are relative to the origin
+/// established by DECOM.  When the bottom or right edge is omitted
+/// it is set to `u32::max_value()`, which means the last line or
+/// column of the page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rectangle {
+    pub top: OneBased,
+    pub left: OneBased,
+    pub bottom: OneBased,
+    pub right: OneBased,
+}
+
+impl Rectangle {
+    fn parse(params: &Cracked, first: usize) -> Result<Self, ()> {
+        let edge = |idx: usize| match params.get(first + idx) {
+            Some(p) => OneBased::from_esc_param_with_big_default(p),
+            None => Ok(OneBased::new(u32::max_value())),
+        };
+        Ok(Self {
+            top: OneBased::from_optional_esc_param(params.get(first))?,
+            left: OneBased::from_optional_esc_param(params.get(first + 1))?,
+            bottom: edge(2)?,
+            right: edge(3)?,
+        })
+    }
+}
+
+impl Display for Rectangle {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        fn edge(value: &OneBased) -> String {
+            if value.as_one_based() == u32::max_value() {
+                String::new()
+            } else {
+                value.to_string()
+            }
+        }
+        write!(
+            f,
+            "{};{};{};{}",
+            self.top,
+            self.left,
+            edge(&self.bottom),
+            edge(&self.right)
+        )
+    }
+}
+
+/// DECSACE - Select Attribute Change Extent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeChangeExtent {
+    /// 0 or 1; DECCARA applies to the characters that lie between
+    /// the start and end positions, in the order that they were written
+    Stream,
+    /// 2; DECCARA applies to the characters within the rectangle
+    Rectangle,
+}
+
+/// The DEC rectangular area operations, introduced in the VT400 series
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RectangularArea {
+    /// DECCRA - Copy Rectangular Area.
+    /// Copies the `source` area so that its top left corner is placed
+    /// at `dest_top`, `dest_left`.  wezterm has only a single page.
+    Copy {
+        source: Rectangle,
+        source_page: OneBased,
+        dest_top: OneBased,
+        dest_left: OneBased,
+        dest_page: OneBased,
+    },
+    /// DECFRA - Fill Rectangular Area.
+    /// Fills the area with `character`, using the current rendition.
+    Fill { character: char, area: Rectangle },
+    /// DECERA - Erase Rectangular Area
+    Erase(Rectangle),
+    /// DECSERA - Selective Erase Rectangular Area.
+    /// Only erases the characters that are not protected by DECSCA.
+    SelectiveErase(Rectangle),
+    /// DECCARA - Change Attributes in Rectangular Area.
+    /// Only bold, underline, blink, reverse and invisible can be
+    /// changed; `Sgr::Reset` turns all of them off.
+    ChangeAttributes {
+        area: Rectangle,
+        attributes: Vec<Sgr>,
+    },
+    /// DECSACE - Select Attribute Change Extent
+    SelectAttributeChangeExtent(AttributeChangeExtent),
+}
+
+impl RectangularArea {
+    /// Maps a DECCARA attribute parameter to the Sgr that it applies
+    fn attribute_from_code(code: i64) -> Result<Sgr, ()> {
+        Ok(match FromPrimitive::from_i64(code).ok_or(())? {
+            SgrCode::Reset => Sgr::Reset,
+            SgrCode::IntensityBold => Sgr::Intensity(Intensity::Bold),
+            SgrCode::NormalIntensity => Sgr::Intensity(Intensity::Normal),
+            SgrCode::UnderlineOn => Sgr::Underline(Underline::Single),
+            SgrCode::UnderlineOff => Sgr::Underline(Underline::None),
+            SgrCode::BlinkOn => Sgr::Blink(Blink::Slow),
+            SgrCode::BlinkOff => Sgr::Blink(Blink::None),
+            SgrCode::InverseOn => Sgr::Inverse(true),
+            SgrCode::InverseOff => Sgr::Inverse(false),
+            SgrCode::InvisibleOn => Sgr::Invisible(true),
+            SgrCode::InvisibleOff => Sgr::Invisible(false),
+            _ => return Err(()),
+        })
+    }
+
+    fn attribute_code(sgr: &Sgr) -> Result<i64, FmtError> {
+        let code = match sgr {
+            Sgr::Reset => SgrCode::Reset,
+            Sgr::Intensity(Intensity::Bold) => SgrCode::IntensityBold,
+            Sgr::Intensity(Intensity::Normal) => SgrCode::NormalIntensity,
+            Sgr::Underline(Underline::Single) => SgrCode::UnderlineOn,
+            Sgr::Underline(Underline::None) => SgrCode::UnderlineOff,
+            Sgr::Blink(Blink::Slow) => SgrCode::BlinkOn,
+            Sgr::Blink(Blink::None) => SgrCode::BlinkOff,
+            Sgr::Inverse(true) => SgrCode::InverseOn,
+            Sgr::Inverse(false) => SgrCode::InverseOff,
+            Sgr::Invisible(true) => SgrCode::InvisibleOn,
+            Sgr::Invisible(false) => SgrCode::InvisibleOff,
+            _ => return Err(FmtError),
+        };
+        Ok(code as i64)
+    }
+}
+
+impl Display for RectangularArea {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match self {
+            RectangularArea::Copy {
+                source,
+                source_page,
+                dest_top,
+                dest_left,
+                dest_page,
+            } => write!(
+                f,
+                "{};{};{};{};{}$v",
+                source, source_page, dest_top, dest_left, dest_page
+            ),
+            RectangularArea::Fill { character, area } => {
+                write!(f, "{};{}$x", *character as u32, area)
+            }
+            RectangularArea::Erase(area) => write!(f, "{}$z", area),
+            RectangularArea::SelectiveErase(area) => write!(f, "{}${{", area),
+            RectangularArea::ChangeAttributes { area, attributes } => {
+                write!(f, "{}", area)?;
+                for sgr in attributes {
+                    write!(f, ";{}", Self::attribute_code(sgr)?)?;
+                }
+                write!(f, "$r")
+            }
+            RectangularArea::SelectAttributeChangeExtent(AttributeChangeExtent::Stream) => {
+                write!(f, "1*x")
+            }
+            RectangularArea::SelectAttributeChangeExtent(AttributeChangeExtent::Rectangle) => {
+                write!(f, "2*x")
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Unspecified {
     pub params: Vec<CsiParam>,
@@ -144,6 +327,8 @@ impl Display for CSI {
                     (a, n) => write!(f, "{};{} k", a, n)?,
                 }
             }
+            CSI::SelectCharacterProtection(protection) => write!(f, "{}\"q", *protection as u8)?,
+            CSI::RectangularArea(area) => area.fmt(f)?,
         };
         Ok(())
     }
@@ -1127,6 +1312,16 @@ pub enum Edit {
 
     /// REP - Repeat the preceding character n times
     Repeat(u32),
+
+    /// DECSED - Selective Erase in Display.
+    /// Like ED, but leaves the characters protected by DECSCA intact.
+    /// <https://vt100.net/docs/vt510-rm/DECSED.html>
+    SelectiveEraseInDisplay(EraseInDisplay),
+
+    /// DECSEL - Selective Erase in Line.
+    /// Like EL, but leaves the characters protected by DECSCA intact.
+    /// <https://vt100.net/docs/vt510-rm/DECSEL.html>
+    SelectiveEraseInLine(EraseInLine),
 }
 
 trait EncodeCSIParam {
@@ -1177,6 +1372,14 @@ impl Display for Edit {
             Edit::ScrollUp(n) => n.write_csi(f, "S")?,
             Edit::EraseInDisplay(n) => n.write_csi(f, "J")?,
             Edit::Repeat(n) => n.write_csi(f, "b")?,
+            Edit::SelectiveEraseInDisplay(n) => {
+                write!(f, "?")?;
+                n.write_csi(f, "J")?
+            }
+            Edit::SelectiveEraseInLine(n) => {
+                write!(f, "?")?;
+                n.write_csi(f, "K")?
+            }
         }
         Ok(())
     }
@@ -1730,6 +1933,13 @@ impl<'a> CSIParser<'a> {
             ('k', [.., CsiParam::P(b' ')]) => self.select_character_path(params),
             ('q', [.., CsiParam::P(b' ')]) => self.cursor_style(params),
             ('y', [.., CsiParam::P(b'*')]) => self.checksum_area(params),
+            ('x', [.., CsiParam::P(b'*')]) => self.attribute_change_extent(params),
+            ('q', [.., CsiParam::P(b'"')]) => self.character_protection(params),
+            ('v', [.., CsiParam::P(b'$')])
+            | ('x', [.., CsiParam::P(b'$')])
+            | ('z', [.., CsiParam::P(b'$')])
+            | ('{', [.., CsiParam::P(b'$')])
+            | ('r', [.., CsiParam::P(b'$')]) => self.rectangular_area(params),
 
             ('c', [CsiParam::P(b'='), ..]) => self
                 .req_tertiary_device_attributes(params)
@@ -1747,6 +1957,12 @@ impl<'a> CSIParser<'a> {
                 .map(|dev| CSI::Device(Box::new(dev))),
 
             ('S', [CsiParam::P(b'?'), ..]) => XtSmGraphics::parse(params),
+            ('J', [CsiParam::P(b'?'), ..]) => {
+                parse!(Edit, SelectiveEraseInDisplay, self.focus(params, 1, 0))
+            }
+            ('K', [CsiParam::P(b'?'), ..]) => {
+                parse!(Edit, SelectiveEraseInLine, self.focus(params, 1, 0))
+            }
             ('p', [CsiParam::Integer(_), CsiParam::P(b'$')])
             | ('p', [CsiParam::P(b'?'), CsiParam::Integer(_), CsiParam::P(b'$')]) => {
                 self.decrqm(params)
@@ -1967,6 +2183,70 @@ impl<'a> CSIParser<'a> {
         })))
     }
 
+    fn character_protection(&mut self, params: &'a [CsiParam]) -> Result<CSI, ()> {
+        let protection = match params {
+            [CsiParam::P(b'"')] => CharacterProtection::Default,
+            [CsiParam::Integer(p), CsiParam::P(b'"')] => FromPrimitive::from_i64(*p).ok_or(())?,
+            _ => return Err(()),
+        };
+        Ok(CSI::SelectCharacterProtection(protection))
+    }
+
+    fn attribute_change_extent(&mut self, params: &'a [CsiParam]) -> Result<CSI, ()> {
+        let extent = match params {
+            [CsiParam::P(b'*')] | [CsiParam::Integer(0 | 1), CsiParam::P(b'*')] => {
+                AttributeChangeExtent::Stream
+            }
+            [CsiParam::Integer(2), CsiParam::P(b'*')] => AttributeChangeExtent::Rectangle,
+            _ => return Err(()),
+        };
+        Ok(CSI::RectangularArea(Box::new(
+            RectangularArea::SelectAttributeChangeExtent(extent),
+        )))
+    }
+
+    fn rectangular_area(&mut self, params: &'a [CsiParam]) -> Result<CSI, ()> {
+        let params = Cracked::parse(&params[..params.len() - 1])?;
+
+        let area = match self.control {
+            'v' => RectangularArea::Copy {
+                source: Rectangle::parse(&params, 0)?,
+                source_page: OneBased::from_optional_esc_param(params.get(4))?,
+                dest_top: OneBased::from_optional_esc_param(params.get(5))?,
+                dest_left: OneBased::from_optional_esc_param(params.get(6))?,
+                dest_page: OneBased::from_optional_esc_param(params.get(7))?,
+            },
+            'x' => RectangularArea::Fill {
+                character: params
+                    .int(0)?
+                    .try_into()
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or(())?,
+                area: Rectangle::parse(&params, 1)?,
+            },
+            'z' => RectangularArea::Erase(Rectangle::parse(&params, 0)?),
+            '{' => RectangularArea::SelectiveErase(Rectangle::parse(&params, 0)?),
+            'r' => {
+                let mut attributes = vec![];
+                for idx in 4..params.len() {
+                    attributes.push(RectangularArea::attribute_from_code(
+                        params.opt_int(idx).unwrap_or(0),
+                    )?);
+                }
+                if attributes.is_empty() {
+                    attributes.push(Sgr::Reset);
+                }
+                RectangularArea::ChangeAttributes {
+                    area: Rectangle::parse(&params, 0)?,
+                    attributes,
+                }
+            }
+            _ => return Err(()),
+        };
+        Ok(CSI::RectangularArea(Box::new(area)))
+    }
+
     fn dsr(&mut self, params: &'a [CsiParam]) -> Result<CSI, ()> {
         match params {
             [CsiParam::Integer(5)] => {
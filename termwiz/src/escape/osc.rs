@@ -1,3 +1,4 @@
+use crate::cell::{TextSize, TextSizeHorizontalAlign, TextSizeVerticalAlign};
 use crate::color::SrgbaTuple;
 pub use crate::hyperlink::Hyperlink;
 use crate::{bail, ensure, Result};
@@ -6,10 +7,13 @@ use bitflags::bitflags;
 use num_derive::*;
 use num_traits::FromPrimitive;
 use ordered_float::NotNan;
+#[cfg(feature = "use_serde")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::{Display, Error as FmtError, Formatter, Result as FmtResult};
 use std::str;
 use std::str::FromStr;
+use wezterm_dynamic::{FromDynamic, ToDynamic};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ColorOrQuery {
@@ -38,6 +42,12 @@ pub enum OperatingSystemCommand {
     QuerySelection(Selection),
     SetSelection(Selection, String),
     SystemNotification(String),
+    /// ConEmu's `OSC 9;4` taskbar progress indicator, which is also
+    /// supported by Windows Terminal
+    ConEmuProgress(Progress),
+    SetPointerShape(PointerShapeRequest),
+    /// kitty's `OSC 66` text sizing protocol
+    TextSizing(TextSize, String),
     ITermProprietary(ITermProprietary),
     FinalTermSemanticPrompt(FinalTermSemanticPrompt),
     ChangeColorNumber(Vec<ChangeColorPair>),
@@ -50,6 +60,147 @@ pub enum OperatingSystemCommand {
     Unspecified(Vec<Vec<u8>>),
 }
 
+/// The state of a progress indicator, as reported by `OSC 9;4;st;pr`.
+/// Percentages are in the range 0-100.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, FromDynamic, ToDynamic)]
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+pub enum Progress {
+    /// There is no operation in progress
+    #[default]
+    None,
+    SetPercentage(u8),
+    /// The operation has failed
+    SetError(u8),
+    /// The operation is ongoing, but its completion is unknown
+    SetIndeterminate,
+    /// The operation has been paused
+    Paused(u8),
+}
+
+impl Progress {
+    fn parse(osc: &[&[u8]]) -> Result<Self> {
+        let state = osc.get(2).copied().unwrap_or(b"0");
+        let state: u8 = if state.is_empty() {
+            0
+        } else {
+            str::from_utf8(state)?.parse()?
+        };
+        let percent: u8 = match osc.get(3) {
+            Some(pr) if !pr.is_empty() => str::from_utf8(pr)?.parse::<u32>()?.min(100) as u8,
+            _ => 0,
+        };
+        Ok(match state {
+            0 => Self::None,
+            1 => Self::SetPercentage(percent),
+            2 => Self::SetError(percent),
+            3 => Self::SetIndeterminate,
+            4 => Self::Paused(percent),
+            _ => bail!("invalid progress state {}", state),
+        })
+    }
+
+    /// Returns the completion percentage, if known
+    pub fn percentage(&self) -> Option<u8> {
+        match self {
+            Self::SetPercentage(pr) | Self::SetError(pr) | Self::Paused(pr) => Some(*pr),
+            Self::None | Self::SetIndeterminate => None,
+        }
+    }
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::None => write!(f, "0"),
+            Self::SetPercentage(pr) => write!(f, "1;{}", pr),
+            Self::SetError(pr) => write!(f, "2;{}", pr),
+            Self::SetIndeterminate => write!(f, "3"),
+            Self::Paused(pr) => write!(f, "4;{}", pr),
+        }
+    }
+}
+
+/// A request to change the shape of the mouse pointer, made via `OSC 22`.
+/// Shapes are identified by their CSS cursor names or by the names of
+/// the X11 cursor font glyphs.  Where several names are listed, the
+/// first that the terminal supports is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PointerShapeRequest {
+    /// Replace the current shape.  An empty list resets the shape.
+    Set(Vec<String>),
+    /// Save the current shape on a stack and then replace it
+    Push(Vec<String>),
+    /// Restore the most recently pushed shape
+    Pop,
+    /// Ask which of the listed shapes are supported
+    Query(Vec<String>),
+}
+
+impl PointerShapeRequest {
+    fn parse(osc: &[&[u8]]) -> Result<Self> {
+        ensure!(osc.len() <= 2, "wrong param count");
+        let spec = match osc.get(1) {
+            Some(spec) => str::from_utf8(spec)?,
+            None => "",
+        };
+        let names = |list: &str| -> Vec<String> {
+            list.split(',')
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        Ok(match spec.chars().next() {
+            Some('=') => Self::Set(names(&spec[1..])),
+            Some('>') => Self::Push(names(&spec[1..])),
+            Some('<') => Self::Pop,
+            Some('?') => Self::Query(names(&spec[1..])),
+            _ => Self::Set(names(spec)),
+        })
+    }
+}
+
+impl Display for PointerShapeRequest {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::Set(names) => write!(f, "{}", names.join(",")),
+            Self::Push(names) => write!(f, ">{}", names.join(",")),
+            Self::Pop => write!(f, "<"),
+            Self::Query(names) => write!(f, "?{}", names.join(",")),
+        }
+    }
+}
+
+/// Formats the metadata of an `OSC 66` sequence, omitting the
+/// keys that have their default values
+struct TextSizeMetadata<'a>(&'a TextSize);
+
+impl<'a> Display for TextSizeMetadata<'a> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let size = self.0;
+        let default = TextSize::default();
+        let mut items = vec![];
+        if size.scale != default.scale {
+            items.push(format!("s={}", size.scale));
+        }
+        if size.width != default.width {
+            items.push(format!("w={}", size.width));
+        }
+        if size.numerator != default.numerator {
+            items.push(format!("n={}", size.numerator));
+        }
+        if size.denominator != default.denominator {
+            items.push(format!("d={}", size.denominator));
+        }
+        if size.vertical_align != default.vertical_align {
+            items.push(format!("v={}", size.vertical_align as u8));
+        }
+        if size.horizontal_align != default.horizontal_align {
+            items.push(format!("h={}", size.horizontal_align as u8));
+        }
+        write!(f, "{}", items.join(":"))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
 #[repr(u8)]
 pub enum DynamicColorNumber {
@@ -249,6 +400,64 @@ impl OperatingSystemCommand {
         ))
     }
 
+    fn parse_text_sizing(osc: &[&[u8]]) -> Result<Self> {
+        ensure!(osc.len() >= 3, "wrong param count");
+        let mut size = TextSize::default();
+        for item in str::from_utf8(osc[1])?.split(':') {
+            if item.is_empty() {
+                continue;
+            }
+            let (key, value) = item
+                .split_once('=')
+                .ok_or_else(|| format!("invalid text sizing metadata {}", item))?;
+            let value: u8 = value.parse()?;
+            match key {
+                "s" => {
+                    ensure!((1..=7).contains(&value), "invalid scale {}", value);
+                    size.scale = value;
+                }
+                "w" => {
+                    ensure!(value <= 7, "invalid width {}", value);
+                    size.width = value;
+                }
+                "n" => {
+                    ensure!(value <= 15, "invalid numerator {}", value);
+                    size.numerator = value;
+                }
+                "d" => {
+                    ensure!(value <= 15, "invalid denominator {}", value);
+                    size.denominator = value;
+                }
+                "v" => {
+                    size.vertical_align = match value {
+                        0 => TextSizeVerticalAlign::Top,
+                        1 => TextSizeVerticalAlign::Bottom,
+                        2 => TextSizeVerticalAlign::Center,
+                        _ => bail!("invalid vertical alignment {}", value),
+                    };
+                }
+                "h" => {
+                    size.horizontal_align = match value {
+                        0 => TextSizeHorizontalAlign::Left,
+                        1 => TextSizeHorizontalAlign::Right,
+                        2 => TextSizeHorizontalAlign::Center,
+                        _ => bail!("invalid horizontal alignment {}", value),
+                    };
+                }
+                _ => bail!("unknown text sizing key {}", key),
+            }
+        }
+
+        // The text may itself contain semicolons
+        let mut text = String::from_utf8(osc[2].to_vec())?;
+        for item in &osc[3..] {
+            text.push(';');
+            text.push_str(str::from_utf8(item)?);
+        }
+
+        Ok(OperatingSystemCommand::TextSizing(size, text))
+    }
+
     fn internal_parse(osc: &[&[u8]]) -> Result<Self> {
         ensure!(!osc.is_empty(), "no params");
         let p1str = String::from_utf8_lossy(osc[0]);
@@ -311,8 +520,15 @@ impl OperatingSystemCommand {
             )),
             SetHyperlink => Ok(OperatingSystemCommand::SetHyperlink(Hyperlink::parse(osc)?)),
             ManipulateSelectionData => Self::parse_selection(osc),
+            SystemNotification if osc.len() > 2 && osc[1] == b"4" => {
+                Progress::parse(osc).map(OperatingSystemCommand::ConEmuProgress)
+            }
             SystemNotification => single_string!(SystemNotification),
             SetCurrentWorkingDirectory => single_string!(CurrentWorkingDirectory),
+            SetPointerShape => {
+                self::PointerShapeRequest::parse(osc).map(OperatingSystemCommand::SetPointerShape)
+            }
+            TextSizing => Self::parse_text_sizing(osc),
             ITermProprietary => {
                 self::ITermProprietary::parse(osc).map(OperatingSystemCommand::ITermProprietary)
             }
@@ -419,10 +635,14 @@ osc_entries!(
     SetHighlightBackgroundColor = "17",
     SetTektronixCursorColor = "18",
     SetHighlightForegroundColor = "19",
+    /// See https://sw.kovidgoyal.net/kitty/pointer-shapes/
+    SetPointerShape = "22",
     SetLogFileName = "46",
     SetFont = "50",
     EmacsShell = "51",
     ManipulateSelectionData = "52",
+    /// See https://sw.kovidgoyal.net/kitty/text-sizing-protocol/
+    TextSizing = "66",
     ResetColors = "104",
     ResetSpecialColor = "105",
     ResetTextForegroundColor = "110",
@@ -509,6 +729,7 @@ impl Display for OperatingSystemCommand {
             QuerySelection(s) => write!(f, "52;{};?", s)?,
             SetSelection(s, val) => write!(f, "52;{};{}", s, base64_encode(val))?,
             SystemNotification(s) => write!(f, "9;{}", s)?,
+            ConEmuProgress(progress) => write!(f, "9;4;{}", progress)?,
             ITermProprietary(i) => i.fmt(f)?,
             FinalTermSemanticPrompt(i) => i.fmt(f)?,
             ResetColors(colors) => {
@@ -533,6 +754,8 @@ impl Display for OperatingSystemCommand {
                 write!(f, "{}", 100 + *color as u8)?;
             }
             CurrentWorkingDirectory(s) => write!(f, "7;{}", s)?,
+            SetPointerShape(request) => write!(f, "22;{}", request)?,
+            TextSizing(size, text) => write!(f, "66;{};{}", TextSizeMetadata(size), text)?,
         };
         // Use the longer form ST as neovim doesn't like the BEL version
         write!(f, "\x1b\\")?;
@@ -1395,6 +1618,115 @@ mod test {
         );
     }
 
+    #[test]
+    fn conemu_progress() {
+        assert_eq!(
+            parse(&["9", "4", "1", "42"], "\x1b]9;4;1;42\x1b\\"),
+            OperatingSystemCommand::ConEmuProgress(Progress::SetPercentage(42))
+        );
+        assert_eq!(
+            parse(&["9", "4", "2", "150"], "\x1b]9;4;2;100\x1b\\"),
+            OperatingSystemCommand::ConEmuProgress(Progress::SetError(100))
+        );
+        assert_eq!(
+            parse(&["9", "4", "3"], "\x1b]9;4;3\x1b\\"),
+            OperatingSystemCommand::ConEmuProgress(Progress::SetIndeterminate)
+        );
+        assert_eq!(
+            parse(&["9", "4", "4"], "\x1b]9;4;4;0\x1b\\"),
+            OperatingSystemCommand::ConEmuProgress(Progress::Paused(0))
+        );
+        assert_eq!(
+            parse(&["9", "4", "0"], "\x1b]9;4;0\x1b\\"),
+            OperatingSystemCommand::ConEmuProgress(Progress::None)
+        );
+
+        // Other forms of OSC 9 remain notifications
+        assert_eq!(
+            parse(&["9", "hello"], "\x1b]9;hello\x1b\\"),
+            OperatingSystemCommand::SystemNotification("hello".into())
+        );
+    }
+
+    #[test]
+    fn pointer_shape() {
+        assert_eq!(
+            parse(&["22", "pointer"], "\x1b]22;pointer\x1b\\"),
+            OperatingSystemCommand::SetPointerShape(PointerShapeRequest::Set(vec![
+                "pointer".into()
+            ]))
+        );
+        assert_eq!(
+            parse(&["22", "=hand2,pointer"], "\x1b]22;hand2,pointer\x1b\\"),
+            OperatingSystemCommand::SetPointerShape(PointerShapeRequest::Set(vec![
+                "hand2".into(),
+                "pointer".into()
+            ]))
+        );
+        assert_eq!(
+            parse(&["22"], "\x1b]22;\x1b\\"),
+            OperatingSystemCommand::SetPointerShape(PointerShapeRequest::Set(vec![]))
+        );
+        assert_eq!(
+            parse(&["22", ">text"], "\x1b]22;>text\x1b\\"),
+            OperatingSystemCommand::SetPointerShape(PointerShapeRequest::Push(vec!["text".into()]))
+        );
+        assert_eq!(
+            parse(&["22", "<"], "\x1b]22;<\x1b\\"),
+            OperatingSystemCommand::SetPointerShape(PointerShapeRequest::Pop)
+        );
+        assert_eq!(
+            parse(&["22", "?text,wait"], "\x1b]22;?text,wait\x1b\\"),
+            OperatingSystemCommand::SetPointerShape(PointerShapeRequest::Query(vec![
+                "text".into(),
+                "wait".into()
+            ]))
+        );
+    }
+
+    #[test]
+    fn text_sizing() {
+        assert_eq!(
+            parse(&["66", "s=2", "hello"], "\x1b]66;s=2;hello\x1b\\"),
+            OperatingSystemCommand::TextSizing(
+                TextSize {
+                    scale: 2,
+                    ..Default::default()
+                },
+                "hello".into()
+            )
+        );
+        assert_eq!(
+            parse(
+                &["66", "w=1:n=1:d=2:v=2:h=1", "a", "b"],
+                "\x1b]66;w=1:n=1:d=2:v=2:h=1;a;b\x1b\\"
+            ),
+            OperatingSystemCommand::TextSizing(
+                TextSize {
+                    scale: 1,
+                    width: 1,
+                    numerator: 1,
+                    denominator: 2,
+                    vertical_align: TextSizeVerticalAlign::Center,
+                    horizontal_align: TextSizeHorizontalAlign::Right,
+                },
+                "a;b".into()
+            )
+        );
+        assert_eq!(
+            parse(&["66", "", "x"], "\x1b]66;;x\x1b\\"),
+            OperatingSystemCommand::TextSizing(TextSize::default(), "x".into())
+        );
+        assert_eq!(
+            parse(&["66", "s=9", "x"], "\x1b]66;s=9;x\x1b\\"),
+            OperatingSystemCommand::Unspecified(vec![
+                b"66".to_vec(),
+                b"s=9".to_vec(),
+                b"x".to_vec()
+            ])
+        );
+    }
+
     #[test]
     fn finalterm() {
         assert_eq!(
@@ -341,8 +341,9 @@ mod test {
     use crate::cell::{Intensity, Underline};
     use crate::color::ColorSpec;
     use crate::escape::csi::{
-        CharacterPath, DecPrivateMode, DecPrivateModeCode, Device, Mode, Sgr, Window, XtSmGraphics,
-        XtSmGraphicsItem, XtermKeyModifierResource,
+        AttributeChangeExtent, CharacterPath, CharacterProtection, DecPrivateMode,
+        DecPrivateModeCode, Device, Edit, EraseInDisplay, EraseInLine, Mode, Rectangle,
+        RectangularArea, Sgr, Window, XtSmGraphics, XtSmGraphicsItem, XtermKeyModifierResource,
     };
     use crate::escape::{EscCode, OneBased};
     use k9::assert_equal as assert_eq;
@@ -815,6 +816,97 @@ mod test {
         );
     }
 
+    #[test]
+    fn rectangular_area() {
+        let rect = |top, left, bottom, right| Rectangle {
+            top: OneBased::new(top),
+            left: OneBased::new(left),
+            bottom: OneBased::new(bottom),
+            right: OneBased::new(right),
+        };
+        let area = |area| vec![Action::CSI(CSI::RectangularArea(Box::new(area)))];
+
+        assert_eq!(
+            round_trip_parse("\x1b[1;2;3;4;1;5;6;1$v"),
+            area(RectangularArea::Copy {
+                source: rect(1, 2, 3, 4),
+                source_page: OneBased::new(1),
+                dest_top: OneBased::new(5),
+                dest_left: OneBased::new(6),
+                dest_page: OneBased::new(1),
+            })
+        );
+        assert_eq!(
+            round_trip_parse("\x1b[88;1;2;3;4$x"),
+            area(RectangularArea::Fill {
+                character: 'X',
+                area: rect(1, 2, 3, 4),
+            })
+        );
+        assert_eq!(
+            round_trip_parse("\x1b[1;2;3;4$z"),
+            area(RectangularArea::Erase(rect(1, 2, 3, 4)))
+        );
+        assert_eq!(
+            parse_as("\x1b[$z", "\x1b[1;1;;$z"),
+            area(RectangularArea::Erase(rect(
+                1,
+                1,
+                u32::max_value(),
+                u32::max_value()
+            )))
+        );
+        assert_eq!(
+            round_trip_parse("\x1b[1;2;3;4${"),
+            area(RectangularArea::SelectiveErase(rect(1, 2, 3, 4)))
+        );
+        assert_eq!(
+            round_trip_parse("\x1b[1;2;3;4;1;24$r"),
+            area(RectangularArea::ChangeAttributes {
+                area: rect(1, 2, 3, 4),
+                attributes: vec![
+                    Sgr::Intensity(Intensity::Bold),
+                    Sgr::Underline(Underline::None)
+                ],
+            })
+        );
+        assert_eq!(
+            parse_as("\x1b[1;2;3;4$r", "\x1b[1;2;3;4;0$r"),
+            area(RectangularArea::ChangeAttributes {
+                area: rect(1, 2, 3, 4),
+                attributes: vec![Sgr::Reset],
+            })
+        );
+        assert_eq!(
+            round_trip_parse("\x1b[2*x"),
+            area(RectangularArea::SelectAttributeChangeExtent(
+                AttributeChangeExtent::Rectangle
+            ))
+        );
+        assert_eq!(
+            round_trip_parse("\x1b[1\"q"),
+            vec![Action::CSI(CSI::SelectCharacterProtection(
+                CharacterProtection::Protected
+            ))]
+        );
+    }
+
+    #[test]
+    fn selective_erase() {
+        assert_eq!(
+            round_trip_parse("\x1b[?1J"),
+            vec![Action::CSI(CSI::Edit(Edit::SelectiveEraseInDisplay(
+                EraseInDisplay::EraseToStartOfDisplay
+            )))]
+        );
+        assert_eq!(
+            parse_as("\x1b[?0K", "\x1b[?K"),
+            vec![Action::CSI(CSI::Edit(Edit::SelectiveEraseInLine(
+                EraseInLine::EraseToEndOfLine
+            )))]
+        );
+    }
+
     #[test]
     fn dec_private_modes() {
         assert_eq!(
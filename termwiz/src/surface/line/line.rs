@@ -744,6 +744,9 @@ impl Line {
         if attr.hyperlink().is_some() {
             self.bits |= LineBits::HAS_HYPERLINK;
         }
+        if attr.scaled_text().is_some() {
+            self.coerce_vec_storage();
+        }
 
         if let CellStorage::C(cl) = &mut self.cells {
             if idx > cl.len() && text == " " && attr == CellAttributes::blank() {
@@ -797,6 +800,11 @@ impl Line {
         if cell.attrs().hyperlink().is_some() {
             self.bits |= LineBits::HAS_HYPERLINK;
         }
+        if cell.attrs().scaled_text().is_some() {
+            // The clustered storage can only represent cells that hold
+            // a single grapheme that is at most two cells wide
+            self.coerce_vec_storage();
+        }
 
         if let CellStorage::C(cl) = &mut self.cells {
             if idx > cl.len() && cell == Cell::blank() {
@@ -1044,6 +1052,11 @@ impl Line {
     /// for mutation.
     pub fn compress_for_scrollback(&mut self) {
         let cv = match &self.cells {
+            // The clustered storage can't represent the cells of
+            // sized text; see set_cell_impl
+            CellStorage::V(v) if v.iter().any(|cell| cell.attrs().scaled_text().is_some()) => {
+                return
+            }
             CellStorage::V(v) => ClusteredLine::from_cell_vec(v.len(), self.visible_cells()),
             CellStorage::C(_) => return,
         };
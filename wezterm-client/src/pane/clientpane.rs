@@ -21,10 +21,11 @@ use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap};
 use std::ops::Range;
 use std::sync::Arc;
+use termwiz::escape::osc::Progress;
 use termwiz::input::KeyEvent;
 use termwiz::surface::SequenceNo;
 use url::Url;
-use wezterm_dynamic::Value;
+use wezterm_dynamic::{ToDynamic, Value};
 use wezterm_term::color::ColorPalette;
 use wezterm_term::{
     Alert, Clipboard, KeyCode, KeyModifiers, Line, MouseEvent, StableRowIndex,
@@ -46,6 +47,7 @@ pub struct ClientPane {
     mouse_grabbed: Mutex<bool>,
     ignore_next_kill: Mutex<bool>,
     user_vars: Mutex<HashMap<String, String>>,
+    progress: Mutex<Progress>,
     config: Mutex<Option<Arc<dyn TerminalConfiguration>>>,
     unseen_output: Mutex<bool>,
 }
@@ -128,6 +130,7 @@ impl ClientPane {
             ignore_next_kill: Mutex::new(false),
             unseen_output: Mutex::new(false),
             user_vars: Mutex::new(HashMap::new()),
+            progress: Mutex::new(Progress::default()),
             config: Mutex::new(None),
         }
     }
@@ -183,6 +186,9 @@ impl ClientPane {
                     Alert::SetUserVar { name, value } => {
                         self.user_vars.lock().insert(name.clone(), value.clone());
                     }
+                    Alert::Progress(progress) => {
+                        *self.progress.lock() = *progress;
+                    }
                     Alert::OutputSinceFocusLost => {
                         *self.unseen_output.lock() = true;
                         mux.notify(MuxNotification::Alert {
@@ -262,6 +268,10 @@ impl Pane for ClientPane {
             Value::String("since_last_response_ms".to_string()),
             Value::U64(inner.last_recv_time.elapsed().as_millis() as u64),
         );
+        map.insert(
+            Value::String("progress".to_string()),
+            self.progress.lock().to_dynamic(),
+        );
 
         Value::Object(map.into())
     }
@@ -454,6 +454,9 @@ impl FallbackResolveInfo {
 
 struct FontConfigInner {
     fonts: RefCell<HashMap<TextStyle, Rc<LoadedFont>>>,
+    /// Fonts for text drawn at a multiple of the configured size,
+    /// keyed by style and the bits of the multiplier
+    scaled_fonts: RefCell<HashMap<(TextStyle, u64), Rc<LoadedFont>>>,
     metrics: RefCell<Option<FontMetrics>>,
     dpi: RefCell<usize>,
     font_scale: RefCell<f64>,
@@ -480,6 +483,7 @@ impl FontConfigInner {
         let locator = new_locator(config.font_locator);
         Ok(Self {
             fonts: RefCell::new(HashMap::new()),
+            scaled_fonts: RefCell::new(HashMap::new()),
             locator,
             metrics: RefCell::new(None),
             title_font: RefCell::new(None),
@@ -500,6 +504,7 @@ impl FontConfigInner {
         *self.config.borrow_mut() = config.clone();
         // Config was reloaded, invalidate our caches
         fonts.clear();
+        self.scaled_fonts.borrow_mut().clear();
         self.title_font.borrow_mut().take();
         self.pane_select_font.borrow_mut().take();
         self.char_select_font.borrow_mut().take();
@@ -932,6 +937,53 @@ impl FontConfigInner {
         Ok(loaded)
     }
 
+    /// Loads (with caching) the font for `style` at `scale` times the
+    /// size of the font returned by `resolve_font`
+    fn resolve_scaled_font(
+        &self,
+        myself: &Rc<Self>,
+        style: &TextStyle,
+        scale: f64,
+    ) -> anyhow::Result<Rc<LoadedFont>> {
+        let key = (style.clone(), scale.to_bits());
+        if let Some(entry) = self.scaled_fonts.borrow().get(&key) {
+            return Ok(Rc::clone(entry));
+        }
+
+        let base = self.resolve_font(myself, style)?;
+        let config = self.config.borrow();
+        let font_size = base.font_size * scale;
+        let handles = base.clone_handles();
+        let shaper = new_shaper(&*config, &handles)?;
+        let metrics = shaper.metrics(font_size, base.dpi).with_context(|| {
+            format!(
+                "obtaining metrics for font_size={} @ dpi {}",
+                font_size, base.dpi
+            )
+        })?;
+
+        let loaded = Rc::new(LoadedFont {
+            rasterizers: RefCell::new(HashMap::new()),
+            handles: RefCell::new(handles),
+            shaper: RefCell::new(shaper),
+            metrics,
+            font_size,
+            dpi: base.dpi,
+            font_config: Rc::downgrade(myself),
+            pending_fallback: Arc::new(Mutex::new(vec![])),
+            text_style: style.clone(),
+            id: alloc_font_id(),
+            tried_glyphs: RefCell::new(HashSet::new()),
+            pixel_geometry: config.display_pixel_geometry,
+        });
+
+        self.scaled_fonts
+            .borrow_mut()
+            .insert(key, Rc::clone(&loaded));
+
+        Ok(loaded)
+    }
+
     pub fn change_scaling(&self, font_scale: f64, dpi: usize) -> (f64, usize) {
         let prior_font = *self.font_scale.borrow();
         let prior_dpi = *self.dpi.borrow();
@@ -939,6 +991,7 @@ impl FontConfigInner {
         *self.dpi.borrow_mut() = dpi;
         *self.font_scale.borrow_mut() = font_scale;
         self.fonts.borrow_mut().clear();
+        self.scaled_fonts.borrow_mut().clear();
         self.metrics.borrow_mut().take();
         self.title_font.borrow_mut().take();
 
@@ -1071,6 +1124,16 @@ impl FontConfiguration {
         self.inner.resolve_font(&self.inner, style)
     }
 
+    /// Load (with caching) the font for text that is drawn at `scale`
+    /// times the configured font size, such as text sized via OSC 66
+    pub fn resolve_scaled_font(
+        &self,
+        style: &TextStyle,
+        scale: f64,
+    ) -> anyhow::Result<Rc<LoadedFont>> {
+        self.inner.resolve_scaled_font(&self.inner, style, scale)
+    }
+
     pub fn change_scaling(&self, font_scale: f64, dpi: usize) -> (f64, usize) {
         self.inner.change_scaling(font_scale, dpi)
     }
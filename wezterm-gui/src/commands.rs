@@ -1407,6 +1407,24 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
                 icon: Some("oct_terminal"),
             }
         }
+        CollapsePreviousOutputs => CommandDef {
+            brief: "Fold the output of previous commands".into(),
+            doc: "Folds the output of each of the commands that have completed \
+                  in the current pane, as marked up by shell integration"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["View"],
+            icon: Some("cod_fold"),
+        },
+        ExpandAllOutputs => CommandDef {
+            brief: "Unfold the output of all commands".into(),
+            doc: "Unfolds the output of all of the commands in the current pane".into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["View"],
+            icon: Some("cod_unfold"),
+        },
         ScrollByCurrentEventWheelDelta => CommandDef {
             brief: "Scrolls based on the mouse wheel position \
                 in the current mouse event"
@@ -2008,6 +2026,34 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["Edit"],
             icon: None,
         },
+        FuzzyScrollbackSearch(_) => CommandDef {
+            brief: "Fuzzy search scrollback".into(),
+            doc: "Shows a modal that fuzzy searches the scrollback of the current pane".into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Edit"],
+            icon: Some("md_text_search"),
+        },
+        ExportText(args) => {
+            let format = match args.format {
+                ExportFormat::Html => "HTML",
+                ExportFormat::Svg => "SVG",
+                ExportFormat::Ansi => "ANSI",
+            };
+            let what = match args.scope {
+                ExportScope::Selection => "the selection",
+                ExportScope::Viewport => "the viewport",
+                ExportScope::Scrollback => "the scrollback",
+            };
+            CommandDef {
+                brief: format!("Export {what} as {format}").into(),
+                doc: format!("Writes {what} of the current pane to a {format} file").into(),
+                keys: vec![],
+                args: &[ArgType::ActivePane],
+                menubar: &["Edit"],
+                icon: Some("md_file_export"),
+            }
+        }
     })
 }
 
@@ -2051,6 +2097,12 @@ fn compute_default_actions() -> Vec<KeyAssignment> {
         ActivateCopyMode,
         ClearKeyTableStack,
         ActivateCommandPalette,
+        FuzzyScrollbackSearch(FuzzyScrollbackSearchArguments::default()),
+        ExportText(ExportTextArguments::default()),
+        ExportText(ExportTextArguments {
+            scope: ExportScope::Scrollback,
+            ..Default::default()
+        }),
         // ----------------- View
         DecreaseFontSize,
         IncreaseFontSize,
@@ -2060,6 +2112,8 @@ fn compute_default_actions() -> Vec<KeyAssignment> {
         ScrollByPage(NotNan::new(1.0).unwrap()),
         ScrollToTop,
         ScrollToBottom,
+        CollapsePreviousOutputs,
+        ExpandAllOutputs,
         // ----------------- Window
         ToggleFullScreen,
         ToggleAlwaysOnTop,
@@ -2,8 +2,8 @@ use crate::selection::{SelectionCoordinate, SelectionRange, SelectionX};
 use crate::termwindow::keyevent::KeyTableArgs;
 use crate::termwindow::{TermWindow, TermWindowNotif};
 use config::keyassignment::{
-    ClipboardCopyDestination, CopyModeAssignment, KeyAssignment, KeyTable, KeyTableEntry,
-    ScrollbackEraseMode, SelectionMode,
+    ClipboardCopyDestination, CopyColumnsArguments, CopyModeAssignment, KeyAssignment, KeyTable,
+    KeyTableEntry, ScrollbackEraseMode, SelectionMode,
 };
 use mux::domain::DomainId;
 use mux::pane::{
@@ -15,24 +15,38 @@ use mux::tab::TabId;
 use ordered_float::NotNan;
 use parking_lot::{MappedMutexGuard, Mutex};
 use rangeset::RangeSet;
-use std::collections::HashMap;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
 use std::ops::Range;
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use termwiz::cell::{Cell, CellAttributes};
 use termwiz::color::AnsiColor;
 use termwiz::surface::{CursorVisibility, SequenceNo, SEQ_ZERO};
 use unicode_segmentation::*;
 use url::Url;
 use wezterm_term::color::ColorPalette;
-use wezterm_term::{unicode_column_width, Clipboard, KeyCode, KeyModifiers, Line, MouseEvent, SemanticType, StableRowIndex, TerminalSize, CursorPosition};
+use wezterm_term::{unicode_column_width, Clipboard, KeyCode, KeyModifiers, Line, LineTimestamps, MouseEvent, SemanticType, StableRowIndex, TerminalSize, CursorPosition};
 use window::{KeyCode as WKeyCode, Modifiers, WindowOps};
 
 lazy_static::lazy_static! {
     static ref SAVED_PATTERN: Mutex<HashMap<TabId, Pattern>> = Mutex::new(HashMap::new());
+    /// Marks are kept per pane so that they outlive the copy overlay
+    static ref SAVED_MARKS: Mutex<HashMap<PaneId, HashMap<char, MarkPosition>>> =
+        Mutex::new(HashMap::new());
 }
 
 const SEARCH_CHUNK_SIZE: StableRowIndex = 1000;
+const MAX_JUMP_LIST_LEN: usize = 100;
+const MAX_COUNT: usize = 9999;
+const MAX_SEARCH_HISTORY: usize = 100;
+
+/// Discards the marks of a pane that has been removed
+pub fn forget_marks(pane_id: PaneId) {
+    SAVED_MARKS.lock().remove(&pane_id);
+}
 
 pub struct CopyOverlay {
     delegate: Arc<dyn Pane>,
@@ -52,6 +66,153 @@ struct Jump {
     target: char,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct MarkPosition {
+    x: usize,
+    y: StableRowIndex,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum PendingMark {
+    Set,
+    MoveTo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+enum PatternKind {
+    CaseSensitive,
+    CaseInSensitive,
+    Regex,
+}
+
+impl PatternKind {
+    fn of(pattern: &Pattern) -> Self {
+        match pattern {
+            Pattern::CaseSensitiveString(_) => Self::CaseSensitive,
+            Pattern::CaseInSensitiveString(_) => Self::CaseInSensitive,
+            Pattern::Regex(_) => Self::Regex,
+        }
+    }
+
+    fn pattern(self, s: String) -> Pattern {
+        match self {
+            Self::CaseSensitive => Pattern::CaseSensitiveString(s),
+            Self::CaseInSensitive => Pattern::CaseInSensitiveString(s),
+            Self::Regex => Pattern::Regex(s),
+        }
+    }
+}
+
+/// The copy mode searches made in a workspace
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct SearchHistory {
+    /// Oldest first
+    patterns: Vec<Pattern>,
+    /// The kind of the most recent search
+    last_kind: Option<PatternKind>,
+}
+
+fn search_history_file_name() -> PathBuf {
+    config::DATA_DIR.join("copy-mode-search-history.json")
+}
+
+/// Loads the search history for each workspace
+fn load_search_history() -> anyhow::Result<HashMap<String, SearchHistory>> {
+    let file_name = search_history_file_name();
+    let f = std::fs::File::open(&file_name)?;
+    let history = serde_json::from_reader(f)?;
+    Ok(history)
+}
+
+fn save_search_history(workspace: &str, pattern: &Pattern) -> anyhow::Result<()> {
+    let mut histories = load_search_history().unwrap_or_default();
+    let history = histories.entry(workspace.to_string()).or_default();
+    history.last_kind.replace(PatternKind::of(pattern));
+    if !pattern.is_empty() {
+        history.patterns.retain(|p| p != pattern);
+        history.patterns.push(pattern.clone());
+        let excess = history.patterns.len().saturating_sub(MAX_SEARCH_HISTORY);
+        history.patterns.drain(..excess);
+    }
+
+    let json = serde_json::to_string(&histories)?;
+    let file_name = search_history_file_name();
+    std::fs::write(&file_name, json)?;
+    Ok(())
+}
+
+/// How an operator treats the text between the cursor positions
+/// before and after a motion; these follow the vim definitions
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum MotionKind {
+    /// The cell at the far end of the motion is not included
+    Exclusive,
+    /// The cell at the far end of the motion is included
+    Inclusive,
+    /// Every line touched by the motion is included in its entirety
+    Linewise,
+}
+
+/// A yank that is waiting for its motion or text object
+#[derive(Copy, Clone, Debug)]
+struct PendingOperator {
+    count: usize,
+    /// Where the cursor was when a `f`/`t` style motion started;
+    /// the yank completes once the jump target has been typed
+    jump_origin: Option<MarkPosition>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum TextObjectScope {
+    Inner,
+    Around,
+}
+
+/// The positions that the cursor has jumped away from, oldest first.
+/// `idx` is the position within `entries` that we have walked back to,
+/// or `entries.len()` if we haven't walked back.
+#[derive(Default, Debug)]
+struct JumpList {
+    entries: Vec<MarkPosition>,
+    idx: usize,
+}
+
+impl JumpList {
+    fn record(&mut self, pos: MarkPosition) {
+        self.entries.truncate(self.idx);
+        if self.entries.last() != Some(&pos) {
+            self.entries.push(pos);
+        }
+        if self.entries.len() > MAX_JUMP_LIST_LEN {
+            self.entries.remove(0);
+        }
+        self.idx = self.entries.len();
+    }
+
+    fn backward(&mut self, current: MarkPosition) -> Option<MarkPosition> {
+        if self.idx == self.entries.len() {
+            // Remember where we are so that forward() can return here
+            if self.entries.last() != Some(&current) {
+                self.entries.push(current);
+            }
+            self.idx = self.entries.len() - 1;
+        }
+        if self.idx == 0 {
+            return None;
+        }
+        self.idx -= 1;
+        Some(self.entries[self.idx])
+    }
+
+    fn forward(&mut self) -> Option<MarkPosition> {
+        if self.idx + 1 >= self.entries.len() {
+            return None;
+        }
+        self.idx += 1;
+        Some(self.entries[self.idx])
+    }
+}
+
 struct CopyRenderable {
     cursor: StableCursorPosition,
     delegate: Arc<dyn Pane>,
@@ -79,6 +240,21 @@ struct CopyRenderable {
     searching: Option<Searching>,
     pending_jump: Option<PendingJump>,
     last_jump: Option<Jump>,
+    pending_mark: Option<PendingMark>,
+    jump_list: JumpList,
+    /// The count typed before a motion or operator
+    count: Option<usize>,
+    pending_operator: Option<PendingOperator>,
+    pending_text_object: Option<TextObjectScope>,
+    workspace: String,
+    /// Searches previously made in this workspace, oldest first
+    search_history: Vec<Pattern>,
+    /// The position in search_history that we have walked back to
+    search_history_pos: Option<usize>,
+    /// What was being typed before walking back through the history
+    search_history_draft: Option<Pattern>,
+    /// The kind of the most recent search in the saved history
+    search_history_kind: Option<PatternKind>,
 }
 
 struct Searching {
@@ -113,9 +289,18 @@ impl CopyOverlay {
         cursor.shape = termwiz::surface::CursorShape::SteadyBlock;
         cursor.visibility = CursorVisibility::Visible;
 
-        let (_domain, _window, tab_id) = mux::Mux::get()
+        let mux = mux::Mux::get();
+        let (_domain, window_id, tab_id) = mux
             .resolve_pane_id(pane.pane_id())
             .ok_or_else(|| anyhow::anyhow!("no tab contains the current pane"))?;
+        let workspace = mux
+            .get_window(window_id)
+            .map(|w| w.get_workspace().to_string())
+            .unwrap_or_else(|| mux.active_workspace());
+        let history = load_search_history()
+            .ok()
+            .and_then(|mut histories| histories.remove(&workspace))
+            .unwrap_or_default();
 
         let window = term_window
             .window
@@ -141,6 +326,7 @@ impl CopyOverlay {
                     .lock()
                     .get(&tab_id)
                     .map(|p| p.clone())
+                    .or_else(|| history.last_kind.map(|kind| kind.pattern(String::new())))
                     .unwrap_or(params.pattern)
             } else {
                 params.pattern
@@ -152,6 +338,16 @@ impl CopyOverlay {
             searching: None,
             pending_jump: None,
             last_jump: None,
+            pending_mark: None,
+            jump_list: JumpList::default(),
+            count: None,
+            pending_operator: None,
+            pending_text_object: None,
+            workspace,
+            search_history: history.patterns,
+            search_history_pos: None,
+            search_history_draft: None,
+            search_history_kind: history.last_kind,
         };
 
         let search_row = render.compute_search_row();
@@ -449,7 +645,18 @@ impl CopyRenderable {
 
     fn select_to_cursor_pos(&mut self) {
         self.clamp_cursor_to_scrollback();
-        if let Some(sel_start) = self.start {
+        if let Some(range) = self.selection_range() {
+            self.adjust_selection(range.start, range);
+        } else {
+            self.adjust_viewport_for_cursor_position();
+            self.window.invalidate();
+        }
+    }
+
+    /// Computes the selected range from the selection start,
+    /// the cursor position and the selection mode
+    fn selection_range(&self) -> Option<SelectionRange> {
+        self.start.map(|sel_start| {
             let cursor = SelectionCoordinate::x_y(self.cursor.x, self.cursor.y);
 
             let (start, end) = match self.selection_mode {
@@ -492,11 +699,8 @@ impl CopyRenderable {
                 }
             };
 
-            self.adjust_selection(start, SelectionRange { start, end });
-        } else {
-            self.adjust_viewport_for_cursor_position();
-            self.window.invalidate();
-        }
+            SelectionRange { start, end }
+        })
     }
 
     fn adjust_selection(&self, start: SelectionCoordinate, range: SelectionRange) {
@@ -562,6 +766,11 @@ impl CopyRenderable {
     }
 
     fn close(&self) {
+        if (self.editing_search || !self.pattern.is_empty()) && self.search_history_needs_saving() {
+            if let Err(err) = save_search_history(&self.workspace, &self.pattern) {
+                log::warn!("Unable to save copy mode search history: {err:#}");
+            }
+        }
         self.set_viewport(None);
         TermWindow::schedule_cancel_overlay_for_pane(self.window.clone(), self.delegate.pane_id());
     }
@@ -645,6 +854,64 @@ impl CopyRenderable {
     fn accept_pattern(&mut self) {
         self.editing_search = false;
         self.update_key_table();
+        self.record_search_history();
+    }
+
+    fn record_search_history(&mut self) {
+        self.search_history_pos.take();
+        self.search_history_draft.take();
+        if !self.search_history_needs_saving() {
+            return;
+        }
+        if !self.pattern.is_empty() {
+            let pattern = self.pattern.clone();
+            self.search_history.retain(|p| *p != pattern);
+            self.search_history.push(pattern);
+            let excess = self.search_history.len().saturating_sub(MAX_SEARCH_HISTORY);
+            self.search_history.drain(..excess);
+        }
+        self.search_history_kind
+            .replace(PatternKind::of(&self.pattern));
+        if let Err(err) = save_search_history(&self.workspace, &self.pattern) {
+            log::warn!("Unable to save copy mode search history: {err:#}");
+        }
+    }
+
+    /// Returns true if recording the current pattern would change
+    /// the saved search history
+    fn search_history_needs_saving(&self) -> bool {
+        self.search_history_kind != Some(PatternKind::of(&self.pattern))
+            || (!self.pattern.is_empty() && self.search_history.last() != Some(&self.pattern))
+    }
+
+    fn prior_search_history(&mut self) {
+        let idx = match self.search_history_pos {
+            Some(0) => return,
+            Some(idx) => idx - 1,
+            None if self.search_history.is_empty() => return,
+            None => {
+                self.search_history_draft.replace(self.pattern.clone());
+                self.search_history.len() - 1
+            }
+        };
+        self.search_history_pos.replace(idx);
+        self.pattern = self.search_history[idx].clone();
+        self.schedule_update_search();
+    }
+
+    fn next_search_history(&mut self) {
+        let idx = match self.search_history_pos {
+            Some(idx) => idx + 1,
+            None => return,
+        };
+        if idx < self.search_history.len() {
+            self.search_history_pos.replace(idx);
+            self.pattern = self.search_history[idx].clone();
+        } else {
+            self.search_history_pos.take();
+            self.pattern = self.search_history_draft.take().unwrap_or_default();
+        }
+        self.schedule_update_search();
     }
 
     fn update_key_table(&mut self) {
@@ -744,6 +1011,37 @@ impl CopyRenderable {
         self.select_to_cursor_pos();
     }
 
+    /// Moves to the first line that arrived at or after the time
+    /// described by `spec`, or to the bottom if no line arrived that late
+    fn move_to_time(&mut self, spec: &str) {
+        let time = match parse_time_spec(spec, chrono::Local::now()) {
+            Some(time) => SystemTime::from(time),
+            None => {
+                log::error!("MoveToTime: {spec:?} is not a valid time");
+                self.bell();
+                return;
+            }
+        };
+
+        let dims = self.delegate.get_dimensions();
+        let timestamps = self.delegate.get_line_timestamps(
+            dims.scrollback_top..dims.physical_top + dims.viewport_rows as StableRowIndex,
+        );
+        if timestamps.iter().next().is_none() {
+            // The pane doesn't know when its lines arrived
+            self.bell();
+            return;
+        }
+        match timestamps.first_row_at_or_after(time) {
+            Some(y) => {
+                self.cursor.y = y;
+                self.cursor.x = 0;
+                self.select_to_cursor_pos();
+            }
+            None => self.move_to_bottom(),
+        }
+    }
+
     fn move_to_end_of_line_content(&mut self) {
         let y = self.cursor.y;
         let (top, lines) = self.delegate.get_lines(y..y + 1);
@@ -1058,6 +1356,425 @@ impl CopyRenderable {
         }
     }
 
+    fn cursor_mark(&self) -> MarkPosition {
+        MarkPosition {
+            x: self.cursor.x,
+            y: self.cursor.y,
+        }
+    }
+
+    fn move_to_mark_position(&mut self, pos: MarkPosition) {
+        self.cursor.x = pos.x;
+        self.cursor.y = pos.y;
+        self.clamp_cursor_to_scrollback();
+        self.select_to_cursor_pos();
+    }
+
+    /// Called before a motion that can move the cursor a long way,
+    /// so that we can come back with `JumpListBackward` or `''`
+    fn record_jump(&mut self) {
+        let pos = self.cursor_mark();
+        self.jump_list.record(pos);
+        SAVED_MARKS
+            .lock()
+            .entry(self.delegate.pane_id())
+            .or_default()
+            .insert('\'', pos);
+    }
+
+    fn set_mark(&mut self, name: char) -> bool {
+        if !name.is_ascii_lowercase() {
+            return false;
+        }
+        let pos = self.cursor_mark();
+        SAVED_MARKS
+            .lock()
+            .entry(self.delegate.pane_id())
+            .or_default()
+            .insert(name, pos);
+        true
+    }
+
+    fn move_to_mark(&mut self, name: char) -> bool {
+        let pos = SAVED_MARKS
+            .lock()
+            .get(&self.delegate.pane_id())
+            .and_then(|marks| marks.get(&name))
+            .copied();
+        match pos {
+            Some(pos) => {
+                self.record_jump();
+                self.move_to_mark_position(pos);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn jump_list_backward(&mut self) {
+        let current = self.cursor_mark();
+        if let Some(pos) = self.jump_list.backward(current) {
+            self.move_to_mark_position(pos);
+        }
+    }
+
+    fn jump_list_forward(&mut self) {
+        if let Some(pos) = self.jump_list.forward() {
+            self.move_to_mark_position(pos);
+        }
+    }
+
+    fn bell(&self) {
+        self.delegate
+            .perform_actions(vec![termwiz::escape::Action::Control(
+                termwiz::escape::ControlCode::Bell,
+            )]);
+    }
+
+    /// Abandons a yank that is waiting for its motion, along with
+    /// any count that was typed for it
+    fn cancel_pending_operator(&mut self) {
+        self.pending_operator.take();
+        self.count.take();
+    }
+
+    fn push_count_digit(&mut self, digit: usize) {
+        let count = self.count.unwrap_or(0).saturating_mul(10) + digit;
+        self.count.replace(count.min(MAX_COUNT));
+    }
+
+    /// Copies the text in the range to the clipboard without
+    /// changing the selection
+    fn yank(&self, range: SelectionRange) {
+        let text = range.text(&*self.delegate, false);
+        self.copy_text(ClipboardCopyDestination::ClipboardAndPrimarySelection, text);
+    }
+
+    fn copy_text(&self, destination: ClipboardCopyDestination, text: String) {
+        self.window
+            .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                term_window.copy_to_clipboard(destination, text);
+            })));
+    }
+
+    /// Copies the chosen columns of the rows in the selection, or of the
+    /// rows that hold a match for the search pattern, and closes copy mode
+    fn copy_columns(&mut self, from_matches: bool, args: &CopyColumnsArguments) {
+        let delimiter = match Regex::new(&args.delimiter) {
+            Ok(re) => re,
+            Err(err) => {
+                log::error!(
+                    "CopyColumns: invalid delimiter {:?}: {err:#}",
+                    args.delimiter
+                );
+                self.bell();
+                return;
+            }
+        };
+
+        let rows = if from_matches {
+            self.matching_rows_text()
+        } else {
+            self.selection_range().map(|range| {
+                range.text(&*self.delegate, self.selection_mode == SelectionMode::Block)
+            })
+        };
+        let text = match rows {
+            Some(rows) => extract_columns(&rows, &delimiter, &args.columns, &args.separator),
+            None => {
+                self.bell();
+                return;
+            }
+        };
+
+        self.copy_text(args.destination, text);
+        self.close();
+    }
+
+    /// Returns the text of each logical line that holds a search match
+    fn matching_rows_text(&self) -> Option<String> {
+        if self.results.is_empty() {
+            return None;
+        }
+        let rows: BTreeSet<StableRowIndex> = self.results.iter().map(|r| r.start_y).collect();
+        let mut lines = vec![];
+        let mut last_line_start = None;
+        for y in rows {
+            let line = SelectionRange::line_around(SelectionCoordinate::x_y(0, y), &*self.delegate);
+            if last_line_start == Some(line.start.y) {
+                continue;
+            }
+            last_line_start = Some(line.start.y);
+            lines.push(line.text(&*self.delegate, false));
+        }
+        Some(lines.join("\n"))
+    }
+
+    fn yank_or_begin_operator(&mut self) {
+        let count = self.count.take().unwrap_or(1);
+        if self.start.is_some() {
+            // With a visual selection, copy it and leave copy mode
+            let pane = Arc::clone(&self.delegate);
+            self.window
+                .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                    let text = term_window.selection_text(&pane);
+                    term_window.copy_to_clipboard(
+                        ClipboardCopyDestination::ClipboardAndPrimarySelection,
+                        text,
+                    );
+                })));
+            self.close();
+        } else {
+            self.pending_operator.replace(PendingOperator {
+                count,
+                jump_origin: None,
+            });
+        }
+    }
+
+    /// Applies an assignment while a yank is waiting for its motion
+    fn operator_assignment(&mut self, assignment: &CopyModeAssignment) {
+        let mut op = match self.pending_operator.take() {
+            Some(op) => op,
+            None => return,
+        };
+        let count = op.count.saturating_mul(self.count.take().unwrap_or(1));
+
+        if *assignment == CopyModeAssignment::Yank {
+            // `yy` yanks the current line and the count-1 lines below it
+            let y = self.cursor.y;
+            self.yank(SelectionRange {
+                start: SelectionCoordinate::x_y(0, y),
+                end: SelectionCoordinate::x_y(usize::max_value(), y + count as isize - 1),
+            });
+            return;
+        }
+
+        let kind = match motion_kind(assignment) {
+            Some(kind) => kind,
+            None => {
+                // Anything other than a motion cancels the operator
+                if *assignment != CopyModeAssignment::Close {
+                    self.bell();
+                }
+                return;
+            }
+        };
+
+        let origin = self.cursor_mark();
+        for _ in 0..count.min(MAX_COUNT) {
+            self.perform_copy_mode_assignment(assignment);
+        }
+        if self.pending_jump.is_some() {
+            op.jump_origin.replace(origin);
+            self.pending_operator.replace(op);
+            return;
+        }
+        self.yank_motion(origin, kind);
+    }
+
+    /// Yanks the text between `origin` and the current cursor position
+    fn yank_motion(&mut self, origin: MarkPosition, kind: MotionKind) {
+        let target = self.cursor_mark();
+        let (first, last) = if (origin.y, origin.x) <= (target.y, target.x) {
+            (origin, target)
+        } else {
+            (target, origin)
+        };
+        let start = SelectionCoordinate::x_y(first.x, first.y);
+        let range = match kind {
+            MotionKind::Linewise => SelectionRange {
+                start: SelectionCoordinate::x_y(0, first.y),
+                end: SelectionCoordinate::x_y(usize::max_value(), last.y),
+            },
+            _ if first == last => {
+                // The motion failed, so there is nothing to yank
+                self.bell();
+                return;
+            }
+            MotionKind::Inclusive => SelectionRange {
+                start,
+                end: SelectionCoordinate::x_y(last.x, last.y),
+            },
+            MotionKind::Exclusive => {
+                let (_top, lines) = self.delegate.get_lines(last.y..last.y + 1);
+                let only_indent_before_end = lines
+                    .get(0)
+                    .map(|line| line.columns_as_str(0..last.x).trim().is_empty())
+                    .unwrap_or(true);
+                let end = if last.y > first.y && only_indent_before_end {
+                    // As in vim, a motion that ends in the indentation
+                    // of a later line stops at the end of the line before it
+                    SelectionCoordinate::x_y(usize::max_value(), last.y - 1)
+                } else {
+                    SelectionCoordinate::x_y(last.x.saturating_sub(1), last.y)
+                };
+                SelectionRange { start, end }
+            }
+        };
+        self.yank(range);
+
+        // Leave the cursor at the start of the yanked text
+        self.cursor.x = first.x;
+        self.cursor.y = first.y;
+        self.select_to_cursor_pos();
+    }
+
+    /// `i` and `a` select a text object when a yank is pending
+    fn begin_text_object(&mut self, scope: TextObjectScope) {
+        if self.pending_operator.take().is_some() {
+            self.pending_text_object.replace(scope);
+        }
+    }
+
+    fn yank_text_object(&mut self, scope: TextObjectScope, object: char) -> bool {
+        let range = match object {
+            'z' => self.zone_text_object(scope),
+            _ => self.line_text_object(scope, object),
+        };
+        match range {
+            Some(range) => {
+                self.yank(range);
+                if let SelectionX::Cell(x) = range.start.x {
+                    self.cursor.x = x;
+                }
+                self.cursor.y = range.start.y;
+                self.select_to_cursor_pos();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Computes the range of a word, quote or bracket text object
+    /// within the logical line under the cursor
+    fn line_text_object(&self, scope: TextObjectScope, object: char) -> Option<SelectionRange> {
+        let y = self.cursor.y;
+        let line = self
+            .delegate
+            .get_logical_lines(y..y + 1)
+            .into_iter()
+            .find(|line| line.contains_y(y))?;
+        let cursor_x = line.xy_to_logical_x(self.cursor.x, y);
+        let (indices, chars): (Vec<usize>, Vec<char>) = line
+            .logical
+            .visible_cells()
+            .map(|cell| (cell.cell_index(), cell.str().chars().next().unwrap_or(' ')))
+            .unzip();
+        let idx = indices.iter().rposition(|&x| x <= cursor_x)?;
+        let inner = scope == TextObjectScope::Inner;
+
+        let (start, end) = match object {
+            'w' => word_object(&chars, idx, inner, word_class),
+            'W' => word_object(&chars, idx, inner, big_word_class),
+            '"' | '\'' | '`' => quote_object(&chars, idx, object, inner)?,
+            '(' | ')' | 'b' => bracket_object(&chars, idx, '(', ')', inner)?,
+            '[' | ']' => bracket_object(&chars, idx, '[', ']', inner)?,
+            '{' | '}' | 'B' => bracket_object(&chars, idx, '{', '}', inner)?,
+            '<' | '>' => bracket_object(&chars, idx, '<', '>', inner)?,
+            _ => return None,
+        };
+
+        let (start_y, start_x) = line.logical_x_to_physical_coord(indices[start]);
+        let (end_y, end_x) = line.logical_x_to_physical_coord(indices[end]);
+        Some(SelectionRange {
+            start: SelectionCoordinate::x_y(start_x, start_y),
+            end: SelectionCoordinate::x_y(end_x, end_y),
+        })
+    }
+
+    /// `iz` is the semantic zone under the cursor, while `az` is the
+    /// whole command that it belongs to: its prompt, input and output
+    fn zone_text_object(&self, scope: TextObjectScope) -> Option<SelectionRange> {
+        let zones = self.delegate.get_semantic_zones().ok()?;
+        let cursor = (self.cursor.y, self.cursor.x);
+        let idx = zones.iter().position(|zone| {
+            (zone.start_y, zone.start_x) <= cursor && cursor <= (zone.end_y, zone.end_x)
+        })?;
+        let (first, last) = match scope {
+            TextObjectScope::Inner => (idx, idx),
+            TextObjectScope::Around => {
+                let first = zones[..=idx]
+                    .iter()
+                    .rposition(|zone| zone.semantic_type == SemanticType::Prompt)
+                    .unwrap_or(idx);
+                let last = zones[idx + 1..]
+                    .iter()
+                    .position(|zone| zone.semantic_type == SemanticType::Prompt)
+                    .map(|n| idx + n)
+                    .unwrap_or(zones.len() - 1);
+                (first, last)
+            }
+        };
+        Some(SelectionRange {
+            start: SelectionCoordinate::x_y(zones[first].start_x, zones[first].start_y),
+            end: SelectionCoordinate::x_y(zones[last].end_x, zones[last].end_y),
+        })
+    }
+
+    fn perform_copy_mode_assignment(&mut self, assignment: &CopyModeAssignment) {
+        use CopyModeAssignment::*;
+        if is_jump_motion(assignment) {
+            self.record_jump();
+        }
+        match assignment {
+            MoveToViewportBottom => self.move_to_viewport_bottom(),
+            MoveToViewportTop => self.move_to_viewport_top(),
+            MoveToViewportMiddle => self.move_to_viewport_middle(),
+            MoveToScrollbackTop => self.move_to_top(),
+            MoveToScrollbackBottom => self.move_to_bottom(),
+            MoveToStartOfLineContent => self.move_to_start_of_line_content(),
+            MoveToEndOfLineContent => self.move_to_end_of_line_content(),
+            MoveToStartOfLine => self.move_to_start_of_line(),
+            MoveToStartOfNextLine => self.move_to_start_of_next_line(),
+            MoveToSelectionOtherEnd => self.move_to_selection_other_end(),
+            MoveToSelectionOtherEndHoriz => self.move_to_selection_other_end_horiz(),
+            MoveBackwardWord => self.move_backward_one_word(),
+            MoveForwardWord => self.move_forward_one_word(),
+            MoveForwardWordEnd => self.move_to_end_of_word(),
+            MoveRight => self.move_right_single_cell(),
+            MoveLeft => self.move_left_single_cell(),
+            MoveUp => self.move_up_single_row(),
+            MoveDown => self.move_down_single_row(),
+            MoveByPage(n) => self.move_by_page(**n),
+            PageUp => self.move_by_page(-1.0),
+            PageDown => self.move_by_page(1.0),
+            Close => self.close(),
+            PriorMatch => self.prior_match(),
+            NextMatch => self.next_match(),
+            PriorMatchPage => self.prior_match_page(),
+            NextMatchPage => self.next_match_page(),
+            CycleMatchType => self.cycle_match_type(),
+            ClearPattern => self.clear_pattern(),
+            EditPattern => self.edit_pattern(),
+            AcceptPattern => self.accept_pattern(),
+            SetSelectionMode(mode) => self.set_selection_mode(mode),
+            ClearSelectionMode => self.clear_selection_mode(),
+            MoveBackwardSemanticZone => self.move_by_zone(-1, None),
+            MoveForwardSemanticZone => self.move_by_zone(1, None),
+            MoveBackwardZoneOfType(zone_type) => self.move_by_zone(-1, Some(*zone_type)),
+            MoveForwardZoneOfType(zone_type) => self.move_by_zone(1, Some(*zone_type)),
+            JumpForward { prev_char } => self.jump(true, *prev_char),
+            JumpBackward { prev_char } => self.jump(false, *prev_char),
+            JumpAgain => self.jump_again(false),
+            JumpReverse => self.jump_again(true),
+            SetMark => {
+                self.pending_mark.replace(PendingMark::Set);
+            }
+            MoveToMark => {
+                self.pending_mark.replace(PendingMark::MoveTo);
+            }
+            JumpListBackward => self.jump_list_backward(),
+            JumpListForward => self.jump_list_forward(),
+            Yank => self.yank_or_begin_operator(),
+            CopyColumnsFromSelection(args) => self.copy_columns(false, args),
+            CopyColumnsFromMatches(args) => self.copy_columns(true, args),
+            PriorSearchHistory => self.prior_search_history(),
+            NextSearchHistory => self.next_search_history(),
+            MoveToTime(time) => self.move_to_time(time),
+        }
+    }
+
     fn set_selection_mode(&mut self, mode: &Option<SelectionMode>) {
         match mode {
             None => self.clear_selection_mode(),
@@ -1130,17 +1847,52 @@ impl Pane for CopyOverlay {
                     };
                     render.last_jump.replace(jump);
                     render.perform_jump(jump, false);
+                    if let Some(origin) =
+                        render.pending_operator.take().and_then(|op| op.jump_origin)
+                    {
+                        let kind = if jump.forward {
+                            MotionKind::Inclusive
+                        } else {
+                            MotionKind::Exclusive
+                        };
+                        render.yank_motion(origin, kind);
+                    }
                 }
                 _ => {
-                    self.delegate
-                        .perform_actions(vec![termwiz::escape::Action::Control(
-                            termwiz::escape::ControlCode::Bell,
-                        )]);
+                    render.pending_operator.take();
+                    render.bell();
                 }
             }
             return Ok(());
         }
 
+        if let Some(pending) = render.pending_mark.take() {
+            let ok = match (key, mods) {
+                (KeyCode::Char(c), KeyModifiers::NONE)
+                | (KeyCode::Char(c), KeyModifiers::SHIFT) => match pending {
+                    PendingMark::Set => render.set_mark(c),
+                    PendingMark::MoveTo => render.move_to_mark(c),
+                },
+                _ => false,
+            };
+            if !ok {
+                render.bell();
+            }
+            return Ok(());
+        }
+
+        if let Some(scope) = render.pending_text_object.take() {
+            let ok = match (key, mods) {
+                (KeyCode::Char(c), KeyModifiers::NONE)
+                | (KeyCode::Char(c), KeyModifiers::SHIFT) => render.yank_text_object(scope, c),
+                _ => false,
+            };
+            if !ok {
+                render.bell();
+            }
+            return Ok(());
+        }
+
         if render.editing_search {
             match (key, mods) {
                 (KeyCode::Char(c), KeyModifiers::NONE)
@@ -1156,66 +1908,62 @@ impl Pane for CopyOverlay {
                 }
                 _ => {}
             }
+        } else {
+            match (key, mods) {
+                (KeyCode::Char(c @ '1'..='9'), KeyModifiers::NONE) => {
+                    render.push_count_digit(c as usize - '0' as usize);
+                }
+                (KeyCode::Char('i'), KeyModifiers::NONE) => {
+                    render.begin_text_object(TextObjectScope::Inner);
+                }
+                (KeyCode::Char('a'), KeyModifiers::NONE) => {
+                    render.begin_text_object(TextObjectScope::Around);
+                }
+                (KeyCode::Escape, _) | (KeyCode::Char(_), _) => {
+                    render.cancel_pending_operator();
+                }
+                _ => {}
+            }
         }
 
         Ok(())
     }
 
     fn perform_assignment(&self, assignment: &KeyAssignment) -> PerformAssignmentResult {
-        use CopyModeAssignment::*;
         let mut render = self.render.lock();
-        if render.pending_jump.is_some() {
+        if render.pending_jump.is_some()
+            || render.pending_mark.is_some()
+            || render.pending_text_object.is_some()
+        {
             // Block key assignments until key_down is called
             // and resolves the next state
             return PerformAssignmentResult::BlockAssignmentAndRouteToKeyDown;
         }
         match assignment {
             KeyAssignment::CopyMode(assignment) => {
-                match assignment {
-                    MoveToViewportBottom => render.move_to_viewport_bottom(),
-                    MoveToViewportTop => render.move_to_viewport_top(),
-                    MoveToViewportMiddle => render.move_to_viewport_middle(),
-                    MoveToScrollbackTop => render.move_to_top(),
-                    MoveToScrollbackBottom => render.move_to_bottom(),
-                    MoveToStartOfLineContent => render.move_to_start_of_line_content(),
-                    MoveToEndOfLineContent => render.move_to_end_of_line_content(),
-                    MoveToStartOfLine => render.move_to_start_of_line(),
-                    MoveToStartOfNextLine => render.move_to_start_of_next_line(),
-                    MoveToSelectionOtherEnd => render.move_to_selection_other_end(),
-                    MoveToSelectionOtherEndHoriz => render.move_to_selection_other_end_horiz(),
-                    MoveBackwardWord => render.move_backward_one_word(),
-                    MoveForwardWord => render.move_forward_one_word(),
-                    MoveForwardWordEnd => render.move_to_end_of_word(),
-                    MoveRight => render.move_right_single_cell(),
-                    MoveLeft => render.move_left_single_cell(),
-                    MoveUp => render.move_up_single_row(),
-                    MoveDown => render.move_down_single_row(),
-                    MoveByPage(n) => render.move_by_page(**n),
-                    PageUp => render.move_by_page(-1.0),
-                    PageDown => render.move_by_page(1.0),
-                    Close => render.close(),
-                    PriorMatch => render.prior_match(),
-                    NextMatch => render.next_match(),
-                    PriorMatchPage => render.prior_match_page(),
-                    NextMatchPage => render.next_match_page(),
-                    CycleMatchType => render.cycle_match_type(),
-                    ClearPattern => render.clear_pattern(),
-                    EditPattern => render.edit_pattern(),
-                    AcceptPattern => render.accept_pattern(),
-                    SetSelectionMode(mode) => render.set_selection_mode(mode),
-                    ClearSelectionMode => render.clear_selection_mode(),
-                    MoveBackwardSemanticZone => render.move_by_zone(-1, None),
-                    MoveForwardSemanticZone => render.move_by_zone(1, None),
-                    MoveBackwardZoneOfType(zone_type) => render.move_by_zone(-1, Some(*zone_type)),
-                    MoveForwardZoneOfType(zone_type) => render.move_by_zone(1, Some(*zone_type)),
-                    JumpForward { prev_char } => render.jump(true, *prev_char),
-                    JumpBackward { prev_char } => render.jump(false, *prev_char),
-                    JumpAgain => render.jump_again(false),
-                    JumpReverse => render.jump_again(true),
+                if *assignment == CopyModeAssignment::MoveToStartOfLine && render.count.is_some() {
+                    // `0` is bound to MoveToStartOfLine, but continues
+                    // a count once one has been started
+                    render.push_count_digit(0);
+                } else if render.pending_operator.is_some() {
+                    render.operator_assignment(assignment);
+                } else if motion_kind(assignment).is_some() {
+                    let count = render.count.take().unwrap_or(1);
+                    for _ in 0..count {
+                        render.perform_copy_mode_assignment(assignment);
+                    }
+                } else {
+                    if *assignment != CopyModeAssignment::Yank {
+                        render.count.take();
+                    }
+                    render.perform_copy_mode_assignment(assignment);
                 }
                 PerformAssignmentResult::Handled
             }
-            _ => PerformAssignmentResult::Unhandled,
+            _ => {
+                render.cancel_pending_operator();
+                PerformAssignmentResult::Unhandled
+            }
         }
     }
 
@@ -1291,6 +2039,10 @@ impl Pane for CopyOverlay {
         self.delegate.get_logical_lines(lines)
     }
 
+    fn get_line_timestamps(&self, lines: Range<StableRowIndex>) -> LineTimestamps {
+        self.delegate.get_line_timestamps(lines)
+    }
+
     fn for_each_logical_line_in_stable_range_mut(
         &self,
         lines: Range<StableRowIndex>,
@@ -1512,6 +2264,270 @@ impl Pane for CopyOverlay {
     }
 }
 
+/// Returns true for the motions that are recorded in the jump list,
+/// which are those that can move the cursor a long way
+fn is_jump_motion(assignment: &CopyModeAssignment) -> bool {
+    use CopyModeAssignment::*;
+    matches!(
+        assignment,
+        MoveToViewportBottom
+            | MoveToViewportTop
+            | MoveToViewportMiddle
+            | MoveToScrollbackTop
+            | MoveToScrollbackBottom
+            | PriorMatch
+            | NextMatch
+            | PriorMatchPage
+            | NextMatchPage
+            | MoveBackwardSemanticZone
+            | MoveForwardSemanticZone
+            | MoveBackwardZoneOfType(_)
+            | MoveForwardZoneOfType(_)
+            | MoveToTime(_)
+    )
+}
+
+/// Returns how an operator treats the text covered by a motion,
+/// or `None` if the assignment is not a motion
+fn motion_kind(assignment: &CopyModeAssignment) -> Option<MotionKind> {
+    use CopyModeAssignment::*;
+    match assignment {
+        MoveUp
+        | MoveDown
+        | MoveToStartOfNextLine
+        | MoveByPage(_)
+        | PageUp
+        | PageDown
+        | MoveToViewportBottom
+        | MoveToViewportTop
+        | MoveToViewportMiddle
+        | MoveToScrollbackTop
+        | MoveToScrollbackBottom
+        | MoveToTime(_) => Some(MotionKind::Linewise),
+        MoveForwardWordEnd
+        | MoveToEndOfLineContent
+        | JumpForward { .. }
+        | JumpAgain
+        | JumpReverse => Some(MotionKind::Inclusive),
+        MoveLeft
+        | MoveRight
+        | MoveBackwardWord
+        | MoveForwardWord
+        | MoveToStartOfLine
+        | MoveToStartOfLineContent
+        | JumpBackward { .. }
+        | PriorMatch
+        | NextMatch
+        | MoveBackwardSemanticZone
+        | MoveForwardSemanticZone
+        | MoveBackwardZoneOfType(_)
+        | MoveForwardZoneOfType(_) => Some(MotionKind::Exclusive),
+        _ => None,
+    }
+}
+
+/// Parses the time given to `MoveToTime`, which is either a time of
+/// day such as `14:05` or `14:05:30`, meaning the most recent such time,
+/// or a time relative to `now` such as `-90s`, `-5m` or `-2h`
+fn parse_time_spec<Tz: chrono::TimeZone>(
+    spec: &str,
+    now: chrono::DateTime<Tz>,
+) -> Option<chrono::DateTime<Tz>> {
+    let spec = spec.trim();
+    if let Some(ago) = spec.strip_prefix('-') {
+        let unit = ago.chars().last()?;
+        let amount: i64 = ago[..ago.len() - unit.len_utf8()].parse().ok()?;
+        let seconds = match unit {
+            's' => amount,
+            'm' => amount.checked_mul(60)?,
+            'h' => amount.checked_mul(60 * 60)?,
+            _ => return None,
+        };
+        return now.checked_sub_signed(chrono::Duration::seconds(seconds));
+    }
+
+    let time = chrono::NaiveTime::parse_from_str(spec, "%H:%M:%S")
+        .or_else(|_| chrono::NaiveTime::parse_from_str(spec, "%H:%M"))
+        .ok()?;
+    let today = now.date_naive().and_time(time);
+    let tz = now.timezone();
+    match tz.from_local_datetime(&today).earliest() {
+        Some(when) if when <= now => Some(when),
+        // That time hasn't happened yet today, so it means yesterday
+        _ => tz
+            .from_local_datetime(&(today - chrono::Duration::days(1)))
+            .earliest(),
+    }
+}
+
+/// Character classes used by the `w` text object;
+/// a word is a run of cells of the same class
+fn word_class(c: char) -> u8 {
+    if c.is_whitespace() {
+        0
+    } else if c.is_alphanumeric() || c == '_' {
+        2
+    } else {
+        1
+    }
+}
+
+/// Character classes used by the `W` text object
+fn big_word_class(c: char) -> u8 {
+    if c.is_whitespace() {
+        0
+    } else {
+        1
+    }
+}
+
+/// Returns the inclusive bounds of the word around `idx`.
+/// The `around` form also takes in the whitespace that follows
+/// the word, or the whitespace before it if there is none after.
+fn word_object(chars: &[char], idx: usize, inner: bool, class: fn(char) -> u8) -> (usize, usize) {
+    let span = |idx: usize| {
+        let c = class(chars[idx]);
+        let mut start = idx;
+        while start > 0 && class(chars[start - 1]) == c {
+            start -= 1;
+        }
+        let mut end = idx;
+        while end + 1 < chars.len() && class(chars[end + 1]) == c {
+            end += 1;
+        }
+        (start, end)
+    };
+
+    let (mut start, mut end) = span(idx);
+    if !inner {
+        let is_space = |idx: usize| class(chars[idx]) == 0;
+        if is_space(idx) {
+            if end + 1 < chars.len() {
+                end = span(end + 1).1;
+            }
+        } else if end + 1 < chars.len() && is_space(end + 1) {
+            end = span(end + 1).1;
+        } else if start > 0 && is_space(start - 1) {
+            start = span(start - 1).0;
+        }
+    }
+    (start, end)
+}
+
+/// Returns the inclusive bounds of the quoted string around `idx`,
+/// or the next one on the line if the cursor is not inside a string.
+/// Quotes preceded by a backslash are not considered.
+fn quote_object(chars: &[char], idx: usize, quote: char, inner: bool) -> Option<(usize, usize)> {
+    let quotes: Vec<usize> = chars
+        .iter()
+        .enumerate()
+        .filter(|&(i, &c)| c == quote && (i == 0 || chars[i - 1] != '\\'))
+        .map(|(i, _)| i)
+        .collect();
+    let (open, close) = quotes
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|&(_, close)| idx <= close)?;
+    if !inner {
+        Some((open, close))
+    } else if close > open + 1 {
+        Some((open + 1, close - 1))
+    } else {
+        None
+    }
+}
+
+/// Returns the inclusive bounds of the innermost `open`/`close`
+/// pair that encloses `idx`
+fn bracket_object(
+    chars: &[char],
+    idx: usize,
+    open: char,
+    close: char,
+    inner: bool,
+) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let mut start = None;
+    for i in (0..=idx).rev() {
+        if chars[i] == close && i != idx {
+            depth += 1;
+        } else if chars[i] == open {
+            if depth == 0 {
+                start = Some(i);
+                break;
+            }
+            depth -= 1;
+        }
+    }
+    let start = start?;
+
+    let mut depth = 0;
+    let mut end = None;
+    for (i, &c) in chars.iter().enumerate().skip(start + 1) {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            if depth == 0 {
+                end = Some(i);
+                break;
+            }
+            depth -= 1;
+        }
+    }
+    let end = end?;
+
+    if !inner {
+        Some((start, end))
+    } else if end > start + 1 {
+        Some((start + 1, end - 1))
+    } else {
+        None
+    }
+}
+
+/// Splits each row into columns on `delimiter` and joins the chosen
+/// columns with `separator`. Columns are numbered from 1, and negative
+/// numbers count back from the last column; a column that a row does
+/// not have is copied as an empty string.
+/// Leading and trailing whitespace is ignored only when splitting on
+/// whitespace; with any other delimiter it may hold an empty field.
+fn extract_columns(rows: &str, delimiter: &Regex, columns: &[i64], separator: &str) -> String {
+    let splits_on_whitespace = delimiter.is_match(" ");
+    let mut result = vec![];
+    for row in rows.lines() {
+        if row.trim().is_empty() {
+            continue;
+        }
+        let row = if splits_on_whitespace {
+            row.trim()
+        } else {
+            row
+        };
+        let fields: Vec<&str> = delimiter.split(row).collect();
+        let chosen: Vec<&str> = if columns.is_empty() {
+            fields.clone()
+        } else {
+            columns
+                .iter()
+                .map(|&n| {
+                    let idx = if n < 0 {
+                        fields.len() as i64 + n
+                    } else {
+                        n - 1
+                    };
+                    if idx < 0 {
+                        ""
+                    } else {
+                        fields.get(idx as usize).copied().unwrap_or("")
+                    }
+                })
+                .collect()
+        };
+        result.push(chosen.join(separator));
+    }
+    result.join("\n")
+}
+
 fn is_whitespace_word(word: &str) -> bool {
     if let Some(c) = word.chars().next() {
         c.is_whitespace()
@@ -1531,7 +2547,7 @@ pub fn search_key_table() -> KeyTable {
         (
             WKeyCode::UpArrow,
             Modifiers::NONE,
-            KeyAssignment::CopyMode(CopyModeAssignment::PriorMatch),
+            KeyAssignment::CopyMode(CopyModeAssignment::PriorSearchHistory),
         ),
         (
             WKeyCode::Char('\r'),
@@ -1561,7 +2577,7 @@ pub fn search_key_table() -> KeyTable {
         (
             WKeyCode::DownArrow,
             Modifiers::NONE,
-            KeyAssignment::CopyMode(CopyModeAssignment::NextMatch),
+            KeyAssignment::CopyMode(CopyModeAssignment::NextSearchHistory),
         ),
         (
             WKeyCode::Char('r'),
@@ -1850,10 +2866,7 @@ pub fn copy_key_table() -> KeyTable {
         (
             WKeyCode::Char('y'),
             Modifiers::NONE,
-            KeyAssignment::Multiple(vec![
-                KeyAssignment::CopyTo(ClipboardCopyDestination::ClipboardAndPrimarySelection),
-                KeyAssignment::CopyMode(CopyModeAssignment::Close),
-            ]),
+            KeyAssignment::CopyMode(CopyModeAssignment::Yank),
         ),
         (
             WKeyCode::Char(';'),
@@ -1865,6 +2878,26 @@ pub fn copy_key_table() -> KeyTable {
             Modifiers::NONE,
             KeyAssignment::CopyMode(CopyModeAssignment::JumpReverse),
         ),
+        (
+            WKeyCode::Char('m'),
+            Modifiers::NONE,
+            KeyAssignment::CopyMode(CopyModeAssignment::SetMark),
+        ),
+        (
+            WKeyCode::Char('\''),
+            Modifiers::NONE,
+            KeyAssignment::CopyMode(CopyModeAssignment::MoveToMark),
+        ),
+        (
+            WKeyCode::Char('o'),
+            Modifiers::CTRL,
+            KeyAssignment::CopyMode(CopyModeAssignment::JumpListBackward),
+        ),
+        (
+            WKeyCode::Char('i'),
+            Modifiers::CTRL,
+            KeyAssignment::CopyMode(CopyModeAssignment::JumpListForward),
+        ),
         (
             WKeyCode::Char('F'),
             Modifiers::NONE,
@@ -1910,3 +2943,167 @@ pub fn copy_key_table() -> KeyTable {
     }
     table
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn pos(y: StableRowIndex) -> MarkPosition {
+        MarkPosition { x: 0, y }
+    }
+
+    #[test]
+    fn walk_back_and_forth() {
+        let mut list = JumpList::default();
+        list.record(pos(1));
+        list.record(pos(2));
+
+        assert_eq!(list.backward(pos(3)), Some(pos(2)));
+        assert_eq!(list.backward(pos(2)), Some(pos(1)));
+        assert_eq!(list.backward(pos(1)), None);
+        assert_eq!(list.forward(), Some(pos(2)));
+        assert_eq!(list.forward(), Some(pos(3)));
+        assert_eq!(list.forward(), None);
+    }
+
+    #[test]
+    fn record_discards_newer_entries() {
+        let mut list = JumpList::default();
+        list.record(pos(1));
+        list.record(pos(2));
+        assert_eq!(list.backward(pos(3)), Some(pos(2)));
+        assert_eq!(list.backward(pos(2)), Some(pos(1)));
+
+        list.record(pos(1));
+        assert_eq!(list.forward(), None);
+        assert_eq!(list.backward(pos(5)), Some(pos(1)));
+    }
+
+    #[test]
+    fn empty_jump_list() {
+        let mut list = JumpList::default();
+        assert_eq!(list.backward(pos(1)), None);
+        assert_eq!(list.forward(), None);
+    }
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn words() {
+        let line = chars("cargo build --release");
+        assert_eq!(word_object(&line, 7, true, word_class), (6, 10));
+        assert_eq!(word_object(&line, 7, false, word_class), (6, 11));
+        assert_eq!(word_object(&line, 12, true, word_class), (12, 13));
+        assert_eq!(word_object(&line, 14, true, big_word_class), (12, 20));
+        // No trailing whitespace, so take the leading whitespace
+        assert_eq!(word_object(&line, 18, false, big_word_class), (11, 20));
+        // On whitespace, `aw` takes the following word too
+        assert_eq!(word_object(&line, 5, false, word_class), (5, 10));
+    }
+
+    #[test]
+    fn quotes() {
+        let line = chars(r#"echo "a \"b\" c" 'd' """#);
+        assert_eq!(quote_object(&line, 7, '"', true), Some((6, 14)));
+        assert_eq!(quote_object(&line, 7, '"', false), Some((5, 15)));
+        // Before the first string, use the next one on the line
+        assert_eq!(quote_object(&line, 0, '\'', true), Some((18, 18)));
+        assert_eq!(quote_object(&line, 21, '"', true), None);
+        assert_eq!(quote_object(&line, 21, '"', false), Some((21, 22)));
+        assert_eq!(quote_object(&line, 20, '`', false), None);
+    }
+
+    #[test]
+    fn brackets() {
+        let line = chars("f(a, g(b), [c]) ()");
+        assert_eq!(bracket_object(&line, 3, '(', ')', true), Some((2, 13)));
+        assert_eq!(bracket_object(&line, 7, '(', ')', false), Some((6, 8)));
+        // On a bracket, that pair is used
+        assert_eq!(bracket_object(&line, 8, '(', ')', true), Some((7, 7)));
+        assert_eq!(bracket_object(&line, 1, '(', ')', true), Some((2, 13)));
+        assert_eq!(bracket_object(&line, 12, '[', ']', true), Some((12, 12)));
+        assert_eq!(bracket_object(&line, 17, '(', ')', true), None);
+        assert_eq!(bracket_object(&line, 15, '(', ')', false), None);
+    }
+
+    const PS: &str =
+        "  PID TTY          TIME CMD\n 4242 pts/1    00:00:01 bash\n\n   17 pts/1    00:00:00 ps\n";
+
+    #[test]
+    fn whitespace_columns() {
+        let ws = Regex::new(r"\s+").unwrap();
+        assert_eq!(
+            extract_columns(PS, &ws, &[1], " "),
+            "PID\n4242\n17".to_string()
+        );
+        assert_eq!(
+            extract_columns(PS, &ws, &[-1, 1], ","),
+            "CMD,PID\nbash,4242\nps,17".to_string()
+        );
+        assert_eq!(
+            extract_columns("a b\nc", &ws, &[2, 0], ":"),
+            "b:\n:".to_string()
+        );
+    }
+
+    #[test]
+    fn delimited_columns() {
+        let comma = Regex::new(",").unwrap();
+        assert_eq!(
+            extract_columns("a,b,c\nd,,f", &comma, &[], "\t"),
+            "a\tb\tc\nd\t\tf".to_string()
+        );
+        assert_eq!(
+            extract_columns("a,b,c\nd,,f", &comma, &[3, 2], " "),
+            "c b\nf ".to_string()
+        );
+    }
+
+    #[test]
+    fn leading_empty_column() {
+        let tab = Regex::new("\t").unwrap();
+        assert_eq!(
+            extract_columns("\tb\na\tb", &tab, &[1, 2], ","),
+            ",b\na,b".to_string()
+        );
+    }
+
+    fn now() -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc
+            .with_ymd_and_hms(2023, 6, 15, 14, 30, 0)
+            .unwrap()
+    }
+
+    fn at(h: u32, m: u32, s: u32, day: u32) -> Option<chrono::DateTime<chrono::Utc>> {
+        Some(chrono::Utc.with_ymd_and_hms(2023, 6, day, h, m, s).unwrap())
+    }
+
+    #[test]
+    fn time_of_day() {
+        assert_eq!(parse_time_spec("14:05", now()), at(14, 5, 0, 15));
+        assert_eq!(parse_time_spec(" 09:15:42 ", now()), at(9, 15, 42, 15));
+        assert_eq!(parse_time_spec("14:30", now()), at(14, 30, 0, 15));
+        // Later today hasn't happened yet, so it means yesterday
+        assert_eq!(parse_time_spec("18:00", now()), at(18, 0, 0, 14));
+    }
+
+    #[test]
+    fn relative_time() {
+        assert_eq!(parse_time_spec("-90s", now()), at(14, 28, 30, 15));
+        assert_eq!(parse_time_spec("-5m", now()), at(14, 25, 0, 15));
+        assert_eq!(parse_time_spec("-15h", now()), at(23, 30, 0, 14));
+    }
+
+    #[test]
+    fn invalid_time() {
+        assert_eq!(parse_time_spec("", now()), None);
+        assert_eq!(parse_time_spec("25:00", now()), None);
+        assert_eq!(parse_time_spec("-5d", now()), None);
+        assert_eq!(parse_time_spec("-m", now()), None);
+        assert_eq!(parse_time_spec("-5é", now()), None);
+        assert_eq!(parse_time_spec("soon", now()), None);
+    }
+}
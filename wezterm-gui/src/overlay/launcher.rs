@@ -10,8 +10,7 @@ use crate::inputmap::InputMap;
 use crate::termwindow::TermWindowNotif;
 use config::configuration;
 use config::keyassignment::{KeyAssignment, SpawnCommand, SpawnTabDomain};
-use fuzzy_matcher::skim::SkimMatcherV2;
-use fuzzy_matcher::FuzzyMatcher;
+use fuzzy::{CaseMatching, Matcher, Pattern};
 use mux::domain::{DomainId, DomainState};
 use mux::pane::PaneId;
 use mux::tab::TabId;
@@ -186,11 +185,12 @@ impl LauncherState {
 
         self.filtered_entries.clear();
 
-        let matcher = SkimMatcherV2::default();
+        let pattern = Pattern::parse(&self.filter_term, CaseMatching::Smart);
+        let mut matcher = Matcher::new();
 
         struct MatchResult {
             row_idx: usize,
-            score: i64,
+            score: i32,
         }
 
         let mut scores: Vec<MatchResult> = self
@@ -198,7 +198,7 @@ impl LauncherState {
             .iter()
             .enumerate()
             .filter_map(|(row_idx, entry)| {
-                let score = matcher.fuzzy_match(&entry.label, &self.filter_term)?;
+                let score = matcher.score(&pattern, &entry.label)?;
                 Some(MatchResult { row_idx, score })
             })
             .collect();
@@ -1,6 +1,9 @@
 use crate::selection::{SelectionCoordinate, SelectionRange};
 use crate::termwindow::{TermWindow, TermWindowNotif};
-use config::keyassignment::{ClipboardCopyDestination, QuickSelectArguments, ScrollbackEraseMode};
+use config::keyassignment::{
+    CaptureGroup, ClipboardCopyDestination, QuickSelectArguments, QuickSelectPattern,
+    ScrollbackEraseMode,
+};
 use config::ConfigHandle;
 use mux::domain::DomainId;
 use mux::pane::{
@@ -12,14 +15,15 @@ use parking_lot::{MappedMutexGuard, Mutex};
 use rangeset::RangeSet;
 use std::collections::HashMap;
 use std::ops::Range;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use termwiz::cell::{Cell, CellAttributes};
 use termwiz::color::AnsiColor;
 use termwiz::surface::{SequenceNo, SEQ_ZERO};
 use url::Url;
 use wezterm_term::color::ColorPalette;
 use wezterm_term::{
-    Clipboard, KeyCode, KeyModifiers, Line, MouseEvent, StableRowIndex, TerminalSize,
+    Clipboard, KeyCode, KeyModifiers, Line, LineTimestamps, MouseEvent, StableRowIndex,
+    TerminalSize,
 };
 use window::WindowOps;
 
@@ -126,8 +130,9 @@ fn compute_labels_for_alphabet_impl(
 }
 
 #[cfg(test)]
-mod alphabet_test {
+mod test {
     use super::*;
+    use config::keyassignment::KeyAssignment;
 
     #[test]
     fn simple_alphabet() {
@@ -191,6 +196,226 @@ mod alphabet_test {
             compute_labels_for_alphabet("abc123", 12)
         );
     }
+
+    fn patterns() -> Vec<QuickSelectPattern> {
+        [
+            (r"https?://\S+", "open"),
+            (r"[0-9a-f]{7,40}", "show"),
+            (r"\S+:\d+", ""),
+            (r"[0-9]{4,}", "show"),
+        ]
+        .iter()
+        .map(|(regex, label)| QuickSelectPattern {
+            regex: regex.to_string(),
+            action: if label.is_empty() {
+                None
+            } else {
+                Some(Box::new(KeyAssignment::Nop))
+            },
+            label: label.to_string(),
+            group: None,
+        })
+        .collect()
+    }
+
+    fn grouped_patterns() -> Vec<QuickSelectPattern> {
+        [
+            (r"https?://\S+", None),
+            (r"commit ([0-9a-f]{7,40})", Some(CaptureGroup::Index(1))),
+            (
+                r"(?P<file>[\w./]+):(?P<line>\d+)",
+                Some(CaptureGroup::Name("line".to_string())),
+            ),
+            (r"(a)(b)", Some(CaptureGroup::Index(3))),
+        ]
+        .iter()
+        .map(|(regex, group)| QuickSelectPattern {
+            regex: regex.to_string(),
+            action: None,
+            label: String::new(),
+            group: group.clone(),
+        })
+        .collect()
+    }
+
+    #[test]
+    fn pattern_groups() {
+        assert_eq!(
+            compute_pattern_groups(&grouped_patterns()),
+            vec![
+                PatternGroups {
+                    pattern: 1,
+                    chosen: None
+                },
+                PatternGroups {
+                    pattern: 2,
+                    chosen: Some(3)
+                },
+                PatternGroups {
+                    pattern: 4,
+                    chosen: Some(6)
+                },
+                PatternGroups {
+                    pattern: 7,
+                    chosen: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn pattern_groups_in_combined_pattern() {
+        let patterns = grouped_patterns();
+        let groups = compute_pattern_groups(&patterns);
+        let re = fancy_regex::Regex::new(&compute_combined_pattern(&patterns)).unwrap();
+
+        let chosen_text = |text| {
+            let c = re.captures(text).unwrap().unwrap();
+            let idx = groups
+                .iter()
+                .position(|g| c.get(g.pattern).is_some())
+                .unwrap();
+            let group = groups[idx].chosen.unwrap_or(groups[idx].pattern);
+            (idx, c.get(group).unwrap().as_str().to_string())
+        };
+
+        assert_eq!(
+            chosen_text("see https://wezfurlong.org/"),
+            (0, "https://wezfurlong.org/".to_string())
+        );
+        assert_eq!(
+            chosen_text("commit deadbeef1"),
+            (1, "deadbeef1".to_string())
+        );
+        assert_eq!(chosen_text("at src/main.rs:12"), (2, "12".to_string()));
+        assert_eq!(chosen_text("ab"), (3, "ab".to_string()));
+    }
+
+    #[test]
+    fn action_labels() {
+        let mut args = QuickSelectArguments::default();
+        assert_eq!(
+            compute_action_label(&patterns(), &args),
+            "open or show or copy"
+        );
+        args.label = "paste".to_string();
+        assert_eq!(
+            compute_action_label(&patterns(), &args),
+            "open or show or paste"
+        );
+        assert_eq!(compute_action_label(&[], &args), "paste");
+    }
+}
+
+/// Describes what selecting a match will do, listing the label of each
+/// distinct action that the patterns can perform
+fn compute_action_label(patterns: &[QuickSelectPattern], args: &QuickSelectArguments) -> String {
+    let default_label = if args.label.is_empty() {
+        "copy"
+    } else {
+        &args.label
+    };
+    let mut labels: Vec<&str> = vec![];
+    for p in patterns {
+        let label = match (&p.action, p.label.as_str()) {
+            (Some(_), "") => "run action",
+            (Some(_), label) => label,
+            (None, _) => default_label,
+        };
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    if labels.is_empty() {
+        default_label.to_string()
+    } else {
+        labels.join(" or ")
+    }
+}
+
+/// Combines the patterns into a single regex in which each
+/// pattern is wrapped in its own capture group
+fn compute_combined_pattern(patterns: &[QuickSelectPattern]) -> String {
+    let mut pattern = "(?m)(?:".to_string();
+    for (idx, p) in patterns.iter().enumerate() {
+        if idx > 0 {
+            pattern.push('|');
+        }
+        pattern.push('(');
+        pattern.push_str(&p.regex);
+        pattern.push(')');
+    }
+    pattern.push(')');
+    pattern
+}
+
+/// Where the groups of a pattern sit within the combined regex
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PatternGroups {
+    /// The group that wraps the whole of the pattern
+    pattern: usize,
+    /// The group selected by the `group` field of the pattern
+    chosen: Option<usize>,
+}
+
+fn compute_pattern_groups(patterns: &[QuickSelectPattern]) -> Vec<PatternGroups> {
+    let mut next_group = 1;
+    patterns
+        .iter()
+        .map(|p| {
+            let pattern = next_group;
+            let re = match fancy_regex::Regex::new(&p.regex) {
+                Ok(re) => re,
+                Err(err) => {
+                    log::error!("quick select pattern {:?}: {err:#}", p.regex);
+                    next_group += 1;
+                    return PatternGroups {
+                        pattern,
+                        chosen: None,
+                    };
+                }
+            };
+            // captures_len includes the whole match, which is
+            // the wrapping group in the combined regex
+            next_group += re.captures_len();
+
+            let chosen = match &p.group {
+                None => None,
+                Some(CaptureGroup::Index(idx)) if *idx < re.captures_len() => Some(pattern + idx),
+                Some(CaptureGroup::Name(name)) => re
+                    .capture_names()
+                    .position(|n| n == Some(name.as_str()))
+                    .map(|idx| pattern + idx),
+                Some(CaptureGroup::Index(_)) => None,
+            };
+            if p.group.is_some() && chosen.is_none() {
+                log::error!(
+                    "quick select pattern {:?} has no capture group {:?}",
+                    p.regex,
+                    p.group
+                );
+            }
+            PatternGroups { pattern, chosen }
+        })
+        .collect()
+}
+
+/// Returns the index of the pattern that produced `result`, and
+/// adjusts `result` to span the group chosen by that pattern
+fn select_pattern_group(groups: &[PatternGroups], result: &mut SearchResult) -> Option<usize> {
+    let idx = groups
+        .iter()
+        .position(|g| matches!(result.captures.get(g.pattern), Some(Some(_))))?;
+    let capture = groups[idx]
+        .chosen
+        .and_then(|c| result.captures.get(c).copied().flatten());
+    if let Some(capture) = capture {
+        result.start_x = capture.start_x;
+        result.start_y = capture.start_y;
+        result.end_x = capture.end_x;
+        result.end_y = capture.end_y;
+    }
+    Some(idx)
 }
 
 pub struct QuickSelectOverlay {
@@ -227,6 +452,131 @@ struct QuickSelectRenderable {
 
     config: ConfigHandle,
     args: QuickSelectArguments,
+    /// The patterns that make up `pattern`, in the same order
+    patterns: Vec<QuickSelectPattern>,
+    pattern_groups: Vec<PatternGroups>,
+    /// The index into `patterns` of the pattern that
+    /// produced each of the `results`
+    result_patterns: Vec<Option<usize>>,
+    /// Describes what selecting a match will do
+    action_label: String,
+    /// Set once the first search has completed
+    searched: bool,
+    group: Option<Arc<QuickSelectGroup>>,
+}
+
+/// Shared by the overlays of a quick select that spans all of the
+/// visible panes in a tab, so that their labels are unique and typing
+/// a label into any of them acts on the match in whichever pane has it
+struct QuickSelectGroup {
+    /// The overlays in the order that labels are handed out
+    overlays: Vec<Weak<QuickSelectOverlay>>,
+    /// The pane that an uppercase label pastes into.  It isn't part of
+    /// the group when it was already showing an overlay of its own.
+    active_pane_id: PaneId,
+}
+
+impl QuickSelectGroup {
+    fn overlays(&self) -> Vec<Arc<QuickSelectOverlay>> {
+        self.overlays.iter().filter_map(Weak::upgrade).collect()
+    }
+
+    /// Shares out the labels once the searches in all of the
+    /// panes have completed
+    fn assign_labels(&self) {
+        let overlays = self.overlays();
+        let mut counts = vec![];
+        for overlay in &overlays {
+            let r = overlay.renderer.lock();
+            if !r.searched {
+                return;
+            }
+            counts.push(r.unique_result_count());
+        }
+        let alphabet = match overlays.first() {
+            Some(overlay) => overlay.renderer.lock().alphabet().to_string(),
+            None => return,
+        };
+
+        let labels = compute_labels_for_alphabet(&alphabet, counts.iter().sum());
+        let mut start = 0;
+        for (overlay, count) in overlays.iter().zip(counts) {
+            let end = (start + count).min(labels.len());
+            overlay
+                .renderer
+                .lock()
+                .apply_labels(&labels[start.min(end)..end]);
+            start += count;
+        }
+    }
+
+    /// Handles the keys that edit the label being typed.
+    /// Returns false for keys that the overlay should handle itself.
+    fn key_down(&self, key: KeyCode, mods: KeyModifiers) -> bool {
+        match (key, mods) {
+            (KeyCode::Escape, KeyModifiers::NONE) => self.close(),
+            (KeyCode::Char(c), KeyModifiers::NONE) | (KeyCode::Char(c), KeyModifiers::SHIFT) => {
+                let selection = self.edit_selection(|s| s.push(c));
+                self.select_by_label(&selection);
+            }
+            (KeyCode::Backspace, KeyModifiers::NONE) => {
+                self.edit_selection(|s| {
+                    s.pop();
+                });
+            }
+            (KeyCode::Char('u'), KeyModifiers::CTRL) => {
+                self.edit_selection(|s| s.clear());
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Applies `edit` to the label being typed, which is shown in
+    /// the bar of every pane
+    fn edit_selection<F: FnOnce(&mut String)>(&self, edit: F) -> String {
+        let overlays = self.overlays();
+        let mut selection = overlays
+            .first()
+            .map(|overlay| overlay.renderer.lock().selection.clone())
+            .unwrap_or_default();
+        edit(&mut selection);
+        for overlay in &overlays {
+            let mut r = overlay.renderer.lock();
+            r.selection = selection.clone();
+            let search_row = r.compute_search_row();
+            r.dirty_results.add(search_row);
+            r.window.invalidate();
+        }
+        selection
+    }
+
+    fn select_by_label(&self, selection: &str) {
+        let lowered = selection.to_lowercase();
+        let overlays = self.overlays();
+        // An uppercase label pastes into the active pane,
+        // rather than into the pane with the match
+        let paste_pane_id = if lowered != selection {
+            Some(self.active_pane_id)
+        } else {
+            None
+        };
+        for overlay in &overlays {
+            let mut r = overlay.renderer.lock();
+            if let Some(result_index) = r.by_label.get(&lowered).cloned() {
+                r.select_and_copy_match_number(result_index, paste_pane_id);
+                drop(r);
+                self.close();
+                return;
+            }
+        }
+    }
+
+    fn close(&self) {
+        for overlay in self.overlays() {
+            overlay.renderer.lock().close();
+        }
+    }
 }
 
 impl QuickSelectOverlay {
@@ -235,43 +585,63 @@ impl QuickSelectOverlay {
         pane: &Arc<dyn Pane>,
         args: &QuickSelectArguments,
     ) -> Arc<dyn Pane> {
+        Self::new(term_window, pane, args)
+    }
+
+    /// Creates an overlay for each of `panes`, with labels that are unique
+    /// across all of them. The first pane is given the shortest labels.
+    pub fn with_panes(
+        term_window: &TermWindow,
+        panes: &[Arc<dyn Pane>],
+        active_pane_id: PaneId,
+        args: &QuickSelectArguments,
+    ) -> Vec<Arc<dyn Pane>> {
+        let overlays: Vec<Arc<QuickSelectOverlay>> = panes
+            .iter()
+            .map(|pane| Self::new(term_window, pane, args))
+            .collect();
+        let group = Arc::new(QuickSelectGroup {
+            overlays: overlays.iter().map(Arc::downgrade).collect(),
+            active_pane_id,
+        });
+        for overlay in &overlays {
+            overlay.renderer.lock().group.replace(Arc::clone(&group));
+        }
+        overlays
+            .into_iter()
+            .map(|overlay| overlay as Arc<dyn Pane>)
+            .collect()
+    }
+
+    fn new(
+        term_window: &TermWindow,
+        pane: &Arc<dyn Pane>,
+        args: &QuickSelectArguments,
+    ) -> Arc<QuickSelectOverlay> {
         let viewport = term_window.get_viewport(pane.pane_id());
         let dims = pane.get_dimensions();
 
         let config = term_window.config.clone();
 
-        let mut pattern = "(?m)(".to_string();
-        let mut have_patterns = false;
+        let mut patterns: Vec<QuickSelectPattern> = vec![];
         if !args.patterns.is_empty() {
-            for p in &args.patterns {
-                if have_patterns {
-                    pattern.push('|');
-                }
-                pattern.push_str(p);
-                have_patterns = true;
-            }
+            patterns.extend(args.patterns.iter().map(|p| (**p).clone()));
         } else {
             // User-provided patterns take precedence over built-ins
-            for p in &config.quick_select_patterns {
-                if have_patterns {
-                    pattern.push('|');
-                }
-                pattern.push_str(p);
-                have_patterns = true;
-            }
+            patterns.extend(config.quick_select_patterns.iter().map(|p| (**p).clone()));
             if !config.disable_default_quick_select_patterns {
-                for p in &PATTERNS {
-                    if have_patterns {
-                        pattern.push('|');
-                    }
-                    pattern.push_str(p);
-                    have_patterns = true;
-                }
+                patterns.extend(PATTERNS.iter().map(|p| QuickSelectPattern {
+                    regex: p.to_string(),
+                    action: None,
+                    label: String::new(),
+                    group: None,
+                }));
             }
         }
-        pattern.push(')');
 
-        let pattern = Pattern::Regex(pattern);
+        let pattern = Pattern::Regex(compute_combined_pattern(&patterns));
+        let pattern_groups = compute_pattern_groups(&patterns);
+        let action_label = compute_action_label(&patterns, args);
 
         let window = term_window.window.clone().unwrap();
         let mut renderer = QuickSelectRenderable {
@@ -290,6 +660,12 @@ impl QuickSelectOverlay {
             height: dims.viewport_rows,
             config,
             args: args.clone(),
+            patterns,
+            pattern_groups,
+            result_patterns: vec![],
+            action_label,
+            searched: false,
+            group: None,
         };
 
         let search_row = renderer.compute_search_row();
@@ -348,6 +724,12 @@ impl Pane for QuickSelectOverlay {
 
     fn key_down(&self, key: KeyCode, mods: KeyModifiers) -> anyhow::Result<()> {
         let mods = mods.remove_positional_mods();
+        let group = self.renderer.lock().group.clone();
+        if let Some(group) = group {
+            if group.key_down(key, mods) {
+                return Ok(());
+            }
+        }
         match (key, mods) {
             (KeyCode::Escape, KeyModifiers::NONE) => self.renderer.lock().close(),
             (KeyCode::UpArrow, KeyModifiers::NONE)
@@ -418,7 +800,12 @@ impl Pane for QuickSelectOverlay {
                 let lowered = r.selection.to_lowercase();
                 let paste = lowered != r.selection;
                 if let Some(result_index) = r.by_label.get(&lowered).cloned() {
-                    r.select_and_copy_match_number(result_index, paste);
+                    let paste_pane_id = if paste {
+                        Some(self.delegate.pane_id())
+                    } else {
+                        None
+                    };
+                    r.select_and_copy_match_number(result_index, paste_pane_id);
                     r.close();
                 }
             }
@@ -515,6 +902,10 @@ impl Pane for QuickSelectOverlay {
         self.delegate.get_logical_lines(lines)
     }
 
+    fn get_line_timestamps(&self, lines: Range<StableRowIndex>) -> LineTimestamps {
+        self.delegate.get_line_timestamps(lines)
+    }
+
     fn with_lines_mut(&self, lines: Range<StableRowIndex>, with_lines: &mut dyn WithPaneLines) {
         let mut renderer = self.renderer.lock();
         // Take care to access self.delegate methods here before we get into
@@ -564,11 +955,7 @@ impl Pane for QuickSelectOverlay {
                             &format!(
                                 "Select: {}  (type highlighted prefix to {}, uppercase pastes, ESC to cancel)",
                                 self.renderer.selection,
-                                if self.renderer.args.label.is_empty() {
-                                    "copy"
-                                } else {
-                                    &self.renderer.args.label
-                                },
+                                self.renderer.action_label,
                             ),
                             rev,
                             SEQ_ZERO,
@@ -650,11 +1037,7 @@ impl Pane for QuickSelectOverlay {
                     &format!(
                         "Select: {}  (type highlighted prefix to {}, uppercase pastes, ESC to cancel)",
                         renderer.selection,
-                        if renderer.args.label.is_empty() {
-                            "copy"
-                        } else {
-                            &renderer.args.label
-                        },
+                        renderer.action_label,
                     ),
                     rev,
                     SEQ_ZERO,
@@ -745,26 +1128,34 @@ impl QuickSelectRenderable {
         self.result_pos = pos;
     }
 
-    fn recompute_results(&mut self) {
-        /// Produce the sorted seq of unique match_ids from the results
-        fn compute_uniq_results(results: &[SearchResult]) -> Vec<usize> {
-            let mut ids: Vec<usize> = results.iter().map(|sr| sr.match_id).collect();
-            ids.sort();
-            ids.dedup();
-            ids
+    fn alphabet(&self) -> &str {
+        if !self.args.alphabet.is_empty() {
+            &self.args.alphabet
+        } else {
+            &self.config.quick_select_alphabet
         }
+    }
 
-        let uniq_results = compute_uniq_results(&self.results);
+    /// The number of distinct match_ids in the results,
+    /// each of which needs its own label
+    fn unique_result_count(&self) -> usize {
+        let mut ids: Vec<usize> = self.results.iter().map(|sr| sr.match_id).collect();
+        ids.sort();
+        ids.dedup();
+        ids.len()
+    }
 
+    fn recompute_results(&mut self) {
         // Label each unique result
-        let labels = compute_labels_for_alphabet(
-            if !self.args.alphabet.is_empty() {
-                &self.args.alphabet
-            } else {
-                &self.config.quick_select_alphabet
-            },
-            uniq_results.len(),
-        );
+        let labels = compute_labels_for_alphabet(self.alphabet(), self.unique_result_count());
+        self.apply_labels(&labels);
+    }
+
+    fn apply_labels(&mut self, labels: &[String]) {
+        for idx in self.by_line.keys() {
+            self.dirty_results.add(*idx);
+        }
+        self.by_line.clear();
         self.by_label.clear();
 
         // Keep track of match_id -> label
@@ -832,6 +1223,7 @@ impl QuickSelectRenderable {
         }
 
         self.results.clear();
+        self.result_patterns.clear();
         self.by_line.clear();
         self.result_pos.take();
 
@@ -844,6 +1236,7 @@ impl QuickSelectRenderable {
             let pattern = self.pattern.clone();
             let scope = self.args.scope_lines;
             let viewport = self.viewport;
+            let pattern_groups = self.pattern_groups.clone();
             promise::spawn::spawn(async move {
                 let dims = pane.get_dimensions();
                 let scope = scope.unwrap_or(1000).max(dims.viewport_rows);
@@ -851,41 +1244,63 @@ impl QuickSelectRenderable {
                 let range = top.saturating_sub(scope as StableRowIndex)
                     ..top + (dims.viewport_rows + scope) as StableRowIndex;
                 let limit = None;
-                let mut results = pane.search(pattern, range, limit).await?;
-                results.sort();
+                let results = pane.search(pattern, range, limit).await?;
+                let mut results: Vec<(SearchResult, Option<usize>)> = results
+                    .into_iter()
+                    .map(|mut result| {
+                        let pattern_idx = select_pattern_group(&pattern_groups, &mut result);
+                        (result, pattern_idx)
+                    })
+                    .collect();
+                results.sort_by(|a, b| a.0.cmp(&b.0));
 
                 let pane_id = pane.pane_id();
-                let mut results = Some(results);
+                let mut results: Option<(Vec<SearchResult>, Vec<Option<usize>>)> =
+                    Some(results.into_iter().unzip());
                 window.notify(TermWindowNotif::Apply(Box::new(move |term_window| {
-                    let state = term_window.pane_state(pane_id);
-                    if let Some(overlay) = state.overlay.as_ref() {
-                        if let Some(search_overlay) =
-                            overlay.pane.downcast_ref::<QuickSelectOverlay>()
-                        {
-                            let mut r = search_overlay.renderer.lock();
-                            r.results = results.take().unwrap();
+                    let group = {
+                        let state = term_window.pane_state(pane_id);
+                        let search_overlay =
+                            match state.overlay.as_ref().and_then(|overlay| {
+                                overlay.pane.downcast_ref::<QuickSelectOverlay>()
+                            }) {
+                                Some(search_overlay) => search_overlay,
+                                None => return,
+                            };
+                        let mut r = search_overlay.renderer.lock();
+                        let (results, result_patterns) = results.take().unwrap();
+                        r.results = results;
+                        r.result_patterns = result_patterns;
+                        r.searched = true;
+                        if r.group.is_none() {
                             r.recompute_results();
-                            let num_results = r.results.len();
-
-                            if !r.results.is_empty() {
-                                match &r.viewport {
-                                    Some(y) if is_initial_run => {
-                                        r.result_pos = r
-                                            .results
-                                            .iter()
-                                            .position(|result| result.start_y >= *y);
-                                    }
-                                    _ => {
-                                        r.activate_match_number(num_results - 1);
-                                    }
+                        }
+                        let num_results = r.results.len();
+
+                        if !r.results.is_empty() {
+                            match &r.viewport {
+                                Some(y) if is_initial_run => {
+                                    r.result_pos =
+                                        r.results.iter().position(|result| result.start_y >= *y);
                                 }
-                            } else {
-                                if !is_initial_run {
-                                    r.set_viewport(None);
+                                _ => {
+                                    r.activate_match_number(num_results - 1);
                                 }
-                                r.clear_selection();
                             }
+                        } else {
+                            if !is_initial_run {
+                                r.set_viewport(None);
+                            }
+                            r.clear_selection();
                         }
+                        let group = r.group.clone();
+                        group
+                    };
+                    // Labels are shared out across the group, which needs
+                    // the locks of the other overlays, so this happens after
+                    // the lock on this one has been released
+                    if let Some(group) = group {
+                        group.assign_labels();
                     }
                 })));
                 anyhow::Result::<()>::Ok(())
@@ -909,11 +1324,27 @@ impl QuickSelectRenderable {
             })));
     }
 
-    fn select_and_copy_match_number(&mut self, n: usize, paste: bool) {
+    /// Selects and acts on the nth result, pasting it into
+    /// `paste_pane_id` if that is set
+    fn select_and_copy_match_number(&mut self, n: usize, paste_pane_id: Option<PaneId>) {
         let result = self.results[n].clone();
 
         let pane_id = self.delegate.pane_id();
-        let action = self.args.action.clone();
+        // The action of the pattern that produced the match takes
+        // precedence over the action from the arguments
+        let action = match self
+            .result_patterns
+            .get(n)
+            .copied()
+            .flatten()
+            .and_then(|idx| self.patterns.get(idx))
+        {
+            Some(QuickSelectPattern {
+                action: Some(action),
+                ..
+            }) => Some(action.clone()),
+            _ => self.args.action.clone(),
+        };
         self.window
             .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
                 let mux = mux::Mux::get();
@@ -938,8 +1369,8 @@ impl QuickSelectRenderable {
 
                     let text = term_window.selection_text(&pane);
                     if !text.is_empty() {
-                        if paste {
-                            let _ = pane.send_paste(&text);
+                        if let Some(paste_pane) = paste_pane_id.and_then(|id| mux.get_pane(id)) {
+                            let _ = paste_pane.send_paste(&text);
                         }
                         if let Some(action) = action {
                             let _ = term_window.perform_key_assignment(&pane, &action);
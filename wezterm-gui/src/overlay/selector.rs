@@ -1,8 +1,7 @@
 use super::quickselect;
 use crate::scripting::guiwin::GuiWin;
 use config::keyassignment::{InputSelector, InputSelectorEntry, KeyAssignment};
-use fuzzy_matcher::skim::SkimMatcherV2;
-use fuzzy_matcher::FuzzyMatcher;
+use fuzzy::{CaseMatching, Matcher, Pattern};
 use mux::termwiztermtab::TermWizTerminal;
 use mux_lua::MuxPane;
 use std::rc::Rc;
@@ -40,11 +39,12 @@ impl SelectorState {
 
         self.filtered_entries.clear();
 
-        let matcher = SkimMatcherV2::default();
+        let pattern = Pattern::parse(&self.filter_term, CaseMatching::Smart);
+        let mut matcher = Matcher::new();
 
         struct MatchResult {
             row_idx: usize,
-            score: i64,
+            score: i32,
         }
 
         let mut scores: Vec<MatchResult> = self
@@ -53,7 +53,7 @@ impl SelectorState {
             .iter()
             .enumerate()
             .filter_map(|(row_idx, entry)| {
-                let score = matcher.fuzzy_match(&entry.label, &self.filter_term)?;
+                let score = matcher.score(&pattern, &entry.label)?;
                 Some(MatchResult { row_idx, score })
             })
             .collect();
@@ -2,7 +2,9 @@
 use super::luaerr;
 use crate::termwindow::TermWindowNotif;
 use crate::TermWindow;
-use config::keyassignment::{ClipboardCopyDestination, KeyAssignment};
+use config::keyassignment::{
+    ClipboardCopyDestination, FuzzyScrollbackSearchArguments, KeyAssignment,
+};
 use luahelper::*;
 use mlua::{UserData, UserDataMethods, UserDataRef};
 use mux::pane::PaneId;
@@ -309,6 +311,17 @@ impl UserData for GuiWin {
                 Ok(())
             },
         );
+        methods.add_method(
+            "open_fuzzy_scrollback",
+            |_, this, args: Option<FuzzyScrollbackSearchArguments>| {
+                let args = args.unwrap_or_default();
+                this.window
+                    .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                        term_window.open_fuzzy_scrollback(&args);
+                    })));
+                Ok(())
+            },
+        );
         methods.add_async_method(
             "get_selection_escapes_for_pane",
             |_, this, pane: UserDataRef<MuxPane>| async move {
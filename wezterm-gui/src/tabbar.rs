@@ -2,9 +2,10 @@ use crate::termwindow::{PaneInformation, TabInformation, UIItem, UIItemType};
 use config::{ConfigHandle, TabBarColors};
 use finl_unicode::grapheme_clusters::Graphemes;
 use mlua::FromLua;
-use termwiz::cell::{unicode_column_width, Cell, CellAttributes};
-use termwiz::color::ColorSpec;
+use termwiz::cell::{unicode_column_width, Cell, CellAttributes, Underline};
+use termwiz::color::{ColorAttribute, ColorSpec};
 use termwiz::escape::csi::Sgr;
+use termwiz::escape::osc::Progress;
 use termwiz::escape::parser::Parser;
 use termwiz::escape::{Action, ControlCode, CSI};
 use termwiz::surface::SEQ_ZERO;
@@ -32,6 +33,8 @@ pub enum TabBarItem {
 pub struct TabEntry {
     pub item: TabBarItem,
     pub title: Line,
+    /// The progress reported by the active pane of a tab
+    pub progress: Progress,
     x: usize,
     width: usize,
 }
@@ -159,6 +162,40 @@ fn compute_tab_title(
     }
 }
 
+/// Returns the palette index of the color used to indicate `progress`
+pub fn progress_palette_index(progress: Progress) -> u8 {
+    match progress {
+        Progress::SetError(_) => 1,
+        Progress::Paused(_) => 3,
+        Progress::None | Progress::SetPercentage(_) | Progress::SetIndeterminate => 2,
+    }
+}
+
+/// Indicates `progress` in the retro tab bar by underlining the portion
+/// of the tab title that corresponds to the completion percentage.
+/// When the completion is unknown, the whole title is underlined with
+/// dashes.
+fn underline_progress(line: &mut Line, progress: Progress) {
+    let (underline, num_cells) = match progress {
+        Progress::None => return,
+        Progress::SetIndeterminate => (Underline::Dashed, line.len()),
+        _ => (
+            Underline::Single,
+            (line.len() * progress.percentage().unwrap_or(0) as usize + 50) / 100,
+        ),
+    };
+    let color = ColorAttribute::PaletteIndex(progress_palette_index(progress));
+    for cell in line
+        .cells_mut_for_attr_changes_only()
+        .iter_mut()
+        .take(num_cells)
+    {
+        cell.attrs_mut()
+            .set_underline(underline)
+            .set_underline_color(color);
+    }
+}
+
 fn is_tab_hover(mouse_x: Option<usize>, x: usize, tab_title_len: usize) -> bool {
     return mouse_x
         .map(|mouse_x| mouse_x >= x && mouse_x < x + tab_title_len)
@@ -172,6 +209,7 @@ impl TabBarState {
             items: vec![TabEntry {
                 item: TabBarItem::None,
                 title: Line::from_text(" ", &CellAttributes::blank(), 1, None),
+                progress: Progress::None,
                 x: 1,
                 width: 1,
             }],
@@ -265,6 +303,7 @@ impl TabBarState {
             items.push(TabEntry {
                 item: TabBarItem::WindowButton(*button),
                 title: title.to_owned(),
+                progress: Progress::None,
                 x: *x,
                 width,
             });
@@ -392,6 +431,7 @@ impl TabBarState {
             items.push(TabEntry {
                 item: TabBarItem::LeftStatus,
                 title: left_status_line.clone(),
+                progress: Progress::None,
                 x,
                 width: left_status_line.len(),
             });
@@ -440,11 +480,21 @@ impl TabBarState {
                 tab_line.resize(tab_width_max, SEQ_ZERO);
             }
 
+            let progress = tab_info[tab_idx]
+                .active_pane
+                .as_ref()
+                .map(|pane| pane.progress)
+                .unwrap_or_default();
+            if !config.use_fancy_tab_bar {
+                underline_progress(&mut tab_line, progress);
+            }
+
             let width = tab_line.len();
 
             items.push(TabEntry {
                 item: TabBarItem::Tab { tab_idx, active },
                 title,
+                progress,
                 x: tab_start_idx,
                 width,
             });
@@ -467,6 +517,7 @@ impl TabBarState {
             items.push(TabEntry {
                 item: TabBarItem::NewTabButton,
                 title: new_tab_button.clone(),
+                progress: Progress::None,
                 x: button_start,
                 width,
             });
@@ -529,6 +580,7 @@ impl TabBarState {
         items.push(TabEntry {
             item: TabBarItem::RightStatus,
             title: right_status_line.clone(),
+            progress: Progress::None,
             x,
             width: status_space_available,
         });
@@ -1,148 +1,621 @@
-include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
-
 use std::borrow::Cow;
 use std::cell::{Ref, RefCell};
-use std::sync::{Arc, mpsc, Mutex};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{mpsc, Arc, Mutex, Weak};
 use std::thread;
-use parking_lot::RwLock;
 
-use config::{Dimension, SrgbaTuple};
-use mux::pane::{LogicalLine, Pane};
+use config::keyassignment::ClipboardCopyDestination;
+use config::keyassignment::{
+    FuzzyCaseMatching, FuzzyScrollbackFilter, FuzzyScrollbackScope, FuzzyScrollbackSearchArguments,
+    FuzzyScrollbackSort,
+};
+use config::{ConfigHandle, Dimension, SrgbaTuple};
+use fuzzy::{CaseMatching, MatchResult, Matcher, Pattern};
 use mux::pane::Pattern::CaseInSensitiveString;
+use mux::pane::{LogicalLine, Pane, PaneId};
+use mux::tab::Tab;
+use mux::{Mux, MuxNotification};
+use mux_lua::MuxPane;
 use termwiz::cell::CellAttributes;
 use termwiz::color;
 use termwiz::color::ColorSpec::TrueColor;
 use termwiz::surface::Line;
-use wezterm_term::{KeyCode, KeyModifiers, MouseEvent, StableRowIndex};
+use wezterm_dynamic::ToDynamic;
+use wezterm_term::input::{MouseButton, MouseEventKind};
+use wezterm_term::{KeyCode, KeyModifiers, MouseEvent, SemanticType, SemanticZone, StableRowIndex};
 use window::color::LinearRgba;
-use window::{Modifiers, WindowOps};
+use window::{Modifiers, RectF, Window, WindowOps};
 
-use crate::termwindow::{DimensionContext, TermWindow};
+use crate::scripting::guiwin::GuiWin;
 use crate::termwindow::box_model::*;
 use crate::termwindow::modal::Modal;
 use crate::termwindow::render::corners::{
     BOTTOM_LEFT_ROUNDED_CORNER, BOTTOM_RIGHT_ROUNDED_CORNER, TOP_LEFT_ROUNDED_CORNER,
     TOP_RIGHT_ROUNDED_CORNER,
 };
+use crate::termwindow::{DimensionContext, TermWindow, TermWindowNotif};
 use crate::utilsprites::RenderMetrics;
 
 pub struct EricRow {
+    /// Index into FuzzySearcher::sources of the pane this row came from
+    pub source: usize,
     pub row_index: StableRowIndex,
     pub first_y: usize,
-    pub positions: Vec<u32>
+    pub positions: Vec<usize>,
+    pub score: i32,
+    /// The text that was matched
+    pub text: String,
+    /// The logical column at which `text` starts
+    pub offset: usize,
 }
 pub struct EricWindow {
     element: RefCell<Option<Vec<ComputedElement>>>,
     selection: RefCell<String>,
     selected_row: RefCell<usize>,
+    /// Identifies the selected result by pane and row, so that the
+    /// selection stays put when new output is merged into the results
+    selected_key: RefCell<Option<(usize, StableRowIndex)>>,
+    seen_results_generation: RefCell<usize>,
     top_row: RefCell<StableRowIndex>,
     max_rows_on_screen: RefCell<usize>,
-    ms: RwLock<Vec<(i32, EricRow)>>,
+    show_preview: RefCell<bool>,
+    /// Results that were marked with Tab, by pane and row
+    marked: RefCell<HashSet<(usize, StableRowIndex)>>,
+    /// Where each visible row of the result list was drawn, along with
+    /// the index of the result it belongs to; used for mouse hit testing
+    result_rects: RefCell<Vec<(usize, RectF)>>,
     row_indexes: RefCell<Vec<EricRow>>,
-    fuzzy_searcher: Arc<FuzzySearcher>
+    fuzzy_searcher: Arc<FuzzySearcher>,
+    scope: FuzzyScrollbackScope,
+    filter: RefCell<FuzzyScrollbackFilter>,
+}
+
+/// A pane whose scrollback is included in the search
+pub struct ScrollbackSource {
+    pub pane: Arc<dyn Pane>,
+    /// "tab title: pane title", shown alongside each result
+    pub label: String,
+}
+
+impl ScrollbackSource {
+    fn new(pane: Arc<dyn Pane>, tab_title: &str) -> Self {
+        let label = format!("{}: {}", tab_title, pane.get_title());
+        Self { pane, label }
+    }
+}
+
+fn tab_title(tab: &Tab, tab_idx: usize) -> String {
+    let title = tab.get_title();
+    if title.is_empty() {
+        format!("{}", tab_idx + 1)
+    } else {
+        title
+    }
+}
+
+fn collect_sources(term_window: &TermWindow, scope: FuzzyScrollbackScope) -> Vec<ScrollbackSource> {
+    let mux = Mux::get();
+    let window_ids = match scope {
+        FuzzyScrollbackScope::Workspace => mux.iter_windows_in_workspace(&mux.active_workspace()),
+        _ => vec![term_window.mux_window_id],
+    };
+
+    let mut sources = vec![];
+    for window_id in window_ids {
+        let tabs: Vec<(usize, Arc<Tab>)> = match mux.get_window(window_id) {
+            Some(window) => window.iter().cloned().enumerate().collect(),
+            None => continue,
+        };
+        let active_tab_id = mux
+            .get_active_tab_for_window(window_id)
+            .map(|tab| tab.tab_id());
+
+        for (tab_idx, tab) in tabs {
+            let is_active_tab = Some(tab.tab_id()) == active_tab_id;
+            let title = tab_title(&tab, tab_idx);
+            match scope {
+                FuzzyScrollbackScope::Pane => {
+                    if is_active_tab {
+                        if let Some(pane) = tab.get_active_pane() {
+                            sources.push(ScrollbackSource::new(pane, &title));
+                        }
+                    }
+                }
+                FuzzyScrollbackScope::Tab => {
+                    if is_active_tab {
+                        for pos in tab.iter_panes() {
+                            sources.push(ScrollbackSource::new(pos.pane, &title));
+                        }
+                    }
+                }
+                FuzzyScrollbackScope::Window | FuzzyScrollbackScope::Workspace => {
+                    for pos in tab.iter_panes() {
+                        sources.push(ScrollbackSource::new(pos.pane, &title));
+                    }
+                }
+            }
+        }
+    }
+    sources
 }
 
-impl EricWindow{
-    pub fn new(term_window: &mut TermWindow) -> Self {
-        unsafe {
-            let pane = term_window.get_active_pane_or_overlay().unwrap();
-            let pn_dim = pane.get_dimensions();
-            let rows = pn_dim.scrollback_rows as StableRowIndex;
+/// Makes the pane active in its tab and window, then opens copy mode
+/// with the cursor at the specified cell
+fn activate_pane_in_copy_mode(
+    term_window: &mut TermWindow,
+    pane: &Arc<dyn Pane>,
+    x: usize,
+    y: StableRowIndex,
+) -> anyhow::Result<()> {
+    let mux = Mux::get();
+    let pane_id = pane.pane_id();
+    let (_domain_id, window_id, tab_id) = mux
+        .resolve_pane_id(pane_id)
+        .ok_or_else(|| anyhow::anyhow!("pane {pane_id} not found"))?;
+
+    if let Some(mut window) = mux.get_window_mut(window_id) {
+        if let Some(tab_idx) = window.idx_by_id(tab_id) {
+            window.save_and_then_set_active(tab_idx);
+        }
+    }
+    if let Some(tab) = mux.get_tab(tab_id) {
+        tab.set_active_pane(pane);
+    }
 
-            let logical_lines = pane.get_logical_lines(0..rows);
-            let (_first_row, lines) = pane.get_lines(0..rows);
-            Self {
-                element: RefCell::new(None),
-                selection: RefCell::new(String::new()),
-                row_indexes: RefCell::new(Vec::new()),
-                ms: RwLock::new(Vec::new()),
-                selected_row: RefCell::new(0),
-                top_row: RefCell::new(0),
-                max_rows_on_screen: RefCell::new(0),
-                fuzzy_searcher: FuzzySearcher::new(logical_lines),
+    if window_id == term_window.mux_window_id {
+        return open_copy_mode(term_window, pane, x, y);
+    }
+
+    let gui_window = crate::frontend::front_end()
+        .gui_window_for_mux_window(window_id)
+        .ok_or_else(|| anyhow::anyhow!("no gui window for mux window {window_id}"))?;
+    gui_window.window.focus();
+    gui_window
+        .window
+        .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+            if let Some(pane) = Mux::get().get_pane(pane_id) {
+                if let Err(err) = open_copy_mode(term_window, &pane, x, y) {
+                    log::error!("failed to open copy mode: {err:#}");
+                }
             }
+        })));
+    Ok(())
+}
+
+fn open_copy_mode(
+    term_window: &mut TermWindow,
+    pane: &Arc<dyn Pane>,
+    x: usize,
+    y: StableRowIndex,
+) -> anyhow::Result<()> {
+    let existing = term_window
+        .pane_state(pane.pane_id())
+        .overlay
+        .as_ref()
+        .map(|overlay| overlay.pane.clone());
+
+    let replace_current = match existing {
+        Some(overlay)
+            if overlay
+                .downcast_ref::<crate::overlay::CopyOverlay>()
+                .is_some() =>
+        {
+            let copy = overlay
+                .downcast_ref::<crate::overlay::CopyOverlay>()
+                .unwrap();
+            let mut params = copy.get_params();
+            params.editing_search = false;
+            copy.apply_params(params);
+            copy.select_cell(x, y);
+            true
         }
+        _ => {
+            let copy = crate::overlay::CopyOverlay::with_pane(
+                term_window,
+                pane,
+                crate::overlay::CopyModeParams {
+                    pattern: CaseInSensitiveString("".to_string()),
+                    editing_search: false,
+                },
+            )?;
+            if let Some(overlay) = copy.downcast_ref::<crate::overlay::CopyOverlay>() {
+                overlay.select_cell(x, y);
+            }
+            term_window.assign_overlay_for_pane(copy.pane_id(), copy);
+            false
+        }
+    };
+
+    if let Some(overlay) = term_window.pane_state(pane.pane_id()).overlay.as_mut() {
+        overlay
+            .key_table_state
+            .activate(crate::termwindow::keyevent::KeyTableArgs {
+                name: "copy_mode",
+                timeout_milliseconds: None,
+                replace_current,
+                one_shot: false,
+                until_unknown: false,
+                prevent_fallback: false,
+            });
+    }
+    Ok(())
+}
+
+/// What happens to the selected result once it has been accepted,
+/// unless a `fuzzy-scrollback-selected` event handler returns `false`
+#[derive(Clone, Copy, Debug)]
+enum SelectedAction {
+    /// Activate the pane and open copy mode at the match
+    CopyMode,
+    /// Paste the command into the active pane, optionally pressing
+    /// Enter to run it
+    Paste { run: bool },
+}
+
+impl SelectedAction {
+    fn perform(
+        self,
+        term_window: &mut TermWindow,
+        pane_id: PaneId,
+        x: usize,
+        y: StableRowIndex,
+        text: &str,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::CopyMode => {
+                let pane = Mux::get()
+                    .get_pane(pane_id)
+                    .ok_or_else(|| anyhow::anyhow!("pane {pane_id} no longer exists"))?;
+                activate_pane_in_copy_mode(term_window, &pane, x, y)
+            }
+            Self::Paste { run } => {
+                if let Some(pane) = term_window.get_active_pane_no_overlay() {
+                    pane.send_paste(text)?;
+                    if run {
+                        pane.key_down(KeyCode::Enter, KeyModifiers::NONE)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The details of the selected result that are passed to the
+/// `fuzzy-scrollback-selected` event
+#[derive(ToDynamic)]
+struct SelectedResult {
+    /// The first row of the logical line that holds the match
+    stable_row: StableRowIndex,
+    /// The text that was matched
+    text: String,
+    /// The indices of the matched chars in `text`
+    positions: Vec<usize>,
+}
+
+/// Emits `fuzzy-scrollback-selected`, then performs `action` in
+/// `window` unless a handler has returned `false`
+async fn emit_selected_event(
+    lua: Option<Rc<mlua::Lua>>,
+    window: GuiWin,
+    pane_id: PaneId,
+    result: SelectedResult,
+    action: SelectedAction,
+    (x, y): (usize, StableRowIndex),
+) -> anyhow::Result<()> {
+    let text = result.text.clone();
+    let default_action = match lua {
+        Some(lua) => {
+            let result = luahelper::dynamic_to_lua_value(&lua, result.to_dynamic())?;
+            let args = lua.pack_multi((window.clone(), MuxPane(pane_id), result))?;
+            config::lua::emit_event(&lua, ("fuzzy-scrollback-selected".to_string(), args))
+                .await
+                .map_err(|e| {
+                    log::error!("while processing fuzzy-scrollback-selected event: {:#}", e);
+                    e
+                })?
+        }
+        None => true,
+    };
+    if default_action {
+        window
+            .window
+            .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                if let Err(err) = action.perform(term_window, pane_id, x, y, &text) {
+                    log::error!("fuzzy scrollback: {:#}", err);
+                }
+            })));
+    }
+    Ok(())
+}
+
+impl EricWindow {
+    pub fn new(term_window: &mut TermWindow, args: &FuzzyScrollbackSearchArguments) -> Self {
+        let sources = collect_sources(term_window, args.scope);
+        let options = SearchOptions::new(&term_window.config, args);
+        let fuzzy_searcher = FuzzySearcher::new(sources, options);
+        fuzzy_searcher.subscribe_to_pane_output(term_window.window.clone());
+        let window = Self {
+            element: RefCell::new(None),
+            selection: RefCell::new(args.initial_query.clone()),
+            row_indexes: RefCell::new(Vec::new()),
+            selected_row: RefCell::new(0),
+            selected_key: RefCell::new(None),
+            seen_results_generation: RefCell::new(0),
+            top_row: RefCell::new(0),
+            max_rows_on_screen: RefCell::new(0),
+            show_preview: RefCell::new(true),
+            marked: RefCell::new(HashSet::new()),
+            result_rects: RefCell::new(vec![]),
+            fuzzy_searcher,
+            scope: args.scope,
+            filter: RefCell::new(args.filter),
+        };
+        if !args.initial_query.is_empty() {
+            window.start_fuzzy_search(term_window);
+        }
+        window
     }
 
     fn start_fuzzy_search(&self, term_window: &mut TermWindow) {
         let selection = self.selection.borrow().clone();
-        match term_window.get_active_pane_or_overlay(){
-            Some(pn_value) => {
-                let fuzzy_searcher_clone = Arc::clone(&self.fuzzy_searcher);
-                fuzzy_searcher_clone.search(selection.as_ref(), pn_value, term_window);
-                term_window.invalidate_modal();
-            },
-            None => {}
-        };
+        let fuzzy_searcher_clone = Arc::clone(&self.fuzzy_searcher);
+        let filter = *self.filter.borrow();
+        fuzzy_searcher_clone.search(selection.as_ref(), filter, term_window);
+        term_window.invalidate_modal();
     }
 
     fn updated_input(&self) {
         *self.selected_row.borrow_mut() = 0;
+        *self.selected_key.borrow_mut() = None;
         *self.top_row.borrow_mut() = 0;
     }
 
+    /// When the results have changed since we last looked, find the
+    /// previously selected row in the new results so that the selection
+    /// doesn't jump around while new output is being merged in
+    fn sync_selection(&self) {
+        let generation = self
+            .fuzzy_searcher
+            .results_generation
+            .load(Ordering::SeqCst);
+        if generation == *self.seen_results_generation.borrow() {
+            return;
+        }
+        *self.seen_results_generation.borrow_mut() = generation;
+
+        let results = self.fuzzy_searcher.results.read().unwrap();
+        let row = self
+            .selected_key
+            .borrow()
+            .and_then(|(source, row_index)| {
+                results
+                    .iter()
+                    .position(|r| r.source == source && r.row_index == row_index)
+            })
+            .unwrap_or(0);
+        *self.selected_row.borrow_mut() = row;
+        *self.selected_key.borrow_mut() = results.get(row).map(|r| (r.source, r.row_index));
+    }
+
+    fn set_selected_row(&self, row: usize) {
+        let results = self.fuzzy_searcher.results.read().unwrap();
+        if let Some(result) = results.get(row) {
+            *self.selected_row.borrow_mut() = row;
+            *self.selected_key.borrow_mut() = Some((result.source, result.row_index));
+            *self.top_row.borrow_mut() = result.row_index;
+        }
+    }
+
     fn move_up(&self) {
-        let mut row = self.selected_row.borrow_mut();
-        *row = row.saturating_sub(1);
-        let mut top_row = self.top_row.borrow_mut();
-        let commands = self.fuzzy_searcher.results.read().unwrap();
-        *top_row = commands[*row].row_index;
+        self.sync_selection();
+        let row = self.selected_row.borrow().saturating_sub(1);
+        self.set_selected_row(row);
     }
 
     fn move_down(&self) {
-        let mut row = self.selected_row.borrow_mut();
-        let commands = self.fuzzy_searcher.results.read().unwrap();
-        if(*row + 1 < commands.iter().count())
-        {
-            *row = row.saturating_add(1);
-            let mut top_row = self.top_row.borrow_mut();
-            if(*row < commands.iter().count())
-            {
-                *top_row = commands[*row].row_index;
+        self.sync_selection();
+        let row = self.selected_row.borrow().saturating_add(1);
+        self.set_selected_row(row);
+    }
+
+    fn page_up(&self) {
+        self.sync_selection();
+        let page = (*self.max_rows_on_screen.borrow()).max(1);
+        let row = self.selected_row.borrow().saturating_sub(page);
+        self.set_selected_row(row);
+    }
+
+    fn page_down(&self) {
+        self.sync_selection();
+        let page = (*self.max_rows_on_screen.borrow()).max(1);
+        let last = self
+            .fuzzy_searcher
+            .results
+            .read()
+            .unwrap()
+            .len()
+            .saturating_sub(1);
+        let row = self.selected_row.borrow().saturating_add(page).min(last);
+        self.set_selected_row(row);
+    }
+
+    /// Removes the word before the end of the input, along with any
+    /// whitespace that follows it
+    fn delete_word(&self) {
+        let mut selection = self.selection.borrow_mut();
+        let len = selection.trim_end().len();
+        selection.truncate(len);
+        let word_start = selection
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(idx, c)| idx + c.len_utf8())
+            .unwrap_or(0);
+        selection.truncate(word_start);
+    }
+
+    fn toggle_mark(&self) {
+        self.sync_selection();
+        if let Some(key) = *self.selected_key.borrow() {
+            let mut marked = self.marked.borrow_mut();
+            if !marked.remove(&key) {
+                marked.insert(key);
             }
         }
+        self.move_down();
+    }
+
+    /// Copies the text of the marked results to the clipboard, in the
+    /// order that they appear in the scrollback.  If nothing is marked,
+    /// the selected result is copied instead.
+    fn copy_marked(&self, term_window: &mut TermWindow) {
+        self.sync_selection();
+        let mut keys: Vec<(usize, StableRowIndex)> = self.marked.borrow().iter().copied().collect();
+        if keys.is_empty() {
+            keys.extend(*self.selected_key.borrow());
+        }
+        keys.sort();
+
+        let text = keys
+            .into_iter()
+            .filter_map(|(source, row_index)| self.fuzzy_searcher.line_text(source, row_index))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        term_window.cancel_modal();
+        if !text.is_empty() {
+            term_window
+                .copy_to_clipboard(ClipboardCopyDestination::ClipboardAndPrimarySelection, text);
+        }
+    }
+
+    fn prompt(&self) -> &'static str {
+        match *self.filter.borrow() {
+            FuzzyScrollbackFilter::All => "> ",
+            FuzzyScrollbackFilter::Input => "history> ",
+        }
+    }
+
+    /// Acts on the selected result: opens copy mode at the match, or
+    /// sends the command to the active pane when picking from the
+    /// command history
+    fn accept_selected(&self, term_window: &mut TermWindow) -> anyhow::Result<()> {
+        let action = match *self.filter.borrow() {
+            FuzzyScrollbackFilter::All => SelectedAction::CopyMode,
+            FuzzyScrollbackFilter::Input => SelectedAction::Paste { run: false },
+        };
+        self.accept(term_window, action);
+        Ok(())
+    }
+
+    fn toggle_filter(&self) {
+        {
+            let mut filter = self.filter.borrow_mut();
+            *filter = match *filter {
+                FuzzyScrollbackFilter::All => FuzzyScrollbackFilter::Input,
+                FuzzyScrollbackFilter::Input => FuzzyScrollbackFilter::All,
+            };
+        }
+        self.marked.borrow_mut().clear();
+        self.updated_input();
+    }
+
+    /// Closes the modal and emits `fuzzy-scrollback-selected` for the
+    /// selected result, which then has `action` performed on it unless
+    /// a handler prevents it
+    fn accept(&self, term_window: &mut TermWindow, action: SelectedAction) {
+        self.sync_selection();
+        let selected = self
+            .fuzzy_searcher
+            .results
+            .read()
+            .unwrap()
+            .get(*self.selected_row.borrow())
+            .map(|row| {
+                let result = SelectedResult {
+                    stable_row: row.row_index,
+                    text: row.text.clone(),
+                    positions: row.positions.iter().map(|p| p - row.offset).collect(),
+                };
+                (row.source, row.row_index, row.first_y, result)
+            });
+
+        term_window.cancel_modal();
+
+        let (source, row_index, logical_x, result) = match selected {
+            Some(selected) => selected,
+            None => return,
+        };
+        let pane_id = self.fuzzy_searcher.sources[source].pane.pane_id();
+        let (y, x) = self
+            .fuzzy_searcher
+            .physical_coord(source, row_index, logical_x);
+        let window = GuiWin::new(term_window);
+
+        promise::spawn::spawn(config::with_lua_config_on_main_thread(move |lua| {
+            emit_selected_event(lua, window, pane_id, result, action, (x, y))
+        }))
+        .detach();
+    }
+
+    /// Returns the index of the result drawn at the position of `event`
+    /// Returns the result under the mouse pointer.  The pointer position
+    /// is in window pixels, the same space in which the results were
+    /// laid out, so there is no need to go via the cell grid.
+    fn result_at(&self, term_window: &TermWindow) -> Option<usize> {
+        let event = term_window.current_mouse_event.as_ref()?;
+        let point = euclid::point2(event.coords.x as f32, event.coords.y as f32);
+
+        self.result_rects
+            .borrow()
+            .iter()
+            .find(|(_, rect)| rect.contains(point))
+            .map(|(idx, _)| *idx)
     }
 
     fn create_prompt_element(
         &self,
         term_window: &TermWindow,
         panel_width: f32,
-        background_color: LinearRgba
+        background_color: LinearRgba,
     ) -> Element {
         let selection = self.selection.borrow();
         let selection = selection.as_str();
-        let font = term_window
-            .fonts
-            .default_font()
-            .expect("to resolve font");
-
-        let prompt_elements =
-            vec![
-                Element::new(&font, ElementContent::Text(format!("> {selection}_")))
-                    .colors(ElementColors {
-                        border: BorderColor::default(),
-                        bg: LinearRgba::TRANSPARENT.into(),
-                        text: term_window
-                            .config
-                            .command_palette_fg_color
-                            .to_linear()
-                            .into(),
-                    })
-                    .display(DisplayType::Block),
-            ];
+        let font = term_window.fonts.default_font().expect("to resolve font");
+
+        let prompt_elements = vec![Element::new(
+            &font,
+            ElementContent::Text(format!("{}{selection}_", self.prompt())),
+        )
+        .colors(ElementColors {
+            border: BorderColor::default(),
+            bg: LinearRgba::TRANSPARENT.into(),
+            text: term_window
+                .config
+                .command_palette_fg_color
+                .to_linear()
+                .into(),
+        })
+        .display(DisplayType::Block)];
         self.create_panel_element(
             term_window,
             panel_width,
             1.0,
             background_color,
             BorderColor::new(
-                    term_window.config.command_palette_fg_color.to_linear().into(),
-                ),
+                term_window
+                    .config
+                    .command_palette_fg_color
+                    .to_linear()
+                    .into(),
+            ),
             ElementContent::Children(prompt_elements),
             0.25,
-            0.25
+            0.25,
         )
     }
 
@@ -155,23 +628,28 @@ impl EricWindow{
         border_color: BorderColor,
         content: ElementContent,
         margin_cell_percent: f32,
-        padding_cell_percent: f32
+        padding_cell_percent: f32,
     ) -> Element {
-        let font = term_window
-            .fonts
-            .default_font()
-            .expect("to resolve font");
+        let font = term_window.fonts.default_font().expect("to resolve font");
 
         Element::new(&font, content)
             .colors(ElementColors {
                 border: BorderColor::default(),
                 bg: background_color.into(),
-                text: term_window.config.command_palette_fg_color.to_linear().into(),
+                text: term_window
+                    .config
+                    .command_palette_fg_color
+                    .to_linear()
+                    .into(),
             })
             .colors(ElementColors {
                 border: border_color,
                 bg: background_color.into(),
-                text: term_window.config.command_palette_fg_color.to_linear().into(),
+                text: term_window
+                    .config
+                    .command_palette_fg_color
+                    .to_linear()
+                    .into(),
             })
             .margin(BoxDimension {
                 left: Dimension::Pixels(margin_cell_percent),
@@ -215,62 +693,90 @@ impl EricWindow{
     }
 }
 
-impl Modal for EricWindow{
-    fn mouse_event(&self, event: MouseEvent, term_window: &mut TermWindow) -> anyhow::Result<()> {
-        Ok(())
+impl Modal for EricWindow {
+    fn mouse_event(&self, event: MouseEvent, term_window: &mut TermWindow) -> anyhow::Result<bool> {
+        match (event.kind, event.button) {
+            (MouseEventKind::Press, MouseButton::WheelUp(amount)) => {
+                for _ in 0..amount.max(1) {
+                    self.move_up();
+                }
+            }
+            (MouseEventKind::Press, MouseButton::WheelDown(amount)) => {
+                for _ in 0..amount.max(1) {
+                    self.move_down();
+                }
+            }
+            (MouseEventKind::Press, MouseButton::Left) => {
+                // Clicks outside of the results, such as on the tab bar,
+                // are left for the window to handle
+                let idx = match self.result_at(term_window) {
+                    Some(idx) => idx,
+                    None => return Ok(false),
+                };
+                // Click to select, double click to accept
+                let is_double_click = term_window
+                    .last_mouse_click
+                    .as_ref()
+                    .map(|click| click.streak > 1)
+                    .unwrap_or(false);
+                self.sync_selection();
+                self.set_selected_row(idx);
+                if is_double_click {
+                    self.accept_selected(term_window)?;
+                }
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
     }
 
-    fn key_down(&self, key: KeyCode, mods: Modifiers, term_window: &mut TermWindow) -> anyhow::Result<bool> {
+    fn key_down(
+        &self,
+        key: KeyCode,
+        mods: Modifiers,
+        term_window: &mut TermWindow,
+    ) -> anyhow::Result<bool> {
         match (key, mods) {
             (KeyCode::Escape, KeyModifiers::NONE) | (KeyCode::Char('g'), KeyModifiers::CTRL) => {
                 term_window.cancel_modal();
             }
             (KeyCode::Enter, KeyModifiers::NONE) => {
-                let mut row = self.selected_row.borrow_mut();
-                *row = row.saturating_sub(1);
-
-                //let commands = self.commands.borrow();
-                let y = self.fuzzy_searcher.results.read().unwrap()[*row].row_index;;
-                let x = self.fuzzy_searcher.results.read().unwrap()[*row].first_y;
-
-                term_window.cancel_modal();
-
-                if let Some(pane) = term_window.get_active_pane_or_overlay() {
-                    let mut replace_current = false;
-                    if let Some(existing) = pane.downcast_ref::<crate::overlay::CopyOverlay>() {
-                        let mut params = existing.get_params();
-                        params.editing_search = false;
-                        existing.apply_params(params);
-                        replace_current = true;
-                    } else {
-                        let copy = crate::overlay::CopyOverlay::with_pane(
-                            term_window,
-                            &pane,
-                            crate::overlay::CopyModeParams {
-                                pattern: CaseInSensitiveString("".to_string()),
-                                editing_search: false,
-                            },
-                        )?;
-                        let actualCopy = copy.downcast_ref::<crate::overlay::CopyOverlay>();
-                        actualCopy.unwrap().select_cell(x, y);
-
-                        term_window.assign_overlay_for_pane(copy.pane_id(), copy);
-                    }
-                    term_window.pane_state(pane.pane_id())
-                        .overlay
-                        .as_mut()
-                        .map(|overlay| {
-                            overlay.key_table_state.activate(crate::termwindow::keyevent::KeyTableArgs {
-                                name: "copy_mode",
-                                timeout_milliseconds: None,
-                                replace_current,
-                                one_shot: false,
-                                until_unknown: false,
-                                prevent_fallback: false,
-                            });
-                        });
+                self.accept_selected(term_window)?;
+            }
+            (KeyCode::Enter, KeyModifiers::CTRL) => {
+                if *self.filter.borrow() == FuzzyScrollbackFilter::Input {
+                    self.accept(term_window, SelectedAction::Paste { run: true });
                 }
-
+            }
+            (KeyCode::Char('r'), KeyModifiers::CTRL) => {
+                self.toggle_filter();
+                self.start_fuzzy_search(term_window);
+            }
+            (KeyCode::Tab, KeyModifiers::NONE) => {
+                self.toggle_mark();
+            }
+            (KeyCode::Char('y'), KeyModifiers::CTRL) => {
+                self.copy_marked(term_window);
+            }
+            (KeyCode::PageUp, KeyModifiers::NONE) => {
+                self.page_up();
+            }
+            (KeyCode::PageDown, KeyModifiers::NONE) => {
+                self.page_down();
+            }
+            (KeyCode::Char('w'), KeyModifiers::CTRL) => {
+                self.delete_word();
+                self.updated_input();
+                self.start_fuzzy_search(term_window);
+            }
+            (KeyCode::Char('u'), KeyModifiers::CTRL) => {
+                self.selection.borrow_mut().clear();
+                self.updated_input();
+                self.start_fuzzy_search(term_window);
+            }
+            (KeyCode::Char('t'), KeyModifiers::CTRL) => {
+                let mut show_preview = self.show_preview.borrow_mut();
+                *show_preview = !*show_preview;
             }
             (KeyCode::UpArrow, KeyModifiers::NONE) | (KeyCode::Char('p'), KeyModifiers::CTRL) => {
                 self.move_up();
@@ -299,14 +805,42 @@ impl Modal for EricWindow{
         Ok(true)
     }
 
-    fn computed_element(&self, term_window: &mut TermWindow) -> anyhow::Result<Ref<[ComputedElement]>> {
+    fn computed_element(
+        &self,
+        term_window: &mut TermWindow,
+    ) -> anyhow::Result<Ref<[ComputedElement]>> {
         let panes = term_window.get_panes_to_render();
         let mut cloned_pane = panes[0].clone();
+        // The preview is painted at an absolute position, not relative
+        // to where the pane sits in its tab
+        cloned_pane.left = 0;
+        cloned_pane.top = 0;
 
-        let font = term_window
-            .fonts
-            .default_font()
-            .expect("to resolve font");
+        self.sync_selection();
+        let mut top_row = self.top_row.borrow_mut();
+        let a = self.fuzzy_searcher.results.read().unwrap();
+        if let Some(selected) = a.get(*self.selected_row.borrow()) {
+            *top_row = selected.row_index;
+            cloned_pane.pane = Arc::clone(&self.fuzzy_searcher.sources[selected.source].pane);
+        }
+
+        // The logical line holding the selected result; it is shown
+        // along with its surrounding context in the preview region
+        let selected_line = a.get(*self.selected_row.borrow()).and_then(|selected| {
+            cloned_pane
+                .pane
+                .get_logical_lines(selected.row_index..selected.row_index + 1)
+                .into_iter()
+                .next()
+                .map(|line| {
+                    let columns =
+                        match_columns(&line.logical, selected.offset, &selected.positions);
+                    (line, columns)
+                })
+        });
+        let show_preview = *self.show_preview.borrow() && selected_line.is_some();
+
+        let font = term_window.fonts.default_font().expect("to resolve font");
 
         let dimensions = term_window.dimensions;
         let size = term_window.terminal_size;
@@ -315,7 +849,8 @@ impl Modal for EricWindow{
             size.cols as f32 * term_window.render_metrics.cell_size.width as f32;
 
         let proposed_window_to_modal_padding_percent = 0.15;
-        let proposed_window_to_modal_padding_pixels = dimensions.pixel_width as f32 * proposed_window_to_modal_padding_percent;
+        let proposed_window_to_modal_padding_pixels =
+            dimensions.pixel_width as f32 * proposed_window_to_modal_padding_percent;
 
         let (padding_left, padding_top) = term_window.padding_left_top();
         let padding_width_percent = 0.15;
@@ -325,61 +860,105 @@ impl Modal for EricWindow{
             desired_width as f32 * term_window.render_metrics.cell_size.width as f32;
 
         let panel_margin_percent = 0.50;
-        let panel_margin_pixels = term_window.render_metrics.cell_size.width as f32 * panel_margin_percent;
+        let panel_margin_pixels =
+            term_window.render_metrics.cell_size.width as f32 * panel_margin_percent;
         let panel_padding_percent = 0.50;
-        let panel_padding_pixels = term_window.render_metrics.cell_size.width as f32 * panel_padding_percent;
+        let panel_padding_pixels =
+            term_window.render_metrics.cell_size.width as f32 * panel_padding_percent;
         let panel_border_pixels = 2.0;
-        let prompt_element_height = font.metrics().cell_height.0 as f32 + panel_margin_pixels + panel_padding_pixels + panel_border_pixels;
+        let prompt_element_height = font.metrics().cell_height.0 as f32
+            + panel_margin_pixels
+            + panel_padding_pixels
+            + panel_border_pixels;
         let panel_decoration_pixels = (panel_margin_pixels + panel_padding_pixels) * 2.0;
 
-        let proposed_content_width_pixels = dimensions.pixel_width as f32 - proposed_window_to_modal_padding_pixels - panel_decoration_pixels;
-        let proposed_full_height = dimensions.pixel_height as f32 - proposed_window_to_modal_padding_pixels - panel_decoration_pixels;
-        let proposed_half_height = ((proposed_full_height - prompt_element_height - panel_decoration_pixels) / 2.0).floor();
-        let content_width_cells = (proposed_content_width_pixels / term_window.render_metrics.cell_size.width as f32).floor();
-        let content_width_pixels = content_width_cells * term_window.render_metrics.cell_size.width as f32;
-        let content_height_cells = (proposed_half_height / term_window.render_metrics.cell_size.height as f32).floor();
-        let content_height_pixels = content_height_cells * term_window.render_metrics.cell_size.height as f32;
+        let proposed_content_width_pixels = dimensions.pixel_width as f32
+            - proposed_window_to_modal_padding_pixels
+            - panel_decoration_pixels;
+        let proposed_full_height = dimensions.pixel_height as f32
+            - proposed_window_to_modal_padding_pixels
+            - panel_decoration_pixels;
+        let proposed_half_height =
+            ((proposed_full_height - prompt_element_height - panel_decoration_pixels) / 2.0)
+                .floor();
+        let content_width_cells = (proposed_content_width_pixels
+            / term_window.render_metrics.cell_size.width as f32)
+            .floor();
+        let content_width_pixels =
+            content_width_cells * term_window.render_metrics.cell_size.width as f32;
+        let content_height_cells =
+            (proposed_half_height / term_window.render_metrics.cell_size.height as f32).floor();
+        let content_height_pixels =
+            content_height_cells * term_window.render_metrics.cell_size.height as f32;
+
+        let cell_height = term_window.render_metrics.cell_size.height as f32;
+        let context_lines = term_window.config.fuzzy_scrollback_preview_context_lines;
+        let preview_height_pixels = match &selected_line {
+            Some((line, _)) if show_preview => {
+                let rows = context_lines * 2 + line.physical_lines.len();
+                (rows as f32 * cell_height).min(content_height_pixels)
+            }
+            _ => 0.,
+        };
+        let preview_panel_height = if show_preview {
+            preview_height_pixels + panel_decoration_pixels
+        } else {
+            0.
+        };
+        let results_height_cells = ((proposed_full_height
+            - prompt_element_height
+            - panel_decoration_pixels
+            - preview_panel_height)
+            / cell_height)
+            .floor()
+            .max(1.);
 
         let real_panel_width = content_width_pixels + panel_decoration_pixels;
-        let real_panel_height = content_height_pixels + panel_decoration_pixels;
-        let real_modal_to_window_width_padding = (dimensions.pixel_width as f32 - real_panel_width) / 2.0;
+        let results_panel_height = results_height_cells * cell_height + panel_decoration_pixels;
+        let real_modal_to_window_width_padding =
+            (dimensions.pixel_width as f32 - real_panel_width) / 2.0;
 
         let x_adjust = real_modal_to_window_width_padding;
-        let x_adjust_content = x_adjust + (panel_margin_pixels + panel_padding_pixels + panel_border_pixels + panel_border_pixels);
+        let x_adjust_content = x_adjust
+            + (panel_margin_pixels
+                + panel_padding_pixels
+                + panel_border_pixels
+                + panel_border_pixels);
         let background_color = cloned_pane.pane.palette().background.to_linear();
 
         let selection = self.selection.borrow();
         let selection = selection.as_str();
-        let font = term_window
-            .fonts
-            .default_font()
-            .expect("to resolve font");
-
-        let prompt_elements =
-            vec![
-                Element::new(&font, ElementContent::Text(format!("> {selection}_")))
-                    .colors(ElementColors {
-                        border: BorderColor::default(),
-                        bg: LinearRgba::TRANSPARENT.into(),
-                        text: term_window
-                            .config
-                            .command_palette_fg_color
-                            .to_linear()
-                            .into(),
-                    })
-                    .display(DisplayType::Block),
-            ];
+        let font = term_window.fonts.default_font().expect("to resolve font");
+
+        let prompt_elements = vec![Element::new(
+            &font,
+            ElementContent::Text(format!("{}{selection}_", self.prompt())),
+        )
+        .colors(ElementColors {
+            border: BorderColor::default(),
+            bg: LinearRgba::TRANSPARENT.into(),
+            text: term_window
+                .config
+                .command_palette_fg_color
+                .to_linear()
+                .into(),
+        })
+        .display(DisplayType::Block)];
         let prompt_element = self.create_panel_element(
             term_window,
             real_panel_width,
             1.0,
             background_color,
             BorderColor::new(
-                term_window.config.command_palette_fg_color.to_linear().into(),
+                term_window
+                    .config
+                    .command_palette_fg_color
+                    .to_linear()
+                    .into(),
             ),
             ElementContent::Children(prompt_elements),
             panel_margin_pixels,
-            panel_padding_pixels
+            panel_padding_pixels,
         );
 
         let top_bar_height = if term_window.show_tab_bar && !term_window.config.tab_bar_at_bottom {
@@ -388,31 +967,29 @@ impl Modal for EricWindow{
             0.
         };
 
-        let padding_height_percent = 0.10;
-        let padding_height_pixels = (dimensions.pixel_height as f32 - top_bar_height) * padding_height_percent;
-        let full_height = (dimensions.pixel_height as f32) - (padding_height_pixels * 2.0) - top_bar_height;
-        let half_height = ((full_height - prompt_element_height - (panel_padding_pixels * 2.0)  - (panel_margin_percent * 2.0)) / 2.0).floor();
-
         //let metrics = RenderMetrics::with_font_metrics(&font.metrics());
         let metrics = term_window.render_metrics;
-        let max_rows_on_screen = (half_height / (metrics.cell_size.height as f32 )) as usize;
+        let max_rows_on_screen = results_height_cells as usize;
         *self.max_rows_on_screen.borrow_mut() = max_rows_on_screen;
         let size = term_window.terminal_size;
 
         let border = term_window.get_os_border();
         let top_pixel_y = padding_top + top_bar_height + real_modal_to_window_width_padding;
-        let top_pixel_y_content = top_pixel_y + (panel_margin_pixels + panel_padding_pixels + panel_border_pixels + panel_border_pixels);
 
-        let mut result_elements = vec![ ];
+        let mut result_elements = vec![];
+        // The result index for each entry in result_elements
+        let mut result_owners = vec![];
+        let marked = self.marked.borrow();
 
-        let mut top_row = self.top_row.borrow_mut();
-        let a = self.fuzzy_searcher.results.read().unwrap();
-        if(a.iter().count() > 0)
-        {
-            *top_row = a[*self.selected_row.borrow()].row_index;
-        }
+        // Scroll the result list so that the selection remains visible
+        let first_visible = (*self.selected_row.borrow() + 1).saturating_sub(max_rows_on_screen);
 
-        for (display_idx, mut c) in a.iter().take(max_rows_on_screen).enumerate() {
+        for (display_idx, mut c) in a
+            .iter()
+            .enumerate()
+            .skip(first_visible)
+            .take(max_rows_on_screen)
+        {
             let mut command = &mut c;
             let solid_bg_color: InheritableColor = term_window
                 .config
@@ -439,31 +1016,71 @@ impl Modal for EricWindow{
             };
 
             let mut attr = CellAttributes::default();
-            if(display_idx == selected_row)
-            {
+            if display_idx == selected_row {
                 attr.set_foreground(TrueColor(*term_window.config.command_palette_bg_color));
-            }
-            else {
+            } else {
                 attr.set_foreground(TrueColor(*term_window.config.command_palette_fg_color));
             }
 
-            let logical_rows = &cloned_pane.pane.get_logical_lines(command.row_index..command.row_index + 1);
+            let source = &self.fuzzy_searcher.sources[command.source];
+            let logical_rows = &source
+                .pane
+                .get_logical_lines(command.row_index..command.row_index + 1);
             if let Some(logical_row) = logical_rows.first() {
-                for line in &logical_row.physical_lines {
+                // The positions are chars in the matched text; find the
+                // physical row and column of each of them
+                let mut highlights: HashMap<StableRowIndex, Vec<usize>> = HashMap::new();
+                for x in match_columns(&logical_row.logical, command.offset, &command.positions) {
+                    let (y, x) = logical_row.logical_x_to_physical_coord(x);
+                    highlights.entry(y).or_default().push(x);
+                }
 
+                for (physical_idx, line) in logical_row.physical_lines.iter().enumerate() {
                     let label_str = line.as_str();
                     let mut line = Line::from_text(&label_str, &attr, 0, None);
 
-                    for p in c.positions.iter() {
-                        if let Some(c) = line.cells_mut_for_attr_changes_only().get_mut(*p as usize) {
+                    let y = logical_row.first_row + physical_idx as StableRowIndex;
+                    for x in highlights.get(&y).into_iter().flatten() {
+                        if let Some(c) = line.cells_mut_for_attr_changes_only().get_mut(*x) {
                             c.attrs_mut().set_foreground(color::AnsiColor::Red);
                         }
                     }
 
-                    let row = vec![
-                        Element::with_line(&font, &line, &term_window.palette().clone()),
-                    ];
+                    let mut row = vec![];
+                    if !marked.is_empty() {
+                        let is_marked = physical_idx == 0
+                            && marked.contains(&(command.source, command.row_index));
+                        let marker = if is_marked { "\u{25cf} " } else { "  " };
+                        row.push(
+                            Element::new(&font, ElementContent::Text(marker.to_string())).colors(
+                                ElementColors {
+                                    border: BorderColor::default(),
+                                    bg: bg.clone(),
+                                    text: text.clone(),
+                                },
+                            ),
+                        );
+                    }
+                    if self.scope != FuzzyScrollbackScope::Pane && physical_idx == 0 {
+                        row.push(
+                            Element::new(
+                                &font,
+                                ElementContent::Text(format!("[{}] ", source.label)),
+                            )
+                            .colors(ElementColors {
+                                border: BorderColor::default(),
+                                bg: label_bg.clone(),
+                                text: label_text.clone(),
+                            }),
+                        );
+                    }
+                    row.push(Element::with_line(
+                        &font,
+                        &line,
+                        &term_window.palette().clone(),
+                    ));
 
+                    result_owners.push(display_idx);
                     result_elements.push(
                         Element::new(&font, ElementContent::Children(row))
                             .colors(ElementColors {
@@ -487,35 +1104,48 @@ impl Modal for EricWindow{
                             .display(DisplayType::Block),
                     );
                 }
-            } else {
             }
         }
 
         let results_element = self.create_panel_element(
             term_window,
             real_panel_width,
-            real_panel_height,
+            results_panel_height,
             background_color,
             BorderColor::new(
-                    term_window.config.command_palette_fg_color.to_linear().into(),
-                ),
+                term_window
+                    .config
+                    .command_palette_fg_color
+                    .to_linear()
+                    .into(),
+            ),
             ElementContent::Children(result_elements),
             panel_margin_pixels,
-            panel_padding_pixels);
+            panel_padding_pixels,
+        );
 
         let preview_border_element = self.create_panel_element(
             term_window,
             real_panel_width,
-            real_panel_height,
+            preview_height_pixels + panel_decoration_pixels,
             background_color,
             BorderColor::new(
-                    term_window.config.command_palette_fg_color.to_linear().into(),
-                ),
+                term_window
+                    .config
+                    .command_palette_fg_color
+                    .to_linear()
+                    .into(),
+            ),
             ElementContent::Children(vec![]),
             panel_margin_pixels,
-            panel_padding_pixels);
+            panel_padding_pixels,
+        );
 
-        let combined = vec![preview_border_element, results_element, prompt_element];
+        let combined = if show_preview {
+            vec![results_element, preview_border_element, prompt_element]
+        } else {
+            vec![results_element, prompt_element]
+        };
         let element = self.create_panel_element(
             term_window,
             real_panel_width,
@@ -523,8 +1153,9 @@ impl Modal for EricWindow{
             background_color,
             BorderColor::default(),
             ElementContent::Children(combined),
-        0.00,
-        0.00);
+            0.00,
+            0.00,
+        );
 
         let computed = term_window.compute_element(
             &LayoutContext {
@@ -547,26 +1178,57 @@ impl Modal for EricWindow{
                 metrics: &metrics,
                 gl_state: term_window.render_state.as_ref().unwrap(),
                 zindex: 100,
-            }, &element)?;
+            },
+            &element,
+        )?;
 
-        let rt = vec!(computed);
-        self.element.borrow_mut().replace(rt);
+        let mut result_rects = vec![];
+        if let ComputedElementContent::Children(panels) = &computed.content {
+            if let Some(ComputedElementContent::Children(rows)) =
+                panels.first().map(|panel| &panel.content)
+            {
+                for (idx, row) in result_owners.iter().zip(rows.iter()) {
+                    result_rects.push((*idx, row.bounds));
+                }
+            }
+        }
+        *self.result_rects.borrow_mut() = result_rects;
 
-        let gl_state = term_window.render_state.as_ref().unwrap();
-        let layer = gl_state
-            .layer_for_zindex(101)?;
-        let mut layers = layer.quad_allocator();
+        let preview_rect = match &computed.content {
+            ComputedElementContent::Children(kids) if show_preview => {
+                kids.get(1).map(|kid| kid.content_rect)
+            }
+            _ => None,
+        };
 
-        //cloned_pane.left = cloned_pane.left;
+        let rt = vec![computed];
+        self.element.borrow_mut().replace(rt);
 
-        term_window.paint_pane2(
-            &cloned_pane,
-            &mut layers,
-            x_adjust_content,
-            top_pixel_y_content,
-            content_width_pixels,
-            content_height_pixels,
-            *top_row)?;
+        if let (Some(rect), Some((line, columns))) = (preview_rect, &selected_line) {
+            let mut highlights: HashMap<StableRowIndex, Vec<usize>> = HashMap::new();
+            for x in columns {
+                let (y, x) = line.logical_x_to_physical_coord(*x);
+                highlights.entry(y).or_default().push(x);
+            }
+            let selected_rows =
+                line.first_row..line.first_row + line.physical_lines.len() as StableRowIndex;
+
+            let gl_state = term_window.render_state.as_ref().unwrap();
+            let layer = gl_state.layer_for_zindex(101)?;
+            let mut layers = layer.quad_allocator();
+
+            term_window.paint_pane2(
+                &cloned_pane,
+                &mut layers,
+                rect.min_x(),
+                rect.min_y(),
+                content_width_pixels,
+                preview_height_pixels,
+                selected_rows,
+                context_lines,
+                &highlights,
+            )?;
+        }
 
         Ok(Ref::map(self.element.borrow(), |v| {
             v.as_ref().unwrap().as_slice()
@@ -578,147 +1240,643 @@ impl Modal for EricWindow{
     }
 }
 
+/// Controls how many results are retained and how they are ranked
+#[derive(Clone, Copy, Debug)]
+pub struct SearchOptions {
+    max_results: usize,
+    case_matching: CaseMatching,
+    sort: FuzzyScrollbackSort,
+}
+
+impl SearchOptions {
+    /// Resolves the options from `args`, falling back to the config
+    /// for those that were not specified
+    fn new(config: &ConfigHandle, args: &FuzzyScrollbackSearchArguments) -> Self {
+        let case_matching = match args
+            .case_matching
+            .unwrap_or(config.fuzzy_scrollback_case_matching)
+        {
+            FuzzyCaseMatching::Smart => CaseMatching::Smart,
+            FuzzyCaseMatching::Ignore => CaseMatching::Ignore,
+            FuzzyCaseMatching::Respect => CaseMatching::Respect,
+        };
+        Self {
+            max_results: args
+                .max_results
+                .unwrap_or(config.fuzzy_scrollback_max_results),
+            case_matching,
+            sort: args.sort.unwrap_or(config.fuzzy_scrollback_sort),
+        }
+    }
+
+    /// Compares two results in display order.  Results are keyed by
+    /// `(source, row_index)`; a larger key is more recent.  When picking
+    /// from the command history, equally scored commands are always
+    /// listed most recent first.
+    fn compare(
+        &self,
+        filter: FuzzyScrollbackFilter,
+        a: (i32, usize, StableRowIndex),
+        b: (i32, usize, StableRowIndex),
+    ) -> std::cmp::Ordering {
+        let (a_score, a_key) = (a.0, (a.1, a.2));
+        let (b_score, b_key) = (b.0, (b.1, b.2));
+        match (self.sort, filter) {
+            (FuzzyScrollbackSort::Score, FuzzyScrollbackFilter::All) => {
+                b_score.cmp(&a_score).then(a_key.cmp(&b_key))
+            }
+            (FuzzyScrollbackSort::Score, FuzzyScrollbackFilter::Input)
+            | (FuzzyScrollbackSort::ScoreThenRecency, _) => {
+                b_score.cmp(&a_score).then(b_key.cmp(&a_key))
+            }
+            (FuzzyScrollbackSort::Recency, _) => b_key.cmp(&a_key),
+        }
+    }
+}
+
 struct SearchTask {
     selection: String,
-    pane: Arc<dyn Pane>,
-    //term_window: Arc<TermWindow>, // Change Rc to Arc
+    filter: FuzzyScrollbackFilter,
+    seq: usize,
+}
+
+/// A portion of a captured line that is eligible to be matched
+struct Candidate {
+    row_index: StableRowIndex,
+    /// The logical column at which `text` starts
+    offset: usize,
+    text: String,
+    /// Whether `text` matched `Captured::matched_query`
+    matched: bool,
+}
+
+/// What has been captured from a source so far
+#[derive(Default)]
+struct CapturedSource {
+    lines: Vec<LogicalLine>,
+    /// The `SemanticType::Input` zones, in the order they appear
+    input_zones: Vec<SemanticZone>,
+    /// The candidates extracted from `lines` with `Captured::filter`,
+    /// in the order they appear
+    candidates: Vec<Candidate>,
+}
+
+impl CapturedSource {
+    fn capture(pane: &Arc<dyn Pane>) -> Self {
+        let dims = pane.get_dimensions();
+        let top = dims.scrollback_top;
+        Self {
+            lines: pane.get_logical_lines(top..top + dims.scrollback_rows as StableRowIndex),
+            input_zones: input_zones(pane),
+            candidates: vec![],
+        }
+    }
+
+    fn extract_candidates(
+        &self,
+        lines: &[LogicalLine],
+        filter: FuzzyScrollbackFilter,
+    ) -> Vec<Candidate> {
+        let mut result = vec![];
+        for line in lines {
+            let text = line.logical.as_str();
+            for (offset, text) in candidates(&text, line, filter, &self.input_zones) {
+                result.push(Candidate {
+                    row_index: line.first_row,
+                    offset,
+                    text: text.into_owned(),
+                    matched: false,
+                });
+            }
+        }
+        result
+    }
+
+    fn find_line(&self, row_index: StableRowIndex) -> Option<&LogicalLine> {
+        self.lines
+            .binary_search_by_key(&row_index, |line| line.first_row)
+            .ok()
+            .map(|idx| &self.lines[idx])
+    }
+}
+
+/// Everything that has been captured from the sources.  The candidates
+/// are extracted lazily, by the first search, and again whenever the
+/// filter changes.  Each search records which candidates matched, so
+/// that a following search for a query that refines it only needs to
+/// re-score those.
+#[derive(Default)]
+struct Captured {
+    /// Indexed the same as `FuzzySearcher::sources`
+    sources: Vec<CapturedSource>,
+    /// The filter that the candidates were extracted with, if they
+    /// have been extracted
+    filter: Option<FuzzyScrollbackFilter>,
+    /// The query that the `matched` flags of the candidates reflect
+    matched_query: Option<String>,
+}
+
+impl Captured {
+    fn set_filter(&mut self, filter: FuzzyScrollbackFilter) {
+        if self.filter == Some(filter) {
+            return;
+        }
+        for source in &mut self.sources {
+            source.candidates = source.extract_candidates(&source.lines, filter);
+        }
+        self.filter = Some(filter);
+        self.matched_query = None;
+    }
+}
+
+fn input_zones(pane: &Arc<dyn Pane>) -> Vec<SemanticZone> {
+    pane.get_semantic_zones()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|zone| zone.semantic_type == SemanticType::Input)
+        .collect()
+}
+
+/// Returns the portions of `line` that are eligible to be matched by
+/// `filter`, each with the logical column at which it starts.
+/// `text` is the text of `line`.
+fn candidates<'a>(
+    text: &'a str,
+    line: &LogicalLine,
+    filter: FuzzyScrollbackFilter,
+    input_zones: &[SemanticZone],
+) -> Vec<(usize, Cow<'a, str>)> {
+    match filter {
+        FuzzyScrollbackFilter::All => vec![(0, Cow::Borrowed(text))],
+        FuzzyScrollbackFilter::Input => {
+            let last_row = line.first_row + line.physical_lines.len() as StableRowIndex - 1;
+            let first_zone = input_zones.partition_point(|zone| zone.end_y < line.first_row);
+
+            let mut result = vec![];
+            for zone in input_zones[first_zone..]
+                .iter()
+                .take_while(|zone| zone.start_y <= last_row)
+            {
+                let start = if zone.start_y >= line.first_row {
+                    line.xy_to_logical_x(zone.start_x, zone.start_y)
+                } else {
+                    0
+                };
+                let end = if zone.end_y <= last_row {
+                    line.xy_to_logical_x(zone.end_x, zone.end_y) + 1
+                } else {
+                    usize::MAX
+                };
+
+                let input = line.logical.columns_as_str(start..end);
+                let leading = input.chars().take_while(|c| c.is_whitespace()).count();
+                let input = input.trim();
+                if !input.is_empty() {
+                    result.push((start + leading, Cow::Owned(input.to_string())));
+                }
+            }
+            result
+        }
+    }
+}
+
+/// Converts `positions`, which index the chars of the matched text
+/// that starts at logical column `offset` of `line` and are offset by
+/// `offset`, into the logical columns that hold those chars.
+fn match_columns(line: &Line, offset: usize, positions: &[usize]) -> Vec<usize> {
+    let mut columns = Vec::with_capacity(positions.len());
+    let mut wanted = positions.iter().map(|p| p - offset).peekable();
+    let mut chars = 0;
+    for cell in line
+        .visible_cells()
+        .skip_while(|cell| cell.cell_index() < offset)
+    {
+        if wanted.peek().is_none() {
+            break;
+        }
+        chars += cell.str().chars().count();
+        while wanted.next_if(|p| *p < chars).is_some() {
+            columns.push(cell.cell_index());
+        }
+    }
+    columns
 }
 
 pub struct FuzzySearcher {
     results: Arc<std::sync::RwLock<Vec<EricRow>>>,
-    cancel_flag: Arc<AtomicBool>,
-    task_sender: Arc<Mutex<Sender<SearchTask>>>,
-    lines: Vec<LogicalLine>,
-    task_thread: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
+    /// Bumped each time `results` is replaced or updated
+    results_generation: AtomicUsize,
+    /// Feeds queries to the search thread
+    task_sender: Mutex<Sender<SearchTask>>,
+    sources: Arc<Vec<ScrollbackSource>>,
+    options: SearchOptions,
+    /// What has been captured from the sources.  Searching and merging
+    /// new output both hold this lock until they have published their
+    /// results, which keeps them from interleaving.
+    captured: Mutex<Captured>,
+    /// The current query and filter; `query_seq` is bumped while this
+    /// is held
+    query: Mutex<(String, FuzzyScrollbackFilter)>,
+    query_seq: AtomicUsize,
+    refresh_dirty: Mutex<HashSet<usize>>,
+    refresh_running: AtomicBool,
+}
+
+impl EricRow {
+    fn from_match(
+        source: usize,
+        row_index: StableRowIndex,
+        offset: usize,
+        text: &str,
+        result: MatchResult,
+    ) -> Self {
+        let positions: Vec<usize> = result.positions.iter().map(|p| p + offset).collect();
+        Self {
+            source,
+            row_index,
+            first_y: positions.first().copied().unwrap_or(offset),
+            positions,
+            score: result.score,
+            text: text.to_string(),
+            offset,
+        }
+    }
+}
+
+/// Orders results according to `options` and keeps the best of them.
+/// When picking from the command history only the most recent instance
+/// of a repeated command is kept, and equally ranked commands are listed
+/// most recent first.
+fn rank_results(
+    results: &mut Vec<EricRow>,
+    filter: FuzzyScrollbackFilter,
+    options: &SearchOptions,
+) {
+    if filter == FuzzyScrollbackFilter::Input {
+        results.sort_by(|a, b| (b.source, b.row_index).cmp(&(a.source, a.row_index)));
+        let mut seen = HashSet::new();
+        results.retain(|r| seen.insert(r.text.clone()));
+    }
+    results.sort_by(|a, b| {
+        options.compare(
+            filter,
+            (a.score, a.source, a.row_index),
+            (b.score, b.source, b.row_index),
+        )
+    });
+    results.truncate(options.max_results);
 }
 
 impl FuzzySearcher {
-    pub fn new(lines: Vec<LogicalLine>) -> Arc<Self> {
+    pub fn new(sources: Vec<ScrollbackSource>, options: SearchOptions) -> Arc<Self> {
         let (task_sender, task_receiver) = mpsc::channel();
 
-        let mut searcher = Arc::new(FuzzySearcher {
+        let captured = Captured {
+            sources: sources
+                .iter()
+                .map(|source| CapturedSource::capture(&source.pane))
+                .collect(),
+            ..Captured::default()
+        };
+
+        let searcher = Arc::new(FuzzySearcher {
             results: Arc::new(std::sync::RwLock::new(Vec::new())),
-            cancel_flag: Arc::new(AtomicBool::new(false)),
-            task_sender: Arc::new(Mutex::new(task_sender)),
-            lines,
-            task_thread: Arc::new(Mutex::new(None))
+            results_generation: AtomicUsize::new(0),
+            task_sender: Mutex::new(task_sender),
+            sources: Arc::new(sources),
+            options,
+            captured: Mutex::new(captured),
+            query: Mutex::new((String::new(), FuzzyScrollbackFilter::default())),
+            query_seq: AtomicUsize::new(0),
+            refresh_dirty: Mutex::new(HashSet::new()),
+            refresh_running: AtomicBool::new(false),
         });
 
+        // The thread only holds a weak reference, and exits when the
+        // searcher, and with it the sending half of the channel, is dropped
+        let weak = Arc::downgrade(&searcher);
+        thread::spawn(move || Self::worker_thread(weak, task_receiver));
+
         searcher
     }
-    pub fn stop(&mut self) {
-        self.cancel_flag.store(true, Ordering::SeqCst);
-        if let Some(thread) = self.task_thread.lock().unwrap().take() {
-            let _ = thread.join();
-        }
-    }
 
-    fn worker_thread(self: Arc<Self>, task_receiver: Receiver<SearchTask>) {
-        for task in task_receiver {
-            let self_clone = Arc::clone(&self);
-            self_clone.cancel_flag.store(false, Ordering::SeqCst);
-            self_clone.perform_search(task.selection, task.pane);
+    fn worker_thread(searcher: Weak<Self>, task_receiver: Receiver<SearchTask>) {
+        while let Ok(mut task) = task_receiver.recv() {
+            // Only the most recent query is worth searching for
+            while let Ok(next) = task_receiver.try_recv() {
+                task = next;
+            }
+            let searcher = match searcher.upgrade() {
+                Some(searcher) => searcher,
+                None => return,
+            };
+            searcher.perform_search(task.selection, task.filter, task.seq);
         }
     }
 
-    fn perform_search(self: Arc<Self>, selection: String, pane: Arc<dyn Pane>) {
-        let cancel_flag_clone = Arc::clone(&self.cancel_flag);
-
-        unsafe {
-            let pn_dim = pane.get_dimensions();
-            let rows = pn_dim.scrollback_rows as StableRowIndex;
-            let _first_row = 0;
-            if !selection.is_empty() {
-                let pattern_str = std::ffi::CString::new(selection).expect("CString::new failed");
-                let slab = fzf_make_default_slab();
-                let pattern = fzf_parse_pattern(
-                    0, // Replace with actual value
-                    false,
-                    pattern_str.as_ptr() as *mut i8,
-                    true,
-                );
-
-                let mut temp = vec![];
-                for (idx, line) in self.lines.iter().enumerate() {
-                    if cancel_flag_clone.load(Ordering::SeqCst) {
-                        return;
-                    }
-                    let c_string = std::ffi::CString::new(line.logical.as_str().as_ref()).expect("CString::new failed");
-                    let ptr = c_string.as_ptr();
-                    let score = fzf_get_score(ptr, pattern, slab);
+    /// Returns true if the query has changed since `seq` was issued
+    fn is_stale(&self, seq: usize) -> bool {
+        self.query_seq.load(Ordering::SeqCst) != seq
+    }
 
-                    if score > 0 {
-                        temp.push((score, _first_row + idx as StableRowIndex, c_string));
-                    }
-                }
+    /// Applies `update` to the results, unless they belong to a query
+    /// that has since been superseded
+    fn publish_results<F: FnOnce(&mut Vec<EricRow>)>(&self, seq: usize, update: F) {
+        let mut results = self.results.write().unwrap();
+        if self.is_stale(seq) {
+            return;
+        }
+        update(&mut results);
+        self.results_generation.fetch_add(1, Ordering::SeqCst);
+    }
 
-                let mut ms = vec![];
-                temp.sort_by(|a, b| a.0.cmp(&b.0).reverse());
-                for (display_idx, mut c) in temp.iter_mut().take(100).enumerate() {
-                    //let line = c.2;
-                    //let c_string = std::ffi::CString::new(line.as_str().as_ref()).expect("CString::new failed");
-                    //let ptr = c_string.as_ptr();
-                    let pos = fzf_get_positions(c.2.as_ptr(), pattern, slab);
-                    if !pos.is_null() {
-                        let s = core::slice::from_raw_parts((*pos).data, (*pos).size);
-                        let mut posVec = vec![];
-                        for &p in s.iter() {
-                            posVec.push(p);
-                        }
-                        fzf_free_positions(pos);
-
-                        let first_y: usize = *posVec.last().unwrap_or(&0) as usize;
-                        let command = EricRow {
-                            //brief: Cow::Owned(c.2),
-                            row_index: c.1 as StableRowIndex,
-                            first_y: first_y,
-                            positions: posVec,
-                        };
-                        ms.push(command);
-                    }
-                }
+    /// Scores the candidates of each source in parallel chunks, checking
+    /// before each chunk whether the query has been superseded.  When
+    /// the query refines the last completed one, only the candidates that
+    /// matched it are scored.
+    fn perform_search(&self, selection: String, filter: FuzzyScrollbackFilter, seq: usize) {
+        if selection.is_empty() {
+            return;
+        }
 
-                fzf_free_pattern(pattern);
-                fzf_free_slab(slab);
+        let pattern = Pattern::parse(&selection, self.options.case_matching);
+
+        let mut captured = self.captured.lock().unwrap();
+        let captured = &mut *captured;
+        let refine = captured.filter == Some(filter)
+            && captured
+                .matched_query
+                .as_deref()
+                .map_or(false, |last| fuzzy::is_refinement(last, &selection));
+        captured.set_filter(filter);
+
+        let mut scored = vec![];
+        for source in &captured.sources {
+            match fuzzy::score_chunks(
+                &pattern,
+                &source.candidates,
+                |c| (!refine || c.matched).then_some(c.text.as_str()),
+                || self.is_stale(seq),
+            ) {
+                Some(matches) => scored.push(matches),
+                None => return,
+            }
+        }
 
-                if cancel_flag_clone.load(Ordering::SeqCst) {
-                    return;
-                }
+        for (source, matches) in captured.sources.iter_mut().zip(&scored) {
+            for candidate in &mut source.candidates {
+                candidate.matched = false;
+            }
+            for (idx, _score) in matches {
+                source.candidates[*idx].matched = true;
+            }
+        }
+        captured.matched_query = Some(selection);
+
+        let mut temp: Vec<(i32, usize, StableRowIndex, usize)> = vec![];
+        for (source_idx, matches) in scored.iter().enumerate() {
+            let candidates = &captured.sources[source_idx].candidates;
+            temp.extend(
+                matches
+                    .iter()
+                    .map(|&(idx, score)| (score, source_idx, candidates[idx].row_index, idx)),
+            );
+        }
 
-                let mut results = self.results.write().unwrap();
-                *results = ms;
+        let compare = |a: &(i32, usize, StableRowIndex, usize),
+                       b: &(i32, usize, StableRowIndex, usize)| {
+            self.options
+                .compare(filter, (a.0, a.1, a.2), (b.0, b.1, b.2))
+        };
+        // Leave room for repeated commands that are removed by rank_results
+        let limit = match filter {
+            FuzzyScrollbackFilter::All => self.options.max_results,
+            FuzzyScrollbackFilter::Input => temp.len(),
+        };
+        if temp.len() > limit {
+            temp.select_nth_unstable_by(limit, compare);
+            temp.truncate(limit);
+        }
+        temp.sort_by(compare);
+
+        let mut matcher = Matcher::new();
+        let mut ms = vec![];
+        for (_score, source_idx, _row_index, idx) in temp {
+            let candidate = &captured.sources[source_idx].candidates[idx];
+            if let Some(result) = matcher.fuzzy_match(&pattern, &candidate.text) {
+                ms.push(EricRow::from_match(
+                    source_idx,
+                    candidate.row_index,
+                    candidate.offset,
+                    &candidate.text,
+                    result,
+                ));
             }
         }
+        rank_results(&mut ms, filter, &self.options);
+
+        self.publish_results(seq, move |results| *results = ms);
     }
 
-    pub fn search(self: Arc<Self>, selection: &str, pane: Arc<dyn Pane>, term_window: &TermWindow) {
-        self.cancel_flag.store(true, Ordering::SeqCst);
+    /// Returns the text of the result at `row_index`: the whole logical
+    /// line, or just the command when picking from the command history
+    fn line_text(&self, source: usize, row_index: StableRowIndex) -> Option<String> {
+        let filter = self.query.lock().unwrap().1;
+        let captured = self.captured.lock().unwrap();
+        let captured = &captured.sources[source];
+        let line = captured.find_line(row_index)?;
+        let text = line.logical.as_str();
+        candidates(&text, line, filter, &captured.input_zones)
+            .into_iter()
+            .next()
+            .map(|(_, candidate)| candidate.trim_end().to_string())
+    }
+
+    pub fn search(
+        self: Arc<Self>,
+        selection: &str,
+        filter: FuzzyScrollbackFilter,
+        term_window: &TermWindow,
+    ) {
+        let seq = {
+            let mut query = self.query.lock().unwrap();
+            *query = (selection.to_string(), filter);
+            self.query_seq.fetch_add(1, Ordering::SeqCst) + 1
+        };
 
         let task = SearchTask {
             selection: selection.to_string(),
-            pane,
+            filter,
+            seq,
         };
 
         if selection.is_empty() {
-            self.results.write().unwrap().clear();
+            self.publish_results(seq, |results| results.clear());
         } else {
-            let self_clone = Arc::clone(&self);
-            thread::spawn(move || {
-                self_clone.cancel_flag.store(false, Ordering::SeqCst);
-                self_clone.perform_search(task.selection, task.pane);
-            });
+            let _ = self.task_sender.lock().unwrap().send(task);
         }
     }
-}
 
-impl Clone for FuzzySearcher {
-    fn clone(&self) -> Self {
-        FuzzySearcher {
-            results: Arc::clone(&self.results),
-            cancel_flag: Arc::clone(&self.cancel_flag),
-            task_sender: Arc::clone(&self.task_sender),
-            lines: self.lines.clone(),
-            task_thread: Arc::new(Mutex::new(None))
+    /// Returns the physical coordinate of the cell at `logical_x` in the
+    /// logical line that starts at `row_index`
+    fn physical_coord(
+        &self,
+        source: usize,
+        row_index: StableRowIndex,
+        logical_x: usize,
+    ) -> (StableRowIndex, usize) {
+        let captured = self.captured.lock().unwrap();
+        captured.sources[source]
+            .find_line(row_index)
+            .map(|line| line.logical_x_to_physical_coord(logical_x))
+            .unwrap_or((row_index, logical_x))
+    }
+
+    /// Arranges for output that arrives in any of the sources to be
+    /// searched and merged into the results for as long as the searcher
+    /// is alive.  `window` is invalidated after each merge.
+    pub fn subscribe_to_pane_output(self: &Arc<Self>, window: Option<Window>) {
+        let searcher = Arc::downgrade(self);
+        Mux::get().subscribe(move |n| {
+            let searcher = match searcher.upgrade() {
+                Some(searcher) => searcher,
+                None => return false,
+            };
+            if let MuxNotification::PaneOutput(pane_id) = n {
+                if let Some(source_idx) = searcher
+                    .sources
+                    .iter()
+                    .position(|source| source.pane.pane_id() == pane_id)
+                {
+                    searcher.schedule_refresh(source_idx, window.clone());
+                }
+            }
+            true
+        });
+    }
+
+    fn schedule_refresh(self: &Arc<Self>, source_idx: usize, window: Option<Window>) {
+        self.refresh_dirty.lock().unwrap().insert(source_idx);
+        if self.refresh_running.swap(true, Ordering::SeqCst) {
+            return;
         }
+
+        let searcher = Arc::clone(self);
+        thread::spawn(move || loop {
+            let dirty: Vec<usize> = searcher.refresh_dirty.lock().unwrap().drain().collect();
+            if dirty.is_empty() {
+                searcher.refresh_running.store(false, Ordering::SeqCst);
+                // Another notification may have arrived after we drained
+                // the set but before we cleared the running flag
+                if searcher.refresh_dirty.lock().unwrap().is_empty()
+                    || searcher.refresh_running.swap(true, Ordering::SeqCst)
+                {
+                    return;
+                }
+                continue;
+            }
+
+            for source_idx in dirty {
+                searcher.refresh_source(source_idx);
+            }
+
+            if let Some(window) = &window {
+                window.notify(TermWindowNotif::Apply(Box::new(|term_window| {
+                    term_window.invalidate_modal();
+                })));
+            }
+        });
+    }
+
+    /// Captures the lines that have been added to a source since we last
+    /// looked at it, and merges any that match the current query into the
+    /// results.  Lines that intersect the viewport may still be changing,
+    /// so they are re-read each time; lines that have been scrolled out
+    /// of the scrollback are forgotten.
+    fn refresh_source(&self, source_idx: usize) {
+        let pane = &self.sources[source_idx].pane;
+        let dims = pane.get_dimensions();
+        let end = dims.scrollback_top + dims.scrollback_rows as StableRowIndex;
+
+        let mut captured = self.captured.lock().unwrap();
+        let captured = &mut *captured;
+        let source = &mut captured.sources[source_idx];
+        let source_lines = &mut source.lines;
+
+        let keep = source_lines
+            .iter()
+            .position(|line| {
+                line.first_row + line.physical_lines.len() as StableRowIndex > dims.physical_top
+            })
+            .unwrap_or(source_lines.len());
+        let start = match source_lines.get(keep) {
+            Some(line) => line.first_row,
+            None => source_lines
+                .last()
+                .map(|line| line.first_row + line.physical_lines.len() as StableRowIndex)
+                .unwrap_or(dims.scrollback_top),
+        }
+        .max(dims.scrollback_top);
+        source_lines.truncate(keep);
+        source_lines.retain(|line| line.first_row >= dims.scrollback_top);
+
+        let new_lines = pane.get_logical_lines(start..end);
+        source.input_zones = input_zones(pane);
+
+        let (seq, (selection, filter)) = {
+            let query = self.query.lock().unwrap();
+            (self.query_seq.load(Ordering::SeqCst), query.clone())
+        };
+        // If the candidates haven't been extracted with the current
+        // filter yet, a search is pending that will pick up these lines
+        let merge = !selection.is_empty() && captured.filter == Some(filter);
+
+        let scrollback_top = dims.scrollback_top;
+        let mut new_results = vec![];
+        if let Some(captured_filter) = captured.filter {
+            source
+                .candidates
+                .retain(|c| c.row_index >= scrollback_top && c.row_index < start);
+            let mut new_candidates = source.extract_candidates(&new_lines, captured_filter);
+
+            let matched_pattern = captured
+                .matched_query
+                .as_ref()
+                .map(|query| Pattern::parse(query, self.options.case_matching));
+            let pattern = Pattern::parse(&selection, self.options.case_matching);
+            let mut matcher = Matcher::new();
+            for candidate in &mut new_candidates {
+                if let Some(matched_pattern) = &matched_pattern {
+                    candidate.matched = matcher.score(matched_pattern, &candidate.text).is_some();
+                }
+                if !merge {
+                    continue;
+                }
+                if let Some(result) = matcher.fuzzy_match(&pattern, &candidate.text) {
+                    new_results.push(EricRow::from_match(
+                        source_idx,
+                        candidate.row_index,
+                        candidate.offset,
+                        &candidate.text,
+                        result,
+                    ));
+                }
+            }
+            source.candidates.extend(new_candidates);
+        }
+        source.lines.extend(new_lines);
+
+        if !merge {
+            return;
+        }
+
+        let options = self.options;
+        self.publish_results(seq, move |results| {
+            results.retain(|r| {
+                r.source != source_idx || (r.row_index >= scrollback_top && r.row_index < start)
+            });
+            results.extend(new_results);
+            rank_results(results, filter, &options);
+        });
     }
-}
\ No newline at end of file
+}
@@ -13,8 +13,7 @@ use config::keyassignment::{
 use config::Dimension;
 use emojis::{Emoji, Group};
 use frecency::Frecency;
-use fuzzy_matcher::skim::SkimMatcherV2;
-use fuzzy_matcher::FuzzyMatcher;
+use fuzzy::{CaseMatching, Matcher, Pattern};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
@@ -272,7 +271,7 @@ fn compute_matches(selection: &str, aliases: &[Alias], group: CharSelectGroup) -
             .map(|(idx, _a)| idx)
             .collect()
     } else {
-        let matcher = SkimMatcherV2::default();
+        let pattern = Pattern::parse(selection, CaseMatching::Smart);
 
         let numeric_selection = if selection.chars().all(|c| c.is_ascii_hexdigit()) {
             // Make this uppercase so that eg: `e1` matches `U+E1` rather
@@ -284,15 +283,18 @@ fn compute_matches(selection: &str, aliases: &[Alias], group: CharSelectGroup) -
         } else {
             None
         };
+        let numeric_pattern = numeric_selection
+            .as_ref()
+            .map(|sel| Pattern::parse(sel, CaseMatching::Smart));
         let start = std::time::Instant::now();
         let all_matches: Vec<(String, MatchResult)> = aliases
             .par_iter()
             .enumerate()
-            .filter_map(|(row_idx, entry)| {
+            .map_init(Matcher::new, |matcher, (row_idx, entry)| {
                 let glyph = entry.glyph();
                 let alias_result = matcher
-                    .fuzzy_match(&entry.name, selection)
-                    .map(|score| MatchResult::new(row_idx, score, selection, aliases));
+                    .score(&pattern, &entry.name)
+                    .map(|score| MatchResult::new(row_idx, i64::from(score), selection, aliases));
                 match &numeric_selection {
                     Some(sel) => {
                         let codepoints = entry.codepoints();
@@ -305,9 +307,12 @@ fn compute_matches(selection: &str, aliases: &[Alias], group: CharSelectGroup) -
                                 },
                             ))
                         } else {
-                            let number_result = matcher
-                                .fuzzy_match(&codepoints, &sel)
-                                .map(|score| MatchResult::new(row_idx, score, sel, aliases));
+                            let number_result = numeric_pattern
+                                .as_ref()
+                                .and_then(|pattern| matcher.score(pattern, &codepoints))
+                                .map(|score| {
+                                    MatchResult::new(row_idx, i64::from(score), sel, aliases)
+                                });
 
                             match (alias_result, number_result) {
                                 (
@@ -328,6 +333,7 @@ fn compute_matches(selection: &str, aliases: &[Alias], group: CharSelectGroup) -
                     None => alias_result.map(|a| (glyph, a)),
                 }
             })
+            .flatten()
             .collect();
 
         let mut matches = HashMap::<String, MatchResult>::new();
@@ -597,8 +603,12 @@ impl Modal for CharSelector {
         false
     }
 
-    fn mouse_event(&self, _event: MouseEvent, _term_window: &mut TermWindow) -> anyhow::Result<()> {
-        Ok(())
+    fn mouse_event(
+        &self,
+        _event: MouseEvent,
+        _term_window: &mut TermWindow,
+    ) -> anyhow::Result<bool> {
+        Ok(false)
     }
 
     fn key_down(
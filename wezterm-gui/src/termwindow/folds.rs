@@ -0,0 +1,354 @@
+//! Folding of the output of previous commands, as delimited by
+//! the semantic zones that shell integration marks up.
+use crate::termwindow::{UIItem, UIItemType};
+use mux::pane::{Pane, PaneId};
+use mux::renderable::RenderableDimensions;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::Arc;
+use wezterm_term::{SemanticType, SemanticZone, StableRowIndex};
+use window::{MouseCursor, MouseEvent, MouseEventKind as WMEK, MousePress, WindowOps};
+
+/// The output of a command that has completed, which makes it
+/// eligible to be folded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoldableOutput {
+    /// The first row of the prompt for the command
+    pub prompt_row: StableRowIndex,
+    /// The rows of output produced by the command
+    pub rows: Range<StableRowIndex>,
+}
+
+/// Pairs the output zones in `zones` with the prompts that precede
+/// them.  The output of the last command is excluded, as it may still
+/// be running, as are outputs that occupy a single row.
+pub fn foldable_outputs(zones: &[SemanticZone]) -> Vec<FoldableOutput> {
+    let mut result = vec![];
+    let mut prompt_row = None;
+    let mut output: Option<Range<StableRowIndex>> = None;
+
+    for zone in zones {
+        match zone.semantic_type {
+            SemanticType::Prompt => {
+                if let (Some(prompt_row), Some(rows)) = (prompt_row, output.take()) {
+                    if rows.end - rows.start > 1 {
+                        result.push(FoldableOutput { prompt_row, rows });
+                    }
+                }
+                prompt_row = Some(zone.start_y);
+            }
+            SemanticType::Output if prompt_row.is_some() => {
+                let end = zone.end_y + 1;
+                output = Some(match output {
+                    Some(rows) => rows.start..end,
+                    None => zone.start_y..end,
+                });
+            }
+            SemanticType::Output | SemanticType::Input => {}
+        }
+    }
+
+    result
+}
+
+/// A row of the viewport
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayRow {
+    /// The row of the pane shown here.  For a fold, this is the
+    /// first of the rows that it hides.
+    pub row: StableRowIndex,
+    /// The number of rows hidden by the fold shown here, or 0 if
+    /// the row is shown as-is
+    pub folded: usize,
+}
+
+/// The folded outputs of a pane.  Each fold is shown as a single row
+/// in place of the rows that it hides.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputFolds {
+    /// Maps the first row of each fold to the row that follows it
+    folds: BTreeMap<StableRowIndex, StableRowIndex>,
+}
+
+impl OutputFolds {
+    pub fn is_empty(&self) -> bool {
+        self.folds.is_empty()
+    }
+
+    pub fn is_folded(&self, rows: &Range<StableRowIndex>) -> bool {
+        self.folds.contains_key(&rows.start)
+    }
+
+    pub fn fold(&mut self, rows: Range<StableRowIndex>) {
+        if rows.end - rows.start > 1 {
+            self.folds.insert(rows.start, rows.end);
+        }
+    }
+
+    pub fn toggle(&mut self, rows: Range<StableRowIndex>) {
+        if self.folds.remove(&rows.start).is_none() {
+            self.fold(rows);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.folds.clear();
+    }
+
+    /// Keeps only the folds that still correspond to one of `outputs`,
+    /// updating their extent to match.  Outputs move around when the
+    /// pane is resized and its lines are rewrapped.
+    pub fn sync_with(&mut self, outputs: &[FoldableOutput]) {
+        let mut folds = BTreeMap::new();
+        for output in outputs {
+            if self.folds.contains_key(&output.rows.start) {
+                folds.insert(output.rows.start, output.rows.end);
+            }
+        }
+        self.folds = folds;
+    }
+
+    /// Returns the fold that hides `row`
+    fn containing(&self, row: StableRowIndex) -> Option<Range<StableRowIndex>> {
+        self.folds
+            .range(..=row)
+            .next_back()
+            .filter(|(_, end)| row < **end)
+            .map(|(start, end)| *start..*end)
+    }
+
+    /// Returns the row that is `delta` rows of the viewport away from
+    /// `row`, with each fold counting as a single row
+    pub fn offset_row(&self, row: StableRowIndex, delta: isize) -> StableRowIndex {
+        let mut row = self.containing(row).map(|r| r.start).unwrap_or(row);
+        if delta > 0 {
+            for _ in 0..delta {
+                row = self.folds.get(&row).copied().unwrap_or(row + 1);
+            }
+        } else {
+            for _ in 0..delta.unsigned_abs() {
+                let prior = row - 1;
+                row = self.containing(prior).map(|r| r.start).unwrap_or(prior);
+            }
+        }
+        row
+    }
+
+    /// Returns up to `count` rows of the viewport starting at `top`,
+    /// stopping short of `end`
+    pub fn display_rows(
+        &self,
+        top: StableRowIndex,
+        count: usize,
+        end: StableRowIndex,
+    ) -> Vec<DisplayRow> {
+        let mut rows = vec![];
+        let mut row = self.containing(top).map(|r| r.start).unwrap_or(top);
+        while rows.len() < count && row < end {
+            match self.folds.get(&row) {
+                Some(fold_end) => {
+                    rows.push(DisplayRow {
+                        row,
+                        folded: (*fold_end - row) as usize,
+                    });
+                    row = *fold_end;
+                }
+                None => {
+                    rows.push(DisplayRow { row, folded: 0 });
+                    row += 1;
+                }
+            }
+        }
+        rows
+    }
+}
+
+/// Returns the runs of consecutive rows in `rows` that are not folded
+pub fn unfolded_segments(rows: &[DisplayRow]) -> Vec<Range<StableRowIndex>> {
+    let mut segments: Vec<Range<StableRowIndex>> = vec![];
+    for display in rows.iter().filter(|display| display.folded == 0) {
+        match segments.last_mut() {
+            Some(segment) if segment.end == display.row => segment.end += 1,
+            _ => segments.push(display.row..display.row + 1),
+        }
+    }
+    segments
+}
+
+impl super::TermWindow {
+    /// Returns the outputs of the pane that can be folded, and
+    /// drops any folds that no longer correspond to one of them
+    pub fn sync_output_folds(&mut self, pane: &Arc<dyn Pane>) -> Vec<FoldableOutput> {
+        let outputs = self.semantic_zone_cache(pane).outputs.clone();
+        self.pane_state(pane.pane_id())
+            .output_folds
+            .sync_with(&outputs);
+        outputs
+    }
+
+    pub fn output_folds(&self, pane_id: PaneId) -> OutputFolds {
+        self.pane_state(pane_id).output_folds.clone()
+    }
+
+    /// Returns the row shown at the top of the viewport, taking into
+    /// account the rows hidden by folds
+    pub fn viewport_top(&self, pane_id: PaneId, dims: &RenderableDimensions) -> StableRowIndex {
+        let state = self.pane_state(pane_id);
+        let end = dims.physical_top + dims.viewport_rows as StableRowIndex;
+        let bottom_top = state
+            .output_folds
+            .offset_row(end, -(dims.viewport_rows as isize))
+            .max(dims.scrollback_top);
+        match state.viewport {
+            Some(top) => top.min(bottom_top),
+            None => bottom_top,
+        }
+    }
+
+    /// Returns the row of the pane shown in row `row` of the viewport
+    pub fn viewport_row_to_stable_row(
+        &self,
+        pane_id: PaneId,
+        dims: &RenderableDimensions,
+        row: i64,
+    ) -> StableRowIndex {
+        let top = self.viewport_top(pane_id, dims);
+        let folds = self.output_folds(pane_id);
+        if folds.is_empty() {
+            top + row as StableRowIndex
+        } else {
+            folds.offset_row(top, row as isize)
+        }
+    }
+
+    /// Scrolls the viewport by `amount` rows, with each fold counting
+    /// as a single row
+    pub fn scroll_viewport_by_rows(&mut self, pane: &Arc<dyn Pane>, amount: isize) {
+        let dims = pane.get_dimensions();
+        let folds = self.output_folds(pane.pane_id());
+        if folds.is_empty() {
+            let position = self
+                .get_viewport(pane.pane_id())
+                .unwrap_or(dims.physical_top)
+                .saturating_add(amount);
+            self.set_viewport(pane.pane_id(), Some(position), dims);
+            return;
+        }
+
+        let end = dims.physical_top + dims.viewport_rows as StableRowIndex;
+        let bottom_top = folds.offset_row(end, -(dims.viewport_rows as isize));
+        let top = self.viewport_top(pane.pane_id(), &dims);
+        let position = folds.offset_row(top, amount).max(dims.scrollback_top);
+        if position >= bottom_top {
+            self.set_viewport(pane.pane_id(), None, dims);
+        } else {
+            self.set_viewport(pane.pane_id(), Some(position), dims);
+        }
+    }
+
+    fn toggle_output_fold(&mut self, pane_id: PaneId, rows: Range<StableRowIndex>) {
+        self.pane_state(pane_id).output_folds.toggle(rows);
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+    }
+
+    /// Folds the output of each of the commands that have completed
+    pub fn collapse_previous_outputs(&mut self, pane: &Arc<dyn Pane>) {
+        let outputs = self.sync_output_folds(pane);
+        {
+            let mut state = self.pane_state(pane.pane_id());
+            for output in outputs {
+                state.output_folds.fold(output.rows);
+            }
+        }
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+    }
+
+    pub fn expand_all_outputs(&mut self, pane: &Arc<dyn Pane>) {
+        self.pane_state(pane.pane_id()).output_folds.clear();
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+    }
+
+    pub fn mouse_event_output_fold(
+        &mut self,
+        item: UIItem,
+        event: MouseEvent,
+        context: &dyn WindowOps,
+    ) {
+        if let (UIItemType::OutputFold { pane_id, rows }, WMEK::Press(MousePress::Left)) =
+            (item.item_type, &event.kind)
+        {
+            self.toggle_output_fold(pane_id, rows);
+        }
+        context.set_cursor(Some(MouseCursor::Arrow));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn zone(semantic_type: SemanticType, rows: Range<StableRowIndex>) -> SemanticZone {
+        SemanticZone {
+            start_y: rows.start,
+            start_x: 0,
+            end_y: rows.end - 1,
+            end_x: 0,
+            semantic_type,
+        }
+    }
+
+    #[test]
+    fn outputs_of_completed_commands() {
+        let zones = vec![
+            zone(SemanticType::Prompt, 0..1),
+            zone(SemanticType::Input, 0..1),
+            zone(SemanticType::Output, 1..10),
+            zone(SemanticType::Prompt, 10..11),
+            zone(SemanticType::Input, 10..11),
+            zone(SemanticType::Output, 11..12),
+            zone(SemanticType::Prompt, 12..13),
+            zone(SemanticType::Input, 12..13),
+            zone(SemanticType::Output, 13..20),
+        ];
+        assert_eq!(
+            foldable_outputs(&zones),
+            vec![FoldableOutput {
+                prompt_row: 0,
+                rows: 1..10
+            }]
+        );
+    }
+
+    #[test]
+    fn display_rows_skip_folds() {
+        let mut folds = OutputFolds::default();
+        folds.fold(2..6);
+        folds.fold(8..10);
+
+        assert_eq!(
+            folds
+                .display_rows(0, 6, 100)
+                .into_iter()
+                .map(|display| (display.row, display.folded))
+                .collect::<Vec<_>>(),
+            vec![(0, 0), (1, 0), (2, 4), (6, 0), (7, 0), (8, 2)]
+        );
+        // A top row inside a fold starts at the fold
+        assert_eq!(folds.display_rows(4, 2, 100)[0].row, 2);
+        assert_eq!(
+            unfolded_segments(&folds.display_rows(0, 8, 100)),
+            vec![0..2, 6..8, 10..12]
+        );
+
+        assert_eq!(folds.offset_row(0, 3), 6);
+        assert_eq!(folds.offset_row(6, -1), 2);
+        assert_eq!(folds.offset_row(12, -4), 7);
+        assert_eq!(folds.offset_row(12, 0), 12);
+    }
+}
@@ -30,8 +30,8 @@ use ::wezterm_term::input::{ClickPosition, MouseButton as TMB};
 use ::window::*;
 use anyhow::{anyhow, ensure, Context};
 use config::keyassignment::{
-    KeyAssignment, PaneDirection, Pattern, PromptInputLine, QuickSelectArguments,
-    RotationDirection, SpawnCommand, SplitSize,
+    ExportScope, ExportTextArguments, FuzzyScrollbackSearchArguments, KeyAssignment, PaneDirection,
+    Pattern, PromptInputLine, QuickSelectArguments, RotationDirection, SpawnCommand, SplitSize,
 };
 use config::window::WindowLevel;
 use config::{
@@ -60,9 +60,10 @@ use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use termwiz::escape::osc::Progress;
 use termwiz::hyperlink::Hyperlink;
 use termwiz::surface::SequenceNo;
-use wezterm_dynamic::Value;
+use wezterm_dynamic::{FromDynamic, ToDynamic, Value};
 use wezterm_font::FontConfiguration;
 use wezterm_term::color::ColorPalette;
 use wezterm_term::input::LastMouseClick;
@@ -72,6 +73,7 @@ pub mod background;
 pub mod box_model;
 pub mod charselect;
 pub mod clipboard;
+pub mod folds;
 pub mod keyevent;
 pub mod modal;
 mod mouseevent;
@@ -161,6 +163,10 @@ pub enum UIItemType {
     ScrollThumb,
     BelowScrollThumb,
     Split(PositionedSplit),
+    OutputFold {
+        pane_id: PaneId,
+        rows: std::ops::Range<StableRowIndex>,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -185,6 +191,7 @@ impl UIItem {
 pub struct SemanticZoneCache {
     seqno: SequenceNo,
     zones: Vec<StableRowIndex>,
+    outputs: Vec<folds::FoldableOutput>,
 }
 
 pub struct OverlayState {
@@ -206,6 +213,7 @@ pub struct PaneState {
 
     bell_start: Option<Instant>,
     pub mouse_terminal_coords: Option<(ClickPosition, StableRowIndex)>,
+    pub output_folds: folds::OutputFolds,
 }
 
 /// Data used when synchronously formatting pane and window titles
@@ -271,6 +279,7 @@ pub struct PaneInformation {
     pub pixel_height: usize,
     pub title: String,
     pub user_vars: HashMap<String, String>,
+    pub progress: Progress,
 }
 
 impl UserData for PaneInformation {
@@ -288,6 +297,9 @@ impl UserData for PaneInformation {
         fields.add_field_method_get("pixel_height", |_, this| Ok(this.pixel_width));
         fields.add_field_method_get("title", |_, this| Ok(this.title.clone()));
         fields.add_field_method_get("user_vars", |_, this| Ok(this.user_vars.clone()));
+        fields.add_field_method_get("progress", |lua, this| {
+            luahelper::dynamic_to_lua_value(lua, this.progress.to_dynamic())
+        });
         fields.add_field_method_get("foreground_process_name", |_, this| {
             let mut name = None;
             if let Some(mux) = Mux::try_get() {
@@ -337,6 +349,17 @@ impl UserData for PaneInformation {
     }
 }
 
+/// Returns the progress that the pane has reported via its metadata
+fn pane_progress(pane: &Arc<dyn Pane>) -> Progress {
+    match pane.get_metadata() {
+        Value::Object(obj) => obj
+            .get(&Value::String("progress".to_string()))
+            .and_then(|value| Progress::from_dynamic(value, Default::default()).ok())
+            .unwrap_or_default(),
+        _ => Progress::None,
+    }
+}
+
 #[derive(Default)]
 pub struct TabState {
     /// If is_some(), rather than display the actual tab
@@ -1206,7 +1229,8 @@ impl TermWindow {
                         | Alert::CurrentWorkingDirectoryChanged
                         | Alert::WindowTitleChanged(_)
                         | Alert::TabTitleChanged(_)
-                        | Alert::IconTitleChanged(_),
+                        | Alert::IconTitleChanged(_)
+                        | Alert::Progress(_),
                     ..
                 } => {
                     self.update_title();
@@ -1454,6 +1478,7 @@ impl TermWindow {
                     | Alert::TabTitleChanged(_)
                     | Alert::IconTitleChanged(_)
                     | Alert::SetUserVar { .. }
+                    | Alert::Progress(_)
                     | Alert::Bell,
             }
             | MuxNotification::PaneFocused(pane_id)
@@ -2277,6 +2302,126 @@ impl TermWindow {
         promise::spawn::spawn(future).detach();
     }
 
+    pub fn open_fuzzy_scrollback(&mut self, args: &FuzzyScrollbackSearchArguments) {
+        let modal = EricWindow::EricWindow::new(self, args);
+        self.set_modal(Rc::new(modal));
+    }
+
+    /// Renders the lines chosen by `args` to a file in the requested
+    /// format, and shows a notification that can be clicked to open it
+    fn export_text(&mut self, pane: &Arc<dyn Pane>, args: &ExportTextArguments) {
+        use wezterm_toast_notification::{
+            persistent_toast_notification, persistent_toast_notification_with_click_to_open_url,
+        };
+
+        let dims = pane.get_dimensions();
+        let has_selection = self.selection(pane.pane_id()).range.is_some();
+        // The selection is resolved now, while we can see it; the lines
+        // of the viewport or scrollback are fetched along with the rest
+        // of the work, away from the gui thread
+        let (selection, range) = match args.scope {
+            ExportScope::Selection if has_selection => (Some(self.selection_lines(pane)), 0..0),
+            ExportScope::Selection | ExportScope::Viewport => {
+                let top = self
+                    .get_viewport(pane.pane_id())
+                    .unwrap_or(dims.physical_top);
+                (None, top..top + dims.viewport_rows as StableRowIndex)
+            }
+            ExportScope::Scrollback => (
+                None,
+                dims.scrollback_top..dims.physical_top + dims.viewport_rows as StableRowIndex,
+            ),
+        };
+
+        let pane_id = pane.pane_id();
+        let path = match &args.path {
+            Some(path) => config::HOME_DIR.join(path),
+            None => config::HOME_DIR.join(format!(
+                "wezterm-pane-{}-{}.{}",
+                pane_id,
+                chrono::Local::now().format("%Y%m%d-%H%M%S"),
+                args.format.extension()
+            )),
+        };
+
+        // Rendering and writing a large scrollback can take a while
+        let pane = Arc::clone(pane);
+        let palette = pane.palette();
+        let format = args.format;
+        promise::spawn::spawn(async move {
+            let result = promise::spawn::spawn_into_new_thread(move || {
+                let lines = match selection {
+                    Some(lines) => lines,
+                    None => pane.get_lines(range).1,
+                };
+                let text = termwiz_funcs::export::export_lines(lines, &palette, format)?;
+                std::fs::write(&path, text)
+                    .with_context(|| format!("writing {}", path.display()))?;
+                Ok(path)
+            })
+            .await;
+            match result {
+                Ok(path) => {
+                    log::info!("exported pane {pane_id} to {}", path.display());
+                    let path_str = path.display().to_string();
+                    match url::Url::from_file_path(&path) {
+                        Ok(url) => persistent_toast_notification_with_click_to_open_url(
+                            "Exported pane text",
+                            &path_str,
+                            url.as_str(),
+                        ),
+                        Err(()) => persistent_toast_notification("Exported pane text", &path_str),
+                    }
+                }
+                Err(err) => {
+                    log::error!("failed to export pane {pane_id}: {err:#}");
+                    persistent_toast_notification(
+                        "Failed to export pane text",
+                        &format!("{err:#}"),
+                    );
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Starts quick select in each of the visible panes in the active tab,
+    /// with labels that are unique across those panes
+    fn activate_quick_select_for_all_panes(&mut self, args: &QuickSelectArguments) {
+        let mux = Mux::get();
+        let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
+            Some(tab) => tab,
+            None => return,
+        };
+
+        // Panes that are already showing an overlay, such as copy mode,
+        // are left alone.  The active pane goes first so that it is
+        // given the shortest labels.
+        let active_pane_id = match tab.get_active_pane() {
+            Some(pane) => pane.pane_id(),
+            None => return,
+        };
+        let mut panes = vec![];
+        for pos in tab.iter_panes() {
+            if self.pane_state(pos.pane.pane_id()).overlay.is_some() {
+                continue;
+            }
+            if pos.is_active {
+                panes.insert(0, pos.pane);
+            } else {
+                panes.push(pos.pane);
+            }
+        }
+        if panes.is_empty() {
+            return;
+        }
+
+        let overlays = QuickSelectOverlay::with_panes(self, &panes, active_pane_id, args);
+        for (pane, overlay) in panes.iter().zip(overlays) {
+            self.assign_overlay_for_pane(pane.pane_id(), overlay);
+        }
+    }
+
     fn show_prompt_input_line(&mut self, args: &PromptInputLine) {
         let mux = Mux::get();
         let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
@@ -2387,7 +2532,7 @@ impl TermWindow {
     }
 
     /// Returns the Prompt semantic zones
-    fn get_semantic_prompt_zones(&mut self, pane: &Arc<dyn Pane>) -> &[StableRowIndex] {
+    fn semantic_zone_cache(&mut self, pane: &Arc<dyn Pane>) -> &SemanticZoneCache {
         let cache = self
             .semantic_zones
             .entry(pane.pane_id())
@@ -2396,6 +2541,7 @@ impl TermWindow {
         let seqno = pane.get_current_seqno();
         if cache.seqno != seqno {
             let zones = pane.get_semantic_zones().unwrap_or_else(|_| vec![]);
+            cache.outputs = folds::foldable_outputs(&zones);
             let mut zones: Vec<StableRowIndex> = zones
                 .into_iter()
                 .filter_map(|zone| {
@@ -2414,7 +2560,11 @@ impl TermWindow {
             cache.zones = zones;
             cache.seqno = seqno;
         }
-        &cache.zones
+        cache
+    }
+
+    fn get_semantic_prompt_zones(&mut self, pane: &Arc<dyn Pane>) -> &[StableRowIndex] {
+        &self.semantic_zone_cache(pane).zones
     }
 
     fn scroll_to_prompt(&mut self, amount: isize, pane: &Arc<dyn Pane>) -> anyhow::Result<()> {
@@ -2442,11 +2592,8 @@ impl TermWindow {
 
     fn scroll_by_page(&mut self, amount: f64, pane: &Arc<dyn Pane>) -> anyhow::Result<()> {
         let dims = pane.get_dimensions();
-        let position = self
-            .get_viewport(pane.pane_id())
-            .unwrap_or(dims.physical_top) as f64
-            + (amount * dims.viewport_rows as f64);
-        self.set_viewport(pane.pane_id(), Some(position as isize), dims);
+        let rows = (amount * dims.viewport_rows as f64) as isize;
+        self.scroll_viewport_by_rows(pane, rows);
         if let Some(win) = self.window.as_ref() {
             win.invalidate();
         }
@@ -2465,12 +2612,7 @@ impl TermWindow {
     }
 
     fn scroll_by_line(&mut self, amount: isize, pane: &Arc<dyn Pane>) -> anyhow::Result<()> {
-        let dims = pane.get_dimensions();
-        let position = self
-            .get_viewport(pane.pane_id())
-            .unwrap_or(dims.physical_top)
-            .saturating_add(amount);
-        self.set_viewport(pane.pane_id(), Some(position), dims);
+        self.scroll_viewport_by_rows(pane, amount);
         if let Some(win) = self.window.as_ref() {
             win.invalidate();
         }
@@ -2816,6 +2958,9 @@ impl TermWindow {
                     self.assign_overlay_for_pane(pane.pane_id(), qa);
                 }
             }
+            QuickSelectArgs(args) if args.all_panes => {
+                self.activate_quick_select_for_all_panes(args);
+            }
             QuickSelectArgs(args) => {
                 if let Some(pane) = self.get_active_pane_no_overlay() {
                     let qa = QuickSelectOverlay::with_pane(self, &pane, args);
@@ -3068,11 +3213,13 @@ impl TermWindow {
                 wezterm_open_url::open_url(link);
             }
             ActivateCommandPalette => {
-                //let modal = crate::termwindow::palette::CommandPalette::new(self);
-                //self.set_modal(Rc::new(modal));
-                let modal2 = EricWindow::EricWindow::new(self);
-                self.set_modal(Rc::new(modal2));
+                let modal = crate::termwindow::palette::CommandPalette::new(self);
+                self.set_modal(Rc::new(modal));
             }
+            FuzzyScrollbackSearch(args) => self.open_fuzzy_scrollback(args),
+            ExportText(args) => self.export_text(pane, args),
+            CollapsePreviousOutputs => self.collapse_previous_outputs(pane),
+            ExpandAllOutputs => self.expand_all_outputs(pane),
             PromptInputLine(args) => self.show_prompt_input_line(args),
             InputSelector(args) => self.show_input_selector(args),
         };
@@ -3363,6 +3510,7 @@ impl TermWindow {
             pixel_height: pos.pixel_height,
             title: pos.pane.get_title(),
             user_vars: pos.pane.copy_user_vars(),
+            progress: pane_progress(&pos.pane),
         }
     }
 
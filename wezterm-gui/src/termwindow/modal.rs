@@ -13,7 +13,9 @@ pub trait Modal: Downcast {
     ) -> bool {
         false
     }
-    fn mouse_event(&self, event: MouseEvent, term_window: &mut TermWindow) -> anyhow::Result<()>;
+    /// Returns true if the modal consumed the event, or false if it
+    /// should be processed as though the modal were not there
+    fn mouse_event(&self, event: MouseEvent, term_window: &mut TermWindow) -> anyhow::Result<bool>;
     fn key_down(
         &self,
         key: KeyCode,
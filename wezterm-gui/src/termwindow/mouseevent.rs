@@ -21,7 +21,7 @@ use termwiz::hyperlink::Hyperlink;
 use termwiz::surface::Line;
 use wezterm_dynamic::ToDynamic;
 use wezterm_term::input::{MouseButton, MouseEventKind as TMEK};
-use wezterm_term::{ClickPosition, LastMouseClick, StableRowIndex};
+use wezterm_term::{ClickPosition, LastMouseClick, PointerShape, StableRowIndex};
 
 impl super::TermWindow {
     fn resolve_ui_item(&self, event: &MouseEvent) -> Option<UIItem> {
@@ -43,7 +43,8 @@ impl super::TermWindow {
             | UIItemType::AboveScrollThumb
             | UIItemType::BelowScrollThumb
             | UIItemType::ScrollThumb
-            | UIItemType::Split(_) => {}
+            | UIItemType::Split(_)
+            | UIItemType::OutputFold { .. } => {}
         }
     }
 
@@ -54,7 +55,8 @@ impl super::TermWindow {
             | UIItemType::AboveScrollThumb
             | UIItemType::BelowScrollThumb
             | UIItemType::ScrollThumb
-            | UIItemType::Split(_) => {}
+            | UIItemType::Split(_)
+            | UIItemType::OutputFold { .. } => {}
         }
     }
 
@@ -187,6 +189,30 @@ impl super::TermWindow {
             _ => {}
         }
 
+        if let Some(modal) = self.get_modal() {
+            let mouse_event = term_mouse_event(
+                &event,
+                ClickPosition {
+                    column: x,
+                    row: y,
+                    x_pixel_offset,
+                    y_pixel_offset,
+                },
+            );
+            match modal.mouse_event(mouse_event, self) {
+                Ok(true) => {
+                    if !matches!(event.kind, WMEK::Move) {
+                        context.invalidate();
+                    }
+                    return;
+                }
+                Ok(false) => {}
+                Err(err) => {
+                    log::error!("Error dispatching mouse event to modal: {err:#}");
+                }
+            }
+        }
+
         let prior_ui_item = self.last_ui_item.clone();
 
         let ui_item = if matches!(self.current_mouse_capture, None | Some(MouseCapture::UI)) {
@@ -382,6 +408,9 @@ impl super::TermWindow {
             UIItemType::CloseTab(idx) => {
                 self.mouse_event_close_tab(idx, event, context);
             }
+            UIItemType::OutputFold { .. } => {
+                self.mouse_event_output_fold(item, event, context);
+            }
         }
     }
 
@@ -768,10 +797,7 @@ impl super::TermWindow {
         );
 
         let dims = pane.get_dimensions();
-        let stable_row = self
-            .get_viewport(pane.pane_id())
-            .unwrap_or(dims.physical_top)
-            + row as StableRowIndex;
+        let stable_row = self.viewport_row_to_stable_row(pane.pane_id(), &dims, row);
 
         self.pane_state(pane.pane_id())
             .mouse_terminal_coords
@@ -833,7 +859,22 @@ impl super::TermWindow {
             || event.coords.y < 0
             || event.coords.y as usize > self.dimensions.pixel_height;
 
-        context.set_cursor(Some(if self.current_highlight.is_some() {
+        let pointer_shape = if outside_window {
+            None
+        } else {
+            pane.get_pointer_shape()
+        };
+
+        context.set_cursor(Some(if let Some(shape) = pointer_shape {
+            // The application has asked for a specific shape
+            match shape {
+                PointerShape::Default => MouseCursor::Arrow,
+                PointerShape::Pointer => MouseCursor::Hand,
+                PointerShape::Text => MouseCursor::Text,
+                PointerShape::RowResize => MouseCursor::SizeUpDown,
+                PointerShape::ColumnResize => MouseCursor::SizeLeftRight,
+            }
+        } else if self.current_highlight.is_some() {
             // When hovering over a hyperlink, show an appropriate
             // mouse cursor to give the cue that it is clickable
             MouseCursor::Hand
@@ -988,46 +1029,15 @@ impl super::TermWindow {
             }
         }
 
-        let mouse_event = wezterm_term::MouseEvent {
-            kind: match event.kind {
-                WMEK::Move => TMEK::Move,
-                WMEK::VertWheel(_) | WMEK::HorzWheel(_) | WMEK::Press(_) => TMEK::Press,
-                WMEK::Release(_) => TMEK::Release,
-            },
-            button: match event.kind {
-                WMEK::Release(ref press) | WMEK::Press(ref press) => mouse_press_to_tmb(press),
-                WMEK::Move => {
-                    if event.mouse_buttons == WMB::LEFT {
-                        TMB::Left
-                    } else if event.mouse_buttons == WMB::RIGHT {
-                        TMB::Right
-                    } else if event.mouse_buttons == WMB::MIDDLE {
-                        TMB::Middle
-                    } else {
-                        TMB::None
-                    }
-                }
-                WMEK::VertWheel(amount) => {
-                    if amount > 0 {
-                        TMB::WheelUp(amount as usize)
-                    } else {
-                        TMB::WheelDown((-amount) as usize)
-                    }
-                }
-                WMEK::HorzWheel(amount) => {
-                    if amount > 0 {
-                        TMB::WheelLeft(amount as usize)
-                    } else {
-                        TMB::WheelRight((-amount) as usize)
-                    }
-                }
+        let mouse_event = term_mouse_event(
+            &event,
+            ClickPosition {
+                column,
+                row,
+                x_pixel_offset,
+                y_pixel_offset,
             },
-            x: column,
-            y: row,
-            x_pixel_offset,
-            y_pixel_offset,
-            modifiers: event.modifiers,
-        };
+        );
 
         if allow_action
             && !(self.config.swallow_mouse_click_on_pane_focus && is_click_to_focus_pane)
@@ -1044,6 +1054,51 @@ impl super::TermWindow {
     }
 }
 
+/// Translates a window mouse event into the equivalent terminal mouse
+/// event at the specified cell position
+fn term_mouse_event(event: &MouseEvent, position: ClickPosition) -> wezterm_term::MouseEvent {
+    wezterm_term::MouseEvent {
+        kind: match event.kind {
+            WMEK::Move => TMEK::Move,
+            WMEK::VertWheel(_) | WMEK::HorzWheel(_) | WMEK::Press(_) => TMEK::Press,
+            WMEK::Release(_) => TMEK::Release,
+        },
+        button: match event.kind {
+            WMEK::Release(ref press) | WMEK::Press(ref press) => mouse_press_to_tmb(press),
+            WMEK::Move => {
+                if event.mouse_buttons == WMB::LEFT {
+                    TMB::Left
+                } else if event.mouse_buttons == WMB::RIGHT {
+                    TMB::Right
+                } else if event.mouse_buttons == WMB::MIDDLE {
+                    TMB::Middle
+                } else {
+                    TMB::None
+                }
+            }
+            WMEK::VertWheel(amount) => {
+                if amount > 0 {
+                    TMB::WheelUp(amount as usize)
+                } else {
+                    TMB::WheelDown((-amount) as usize)
+                }
+            }
+            WMEK::HorzWheel(amount) => {
+                if amount > 0 {
+                    TMB::WheelLeft(amount as usize)
+                } else {
+                    TMB::WheelRight((-amount) as usize)
+                }
+            }
+        },
+        x: position.column,
+        y: position.row,
+        x_pixel_offset: position.x_pixel_offset,
+        y_pixel_offset: position.y_pixel_offset,
+        modifiers: event.modifiers,
+    }
+}
+
 fn mouse_press_to_tmb(press: &MousePress) -> TMB {
     match press {
         MousePress::Left => TMB::Left,
@@ -10,8 +10,7 @@ use crate::utilsprites::RenderMetrics;
 use config::keyassignment::KeyAssignment;
 use config::Dimension;
 use frecency::Frecency;
-use fuzzy_matcher::skim::SkimMatcherV2;
-use fuzzy_matcher::FuzzyMatcher;
+use fuzzy::{CaseMatching, Matcher, Pattern};
 use luahelper::{from_lua_value_dynamic, impl_lua_conversion_dynamic};
 use mux_lua::MuxPane;
 use serde::{Deserialize, Serialize};
@@ -20,18 +19,11 @@ use std::cell::{Ref, RefCell};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use mux::pane::Pane;
-use mux::pane::Pattern::CaseInSensitiveString;
-use termwiz::cell::{Cell, CellAttributes};
-use termwiz::{color, Context};
-use termwiz::color::ColorSpec::TrueColor;
 use termwiz::nerdfonts::NERD_FONTS;
-use termwiz::surface::Line;
 use wezterm_dynamic::{FromDynamic, ToDynamic};
-use wezterm_term::{KeyCode, KeyModifiers, MouseEvent, StableRowIndex};
+use wezterm_term::{KeyCode, KeyModifiers, MouseEvent};
 use window::color::LinearRgba;
 use window::{Modifiers, WindowOps};
-use crate::selection::SelectionX;
 
 struct MatchResults {
     selection: String,
@@ -179,18 +171,18 @@ fn build_commands(
 #[derive(Debug)]
 struct MatchResult {
     row_idx: usize,
-    score: i64,
+    score: i32,
 }
 
 impl MatchResult {
-    fn new(row_idx: usize, score: i64, selection: &str, commands: &[ExpandedCommand]) -> Self {
+    fn new(row_idx: usize, score: i32, selection: &str, commands: &[ExpandedCommand]) -> Self {
         Self {
             row_idx,
             score: if commands[row_idx].brief == selection {
                 // Pump up the score for an exact match, otherwise
                 // the order may be undesirable if there are a lot
                 // of candidates with the same score
-                i64::max_value()
+                i32::max_value()
             } else {
                 score
             },
@@ -202,7 +194,8 @@ fn compute_matches(selection: &str, commands: &[ExpandedCommand]) -> Vec<usize>
     if selection.is_empty() {
         commands.iter().enumerate().map(|(idx, _)| idx).collect()
     } else {
-        let matcher = SkimMatcherV2::default();
+        let pattern = Pattern::parse(selection, CaseMatching::Smart);
+        let mut matcher = Matcher::new();
 
         let start = std::time::Instant::now();
         let mut scores: Vec<MatchResult> = commands
@@ -212,7 +205,7 @@ fn compute_matches(selection: &str, commands: &[ExpandedCommand]) -> Vec<usize>
                 let group = entry.menubar.join(" ");
                 let text = format!("{group}: {}. {} {:?}", entry.brief, entry.doc, entry.action);
                 matcher
-                    .fuzzy_match(&text, selection)
+                    .score(&pattern, &text)
                     .map(|score| MatchResult::new(row_idx, score, selection, commands))
             })
             .collect();
@@ -240,36 +233,7 @@ impl CommandPalette {
             .get_active_pane_or_overlay()
             .map(|pane| MuxPane(pane.pane_id()));
 
-        let mut commands = build_commands(GuiWin::new(term_window), mux_pane, filter_copy_mode);
-
-        let pn = term_window.get_active_pane_or_overlay();
-        match pn {
-            Some(pn_value) => {
-                let matcher = SkimMatcherV2::default();
-                let (_first_row, lines) = pn_value.get_lines(0..10);
-                for line in lines {
-                    match matcher.fuzzy_match(line.as_str().as_ref(), "Mic")
-                    {
-                        Some(score) => {
-                            let command = ExpandedCommand {
-                                brief: Cow::Owned(line.as_str().to_string()),
-                                doc: Default::default(),
-                                action: KeyAssignment::Nop,
-                                keys: vec![],
-                                menubar: &[],
-                                icon: None,
-                            };
-                            commands.push(command);
-                        }, None => {
-
-                        }
-                    }
-
-                }
-            }, None => {
-
-            }
-        }
+        let commands = build_commands(GuiWin::new(term_window), mux_pane, filter_copy_mode);
 
         Self {
             element: RefCell::new(None),
@@ -618,8 +582,12 @@ impl Modal for CommandPalette {
         false
     }
 
-    fn mouse_event(&self, _event: MouseEvent, _term_window: &mut TermWindow) -> anyhow::Result<()> {
-        Ok(())
+    fn mouse_event(
+        &self,
+        _event: MouseEvent,
+        _term_window: &mut TermWindow,
+    ) -> anyhow::Result<bool> {
+        Ok(false)
     }
 
     fn key_down(
@@ -1,5 +1,5 @@
 use crate::customglyph::*;
-use crate::tabbar::{TabBarItem, TabEntry};
+use crate::tabbar::{progress_palette_index, TabBarItem, TabEntry};
 use crate::termwindow::box_model::*;
 use crate::termwindow::render::corners::*;
 
@@ -7,6 +7,9 @@ use crate::termwindow::render::window_buttons::window_button_element;
 use crate::termwindow::{UIItem, UIItemType};
 use crate::utilsprites::RenderMetrics;
 use config::{Dimension, DimensionContext, TabBarColors};
+use std::rc::Rc;
+use termwiz::escape::osc::Progress;
+use wezterm_font::LoadedFont;
 use wezterm_term::color::{ColorAttribute, ColorPalette};
 use window::{IntegratedTitleButtonAlignment, IntegratedTitleButtonStyle};
 
@@ -48,6 +51,64 @@ const PLUS_BUTTON: &[Poly] = &[
     },
 ];
 
+/// The width of the track of the progress bar shown in a tab, in cells
+const PROGRESS_BAR_WIDTH: f32 = 2.0;
+
+/// Returns a small bar that indicates `progress`, or None if there is
+/// no operation in progress.  When the completion is unknown, a
+/// segment in the middle of the track is filled.
+fn progress_bar_element(
+    font: &Rc<LoadedFont>,
+    metrics: &RenderMetrics,
+    palette: &ColorPalette,
+    progress: Progress,
+) -> Option<Element> {
+    let (offset, filled) = match progress {
+        Progress::None => return None,
+        Progress::SetIndeterminate => (PROGRESS_BAR_WIDTH / 3., PROGRESS_BAR_WIDTH / 3.),
+        _ => (
+            0.,
+            PROGRESS_BAR_WIDTH * progress.percentage().unwrap_or(0) as f32 / 100.,
+        ),
+    };
+    let height = Dimension::Pixels((metrics.underline_height.max(2) * 2) as f32);
+    let colors = |color: ColorAttribute| {
+        let color = palette.resolve_fg(color).to_linear();
+        ElementColors {
+            border: BorderColor::default(),
+            bg: color.into(),
+            text: color.into(),
+        }
+    };
+
+    let fill = Element::new(font, ElementContent::Children(vec![]))
+        .min_width(Some(Dimension::Cells(filled)))
+        .min_height(Some(height))
+        .margin(BoxDimension {
+            left: Dimension::Cells(offset),
+            right: Dimension::Cells(0.),
+            top: Dimension::Cells(0.),
+            bottom: Dimension::Cells(0.),
+        })
+        .colors(colors(ColorAttribute::PaletteIndex(
+            progress_palette_index(progress),
+        )));
+
+    Some(
+        Element::new(font, ElementContent::Children(vec![fill]))
+            .vertical_align(VerticalAlign::Middle)
+            .min_width(Some(Dimension::Cells(PROGRESS_BAR_WIDTH)))
+            .min_height(Some(height))
+            .margin(BoxDimension {
+                left: Dimension::Cells(0.5),
+                right: Dimension::Cells(0.),
+                top: Dimension::Cells(0.),
+                bottom: Dimension::Cells(0.),
+            })
+            .colors(colors(ColorAttribute::PaletteIndex(8))),
+    )
+}
+
 impl crate::TermWindow {
     pub fn invalidate_fancy_tab_bar(&mut self) {
         self.fancy_tab_bar.take();
@@ -399,6 +460,11 @@ impl crate::TermWindow {
                                 bottom: Dimension::Cells(0.),
                             });
 
+                            if let Some(progress_bar) =
+                                progress_bar_element(&font, &metrics, palette, item.progress)
+                            {
+                                kids.push(progress_bar);
+                            }
                             kids.push(x_button);
                             ElementContent::Children(kids)
                         }
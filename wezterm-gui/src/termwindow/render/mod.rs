@@ -171,6 +171,9 @@ pub struct RenderScreenLineParams<'a> {
     pub render_metrics: RenderMetrics,
     pub shape_key: Option<LineToEleShapeCacheKey>,
     pub password_input: bool,
+    /// True if this is the topmost row shown in the pane, which
+    /// draws text sized via OSC 66 whose block starts above it
+    pub is_first_visible_row: bool,
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
@@ -797,6 +800,41 @@ impl crate::TermWindow {
         Ok(glyph_info)
     }
 
+    /// Shape the text of a cluster that was sized via OSC 66, using a
+    /// font that is rasterized at `scale` times the regular size.
+    /// The shape cache is keyed by style and text alone, so the result
+    /// is not kept there; the line element cache holds it instead.
+    fn scaled_cluster_shape(
+        &self,
+        style: &TextStyle,
+        cluster: &CellCluster,
+        gl_state: &RenderState,
+        scale: f64,
+    ) -> anyhow::Result<Rc<Vec<ShapedInfo>>> {
+        let font = self.fonts.resolve_scaled_font(style, scale)?;
+        let metrics = RenderMetrics::with_font_metrics(&font.metrics());
+        let window = self.window.as_ref().unwrap().clone();
+        let presentation_width = PresentationWidth::with_cluster(cluster);
+
+        let info = font.shape(
+            &cluster.text,
+            move || window.notify(TermWindowNotif::InvalidateShapeCache),
+            BlockKey::filter_out_synthetic,
+            Some(cluster.presentation),
+            cluster.direction,
+            None,
+            Some(&presentation_width),
+        )?;
+        let glyphs = self.glyph_infos_to_glyphs(
+            style,
+            &mut gl_state.glyph_cache.borrow_mut(),
+            &info,
+            &font,
+            &metrics,
+        )?;
+        Ok(Rc::new(ShapedInfo::process(&info, &glyphs)))
+    }
+
     fn lookup_cached_shape(
         &self,
         key: &dyn ShapeCacheKeyTrait,
@@ -1,6 +1,8 @@
+use crate::customglyph::*;
 use crate::quad::{HeapQuadAllocator, QuadTrait, TripleLayerQuadAllocator};
 use crate::selection::{SelectionRange, SelectionX};
 use crate::termwindow::box_model::*;
+use crate::termwindow::folds::{unfolded_segments, DisplayRow, FoldableOutput, OutputFolds};
 use crate::termwindow::render::{
     same_hyperlink, CursorProperties, LineQuadCacheKey, LineQuadCacheValue, LineToEleShapeCacheKey,
     RenderScreenLineParams,
@@ -14,14 +16,42 @@ use mux::pane::{PaneId, WithPaneLines};
 use mux::renderable::{RenderableDimensions, StableCursorPosition};
 use mux::tab::PositionedPane;
 use ordered_float::NotNan;
+use std::collections::HashMap;
+use std::ops::Range;
 use std::time::Instant;
-use termwiz::surface::CursorVisibility;
+use termwiz::cell::CellAttributes;
+use termwiz::color::AnsiColor;
+use termwiz::surface::{CursorVisibility, SEQ_ZERO};
 use wezterm_dynamic::Value;
 use wezterm_term::color::{ColorAttribute, ColorPalette};
-use wezterm_term::{Line, StableRowIndex};
+use wezterm_term::{unicode_column_width, Line, LineTimestamps, StableRowIndex};
 use window::color::LinearRgba;
 use window::KeyCode::Select;
 
+/// Shown alongside the prompt of a command whose output is visible
+const FOLD_EXPANDED: &[Poly] = &[Poly {
+    path: &[
+        PolyCommand::MoveTo(BlockCoord::Frac(1, 5), BlockCoord::Frac(1, 3)),
+        PolyCommand::LineTo(BlockCoord::Frac(4, 5), BlockCoord::Frac(1, 3)),
+        PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Frac(2, 3)),
+        PolyCommand::Close,
+    ],
+    intensity: BlockAlpha::Full,
+    style: PolyStyle::Fill,
+}];
+
+/// Shown alongside the prompt of a command whose output is folded
+const FOLD_COLLAPSED: &[Poly] = &[Poly {
+    path: &[
+        PolyCommand::MoveTo(BlockCoord::Frac(1, 3), BlockCoord::Frac(1, 5)),
+        PolyCommand::LineTo(BlockCoord::Frac(2, 3), BlockCoord::Frac(1, 2)),
+        PolyCommand::LineTo(BlockCoord::Frac(1, 3), BlockCoord::Frac(4, 5)),
+        PolyCommand::Close,
+    ],
+    intensity: BlockAlpha::Full,
+    style: PolyStyle::Fill,
+}];
+
 impl crate::TermWindow {
     fn paint_pane_box_model(&mut self, pos: &PositionedPane) -> anyhow::Result<()> {
         let computed = self.build_pane(pos)?;
@@ -31,6 +61,10 @@ impl crate::TermWindow {
         self.render_element(&computed, gl_state, None)
     }
 
+    /// Paints the rows of `pos.pane` around `selected_rows` into the
+    /// specified rect, with `context_lines` rows shown above the
+    /// selection.  The selected rows are shown as selected, and the
+    /// cells listed in `highlights` are shown in reverse video.
     pub fn paint_pane2(
         &mut self,
         pos: &PositionedPane,
@@ -39,17 +73,16 @@ impl crate::TermWindow {
         top_pixel_y: f32,
         width: f32,
         height: f32,
-        topRow: StableRowIndex
+        selected_rows: Range<StableRowIndex>,
+        context_lines: usize,
+        highlights: &HashMap<StableRowIndex, Vec<usize>>,
     ) -> anyhow::Result<()> {
         if self.config.use_box_model_render {
             return self.paint_pane_box_model(pos);
         }
 
-        let mut viewTopRow = 0;
-        if(topRow - 3 > 0)
-        {
-            viewTopRow = topRow - 3;
-        }
+        let viewTopRow = (selected_rows.start - context_lines as StableRowIndex)
+            .max(pos.pane.get_dimensions().scrollback_top);
         self.check_for_dirty_lines_and_invalidate_selection(&pos.pane);
         /*
         let zone = {
@@ -72,26 +105,6 @@ impl crate::TermWindow {
         let config = self.config.clone();
         let palette = pos.pane.palette();
 
-        let (mut padding_left, mut padding_top) = self.padding_left_top();
-        padding_left = padding_left + 10.0;
-        padding_top = padding_top + 10.0;
-        padding_left = 0.0;
-        padding_top = 0.0;
-
-        let tab_bar_height = if self.show_tab_bar {
-            self.tab_bar_pixel_height()
-                .context("tab_bar_pixel_height")?
-        } else {
-            0.
-        };
-        let (top_bar_height, bottom_bar_height) = if self.config.tab_bar_at_bottom {
-            (0.0, tab_bar_height)
-        } else {
-            (tab_bar_height, 0.0)
-        };
-
-        let border = self.get_os_border();
-
         let mut cursor = pos.pane.get_cursor_position();
         cursor.visibility = CursorVisibility::Hidden;
         if pos.is_active {
@@ -99,7 +112,6 @@ impl crate::TermWindow {
         }
 
         let pane_id = pos.pane.pane_id();
-        let current_viewport = self.get_viewport(pane_id);
         let mut dims = pos.pane.get_dimensions().clone();
         dims.pixel_width = 400;
 
@@ -125,51 +137,7 @@ impl crate::TermWindow {
         let mut cell_width = self.render_metrics.cell_size.width as f32;
         dims.cols = ((width) / cell_width) as usize;
         let cell_height = self.render_metrics.cell_size.height as f32;
-        let background_rect = {
-            // We want to fill out to the edges of the splits
-            let (x, width_delta) = if pos.left == 0 {
-                (
-                    0.,
-                    padding_left + border.left.get() as f32 + (cell_width / 2.0) ,
-                )
-            } else {
-                (
-                    left_pixel_x,
-                    cell_width,
-                )
-            };
-
-            let (y, height_delta) = if pos.top == 0 {
-                (
-                    (top_pixel_y - padding_top),
-                    padding_top + (cell_height / 2.0),
-                )
-            } else {
-                (
-                    top_pixel_y,
-                    cell_height,
-                )
-            };
-            euclid::rect(
-                x,
-                y,
-                // Go all the way to the right edge if we're right-most
-                if pos.left + pos.width >= self.terminal_size.cols as usize {
-                    width
-                    //self.dimensions.pixel_width as f32 - x - 300.0
-                } else {
-                    (pos.width as f32 * cell_width) + width_delta
-                },
-                // Go all the way to the bottom if we're bottom-most
-                if pos.top + pos.height >= self.terminal_size.rows as usize {
-                    height
-                    //cell_height * 25.0 + (padding_top * 2.0)
-                    //self.dimensions.pixel_height as f32 - y - 600.0
-                } else {
-                    (pos.height as f32 * cell_height) + height_delta as f32
-                },
-            )
-        };
+        let background_rect = euclid::rect(left_pixel_x, top_pixel_y, width, height);
 
         if self.window_background.is_empty() {
             // Per-pane, palette-specified background
@@ -203,9 +171,12 @@ impl crate::TermWindow {
 
             let sel = SelectionRange::start(crate::selection::SelectionCoordinate {
                 x: SelectionX::Cell(0),
-                y: topRow,
+                y: selected_rows.start,
             })
-                .extend(crate::selection::SelectionCoordinate { x: SelectionX::Cell(dims.cols), y: topRow });
+                .extend(crate::selection::SelectionCoordinate {
+                    x: SelectionX::Cell(dims.cols),
+                    y: (selected_rows.end - 1).max(selected_rows.start),
+                });
             (sel, false)
         };
 
@@ -219,11 +190,7 @@ impl crate::TermWindow {
 
         let numberOfRows = height / cell_height;
         {
-            let stable_range = match current_viewport {
-                Some(top) => top..top + dims.viewport_rows as StableRowIndex,
-                //None => dims.physical_top..dims.physical_top + 25 as StableRowIndex,
-                None => viewTopRow..viewTopRow + numberOfRows as StableRowIndex,
-            };
+            let stable_range = viewTopRow..viewTopRow + numberOfRows as StableRowIndex;
 
             pos.pane
                 .apply_hyperlinks(stable_range.clone(), &self.config.hyperlink_rules);
@@ -251,6 +218,7 @@ impl crate::TermWindow {
                 filled_box: TextureRect,
                 window_is_transparent: bool,
                 layers: &'a mut TripleLayerQuadAllocator<'b>,
+                highlights: &'a HashMap<StableRowIndex, Vec<usize>>,
                 error: Option<anyhow::Error>,
             }
 
@@ -277,6 +245,7 @@ impl crate::TermWindow {
                 filled_box,
                 window_is_transparent,
                 layers,
+                highlights,
                 error: None,
             };
 
@@ -285,9 +254,25 @@ impl crate::TermWindow {
                     &mut self,
                     stable_top: StableRowIndex,
                     line_idx: usize,
-                    line: &&mut Line,
+                    line: &Line,
                 ) -> anyhow::Result<()> {
                     let stable_row = stable_top + line_idx as StableRowIndex;
+
+                    let highlighted;
+                    let line = match self.highlights.get(&stable_row) {
+                        Some(cols) => {
+                            let mut copy = line.clone();
+                            let cells = copy.cells_mut_for_attr_changes_only();
+                            for col in cols {
+                                if let Some(cell) = cells.get_mut(*col) {
+                                    cell.attrs_mut().set_reverse(true);
+                                }
+                            }
+                            highlighted = copy;
+                            &highlighted
+                        }
+                        None => line,
+                    };
                     let selrange = self
                         .selrange
                         .map_or(0..0, |sel| sel.cols_for_row(stable_row, self.rectangular));
@@ -436,6 +421,7 @@ impl crate::TermWindow {
                                 render_metrics: self.term_window.render_metrics,
                                 shape_key: Some(shape_key),
                                 password_input,
+                                is_first_visible_row: line_idx == 0,
                             },
                             &mut TripleLayerQuadAllocator::Heap(&mut buf),
                         )
@@ -471,7 +457,7 @@ impl crate::TermWindow {
             impl<'a, 'b> WithPaneLines for LineRender<'a, 'b> {
                 fn with_lines_mut(&mut self, stable_top: StableRowIndex, lines: &mut [&mut Line]) {
                     for (line_idx, line) in lines.iter().enumerate() {
-                        if let Err(err) = self.render_line(stable_top, line_idx, line) {
+                        if let Err(err) = self.render_line(stable_top, line_idx, &**line) {
                             self.error.replace(err);
                             return;
                         }
@@ -769,14 +755,54 @@ impl crate::TermWindow {
         let cursor_is_default_color =
             palette.cursor_fg == global_cursor_fg && palette.cursor_bg == global_cursor_bg;
 
-        {
-            let stable_range = match current_viewport {
-                Some(top) => top..top + dims.viewport_rows as StableRowIndex,
-                None => dims.physical_top..dims.physical_top + dims.viewport_rows as StableRowIndex,
+        let outputs = self.sync_output_folds(&pos.pane);
+        let folds = self.output_folds(pane_id);
+
+        let left_pixel_x = padding_left
+            + border.left.get() as f32
+            + (pos.left as f32 * self.render_metrics.cell_size.width as f32);
+
+        let display_rows = {
+            let display_rows: Vec<DisplayRow> = if folds.is_empty() {
+                let stable_range = match current_viewport {
+                    Some(top) => top..top + dims.viewport_rows as StableRowIndex,
+                    None => {
+                        dims.physical_top..dims.physical_top + dims.viewport_rows as StableRowIndex
+                    }
+                };
+                stable_range
+                    .map(|row| DisplayRow { row, folded: 0 })
+                    .collect()
+            } else {
+                folds.display_rows(
+                    self.viewport_top(pane_id, &dims),
+                    dims.viewport_rows,
+                    dims.physical_top + dims.viewport_rows as StableRowIndex,
+                )
             };
+            // The rows of the pane that are visible, as opposed to
+            // being hidden by a fold
+            let segments = unfolded_segments(&display_rows);
+
+            let mut timestamps = HashMap::new();
+            for segment in &segments {
+                pos.pane
+                    .apply_hyperlinks(segment.clone(), &self.config.hyperlink_rules);
+
+                if config.show_line_timestamps {
+                    timestamps.extend(line_timestamp_labels(
+                        &pos.pane.get_line_timestamps(segment.clone()),
+                        segment.start,
+                        &config.line_timestamp_format,
+                    ));
+                }
+            }
 
-            pos.pane
-                .apply_hyperlinks(stable_range.clone(), &self.config.hyperlink_rules);
+            let display_index: HashMap<StableRowIndex, usize> = display_rows
+                .iter()
+                .enumerate()
+                .map(|(display_idx, display)| (display.row, display_idx))
+                .collect();
 
             struct LineRender<'a, 'b> {
                 term_window: &'a mut crate::TermWindow,
@@ -801,13 +827,11 @@ impl crate::TermWindow {
                 filled_box: TextureRect,
                 window_is_transparent: bool,
                 layers: &'a mut TripleLayerQuadAllocator<'b>,
+                timestamps: HashMap<StableRowIndex, String>,
+                display_index: HashMap<StableRowIndex, usize>,
                 error: Option<anyhow::Error>,
             }
 
-            let left_pixel_x = padding_left
-                + border.left.get() as f32
-                + (pos.left as f32 * self.render_metrics.cell_size.width as f32);
-
             let mut render = LineRender {
                 term_window: self,
                 selrange,
@@ -831,17 +855,18 @@ impl crate::TermWindow {
                 filled_box,
                 window_is_transparent,
                 layers,
+                timestamps,
+                display_index,
                 error: None,
             };
 
             impl<'a, 'b> LineRender<'a, 'b> {
                 fn render_line(
                     &mut self,
-                    stable_top: StableRowIndex,
-                    line_idx: usize,
-                    line: &&mut Line,
+                    stable_row: StableRowIndex,
+                    display_idx: usize,
+                    line: &Line,
                 ) -> anyhow::Result<()> {
-                    let stable_row = stable_top + line_idx as StableRowIndex;
                     let selrange = self
                         .selrange
                         .map_or(0..0, |sel| sel.cols_for_row(stable_row, self.rectangular));
@@ -902,10 +927,10 @@ impl crate::TermWindow {
                         cursor,
                         shape_hash,
                         top_pixel_y: NotNan::new(self.top_pixel_y).unwrap()
-                            + (line_idx + self.pos.top) as f32
+                            + (display_idx + self.pos.top) as f32
                                 * self.term_window.render_metrics.cell_size.height as f32,
                         left_pixel_x: NotNan::new(self.left_pixel_x).unwrap(),
-                        phys_line_idx: line_idx,
+                        phys_line_idx: display_idx,
                         reverse_video: self.dims.reverse_video,
                     };
 
@@ -990,6 +1015,7 @@ impl crate::TermWindow {
                                 render_metrics: self.term_window.render_metrics,
                                 shape_key: Some(shape_key),
                                 password_input,
+                                is_first_visible_row: display_idx == 0,
                             },
                             &mut TripleLayerQuadAllocator::Heap(&mut buf),
                         )
@@ -1025,7 +1051,20 @@ impl crate::TermWindow {
             impl<'a, 'b> WithPaneLines for LineRender<'a, 'b> {
                 fn with_lines_mut(&mut self, stable_top: StableRowIndex, lines: &mut [&mut Line]) {
                     for (line_idx, line) in lines.iter().enumerate() {
-                        if let Err(err) = self.render_line(stable_top, line_idx, line) {
+                        let stable_row = stable_top + line_idx as StableRowIndex;
+                        let display_idx = match self.display_index.get(&stable_row) {
+                            Some(display_idx) => *display_idx,
+                            None => continue,
+                        };
+                        let stamped;
+                        let line: &Line = match self.timestamps.get(&stable_row) {
+                            Some(label) => {
+                                stamped = overlay_line_timestamp(line, label, self.dims.cols);
+                                &stamped
+                            }
+                            None => &**line,
+                        };
+                        if let Err(err) = self.render_line(stable_row, display_idx, line) {
                             self.error.replace(err);
                             return;
                         }
@@ -1033,10 +1072,39 @@ impl crate::TermWindow {
                 }
             }
 
-            pos.pane.with_lines_mut(stable_range.clone(), &mut render);
-            if let Some(error) = render.error.take() {
-                return Err(error).context("error while calling with_lines_mut");
+            for segment in segments {
+                pos.pane.with_lines_mut(segment, &mut render);
+                if let Some(error) = render.error.take() {
+                    return Err(error).context("error while calling with_lines_mut");
+                }
             }
+
+            for (display_idx, display) in display_rows.iter().enumerate() {
+                if display.folded > 0 {
+                    render
+                        .render_line(
+                            display.row,
+                            display_idx,
+                            &folded_output_line(display.folded),
+                        )
+                        .context("render folded output")?;
+                }
+            }
+
+            display_rows
+        };
+
+        if config.show_output_fold_toggles && !outputs.is_empty() {
+            self.paint_output_fold_toggles(
+                pos,
+                layers,
+                &display_rows,
+                &outputs,
+                &folds,
+                left_pixel_x,
+                top_pixel_y,
+            )
+            .context("paint_output_fold_toggles")?;
         }
 
         /*
@@ -1050,6 +1118,75 @@ impl crate::TermWindow {
         Ok(())
     }
 
+    /// Draws a toggle in the gutter to the left of the pane alongside
+    /// the prompt of each command whose output can be folded
+    fn paint_output_fold_toggles(
+        &mut self,
+        pos: &PositionedPane,
+        layers: &mut TripleLayerQuadAllocator,
+        display_rows: &[DisplayRow],
+        outputs: &[FoldableOutput],
+        folds: &OutputFolds,
+        left_pixel_x: f32,
+        top_pixel_y: f32,
+    ) -> anyhow::Result<()> {
+        let cell_width = self.render_metrics.cell_size.width as f32;
+        let cell_height = self.render_metrics.cell_size.height as f32;
+        let (padding_left, _) = self.padding_left_top();
+        // Panes to the right of a split only have half a cell to spare
+        let size = if pos.left == 0 {
+            padding_left
+        } else {
+            cell_width / 2.
+        }
+        .min(cell_width)
+        .min(cell_height);
+        if size < 1. {
+            return Ok(());
+        }
+
+        let color = pos.pane.palette().foreground.to_linear().mul_alpha(0.5);
+        let x = left_pixel_x - size;
+
+        for (display_idx, display) in display_rows.iter().enumerate() {
+            let output = match outputs
+                .iter()
+                .find(|output| output.prompt_row == display.row)
+            {
+                Some(output) => output,
+                None => continue,
+            };
+            let row_y = top_pixel_y + (display_idx + pos.top) as f32 * cell_height;
+
+            self.poly_quad(
+                layers,
+                2,
+                euclid::point2(x, row_y + (cell_height - size) / 2.),
+                if folds.is_folded(&output.rows) {
+                    FOLD_COLLAPSED
+                } else {
+                    FOLD_EXPANDED
+                },
+                self.render_metrics.underline_height,
+                euclid::size2(size, size),
+                color,
+            )?;
+
+            self.ui_items.push(UIItem {
+                x: x.max(0.) as usize,
+                y: row_y as usize,
+                width: size.ceil() as usize,
+                height: cell_height as usize,
+                item_type: UIItemType::OutputFold {
+                    pane_id: pos.pane.pane_id(),
+                    rows: output.rows.clone(),
+                },
+            });
+        }
+
+        Ok(())
+    }
+
     pub fn build_pane(&mut self, pos: &PositionedPane) -> anyhow::Result<ComputedElement> {
         // First compute the bounds for the pane background
 
@@ -1155,3 +1292,59 @@ impl crate::TermWindow {
         })
     }
 }
+
+/// Returns the line shown in place of the `folded` rows of a fold
+fn folded_output_line(folded: usize) -> Line {
+    let mut attr = CellAttributes::default();
+    attr.set_foreground(AnsiColor::Grey).set_italic(true);
+    Line::from_text(
+        &format!("\u{22ef} {folded} lines folded"),
+        &attr,
+        SEQ_ZERO,
+        None,
+    )
+}
+
+/// Computes the timestamp labels to show for the rows from `top` onwards.
+/// A label is shown for the first row of each run of lines that arrived
+/// together, along with the top row so that the visible lines always
+/// have a time for context.
+fn line_timestamp_labels(
+    timestamps: &LineTimestamps,
+    top: StableRowIndex,
+    format: &str,
+) -> HashMap<StableRowIndex, String> {
+    // An invalid format would panic when the time is formatted,
+    // so fall back to the default format in that case
+    let format = if chrono::format::StrftimeItems::new(format)
+        .any(|item| item == chrono::format::Item::Error)
+    {
+        "%H:%M:%S"
+    } else {
+        format
+    };
+    timestamps
+        .iter()
+        .map(|(rows, time)| {
+            let label = chrono::DateTime::<chrono::Local>::from(time)
+                .format(format)
+                .to_string();
+            (rows.start.max(top), format!(" {label} "))
+        })
+        .collect()
+}
+
+/// Returns a copy of `line` with `label` drawn over its right hand edge
+fn overlay_line_timestamp(line: &Line, label: &str, cols: usize) -> Line {
+    let mut line = line.clone();
+    let width = unicode_column_width(label, None);
+    if width < cols {
+        let seqno = line.current_seqno();
+        let mut attr = CellAttributes::default();
+        attr.set_foreground(AnsiColor::Grey)
+            .set_background(AnsiColor::Black)
+            .set_italic(true);
+        line.overlay_text_with_attribute(cols - width, label, attr, seqno);
+    }
+    line
+}
@@ -4,14 +4,17 @@ use crate::termwindow::render::{
     ComputeCellFgBgParams, ComputeCellFgBgResult, LineToElementParams, LineToElementShape,
     RenderScreenLineParams, RenderScreenLineResult,
 };
-use crate::termwindow::LineToElementShapeItem;
+use crate::termwindow::{LineToElementShapeItem, RenderState};
+use crate::utilsprites::RenderMetrics;
 use ::window::DeadKeyStatus;
 use anyhow::Context;
 use config::{HsbTransform, TextStyle};
 use std::ops::Range;
 use std::rc::Rc;
 use std::time::Instant;
-use termwiz::cell::{unicode_column_width, Blink};
+use termwiz::cell::{
+    unicode_column_width, Blink, ScaledText, TextSizeHorizontalAlign, TextSizeVerticalAlign,
+};
 use termwiz::color::LinearRgba;
 use termwiz::surface::CursorShape;
 use wezterm_bidi::Direction;
@@ -460,6 +463,28 @@ impl crate::TermWindow {
                 };
             }
 
+            if let Some(scaled) = cluster.attrs.scaled_text() {
+                // The glyphs extend over the rows below, so they are
+                // drawn from the first row of the block that is visible
+                if scaled.row == 0 || params.is_first_visible_row {
+                    self.render_scaled_text(
+                        item,
+                        scaled,
+                        params.left_pixel_x + cluster_x_pos,
+                        &params,
+                        gl_state,
+                        layers,
+                        hsv,
+                    )
+                    .context("render_scaled_text")?;
+                }
+                visual_cell_idx += cluster.width;
+                if direction == Direction::LeftToRight {
+                    cluster_x_pos += cluster.width as f32 * cell_width;
+                }
+                continue;
+            }
+
             for info in glyph_info.iter() {
                 let glyph = &info.glyph;
 
@@ -719,6 +744,162 @@ impl crate::TermWindow {
         })
     }
 
+    /// Renders the glyphs of a block of text that was sized via OSC 66,
+    /// starting at the pixel offset `left`.
+    /// The glyphs were rasterized at the size of the text, and are
+    /// positioned within the block according to its alignment.  When
+    /// the block starts above this row, the part of it above the row
+    /// is clipped away.
+    fn render_scaled_text(
+        &self,
+        item: &LineToElementShape,
+        scaled: ScaledText,
+        left: f32,
+        params: &RenderScreenLineParams,
+        gl_state: &RenderState,
+        layers: &mut TripleLayerQuadAllocator,
+        hsv: Option<HsbTransform>,
+    ) -> anyhow::Result<()> {
+        let cluster = &item.cluster;
+        if cluster.attrs.invisible() {
+            return Ok(());
+        }
+        let size = scaled.size;
+
+        let width_scale = if params.line.is_single_width() {
+            1.0
+        } else {
+            2.0
+        };
+        let height_scale = if params.line.is_double_height_top() {
+            2.0
+        } else {
+            1.0
+        };
+        let cell_width = params.render_metrics.cell_size.width as f32 * width_scale;
+        let cell_height = params.render_metrics.cell_size.height as f32 * height_scale;
+
+        let font = self
+            .fonts
+            .resolve_scaled_font(&item.style, size.font_scale() as f64)?;
+        let metrics = RenderMetrics::with_font_metrics(&font.metrics());
+
+        // Fractionally scaled text can be smaller than its block
+        let block_width = cluster.width as f32 * cell_width;
+        let block_height = size.scale as f32 * cell_height;
+        let text_width: f32 = item
+            .glyph_info
+            .iter()
+            .map(|info| info.glyph.x_advance.get() as f32 * width_scale)
+            .sum();
+        let text_height = metrics.cell_size.height as f32 * height_scale;
+        let x_pad = match size.horizontal_align {
+            TextSizeHorizontalAlign::Left => 0.,
+            TextSizeHorizontalAlign::Right => block_width - text_width,
+            TextSizeHorizontalAlign::Center => (block_width - text_width) / 2.,
+        }
+        .max(0.);
+        let y_pad = match size.vertical_align {
+            TextSizeVerticalAlign::Top => 0.,
+            TextSizeVerticalAlign::Bottom => block_height - text_height,
+            TextSizeVerticalAlign::Center => (block_height - text_height) / 2.,
+        }
+        .max(0.);
+
+        let ComputeCellFgBgResult {
+            fg_color: glyph_color,
+            bg_color,
+            fg_color_alt,
+            fg_color_mix,
+            ..
+        } = self.compute_cell_fg_bg(ComputeCellFgBgParams {
+            cursor: None,
+            selected: params.selection.contains(&cluster.first_cell_idx),
+            fg_color: item.fg_color,
+            bg_color: item.bg_color,
+            is_active_pane: params.is_active,
+            config: params.config,
+            selection_fg: params.selection_fg,
+            selection_bg: params.selection_bg,
+            cursor_fg: params.cursor_fg,
+            cursor_bg: params.cursor_bg,
+            cursor_is_default_color: params.cursor_is_default_color,
+            cursor_border_color: params.cursor_border_color,
+            pane: params.pane,
+        });
+        if glyph_color == bg_color {
+            return Ok(());
+        }
+
+        let gl_x = self.dimensions.pixel_width as f32 / -2.;
+        let row_top = (self.dimensions.pixel_height as f32 / -2.) + params.top_pixel_y;
+        // The block starts `scaled.row` rows above this one
+        let pos_y = row_top - scaled.row as f32 * cell_height + y_pad;
+        let mut pen_x = left + x_pad;
+
+        for info in item.glyph_info.iter() {
+            let glyph = &info.glyph;
+            let mut texture = glyph.texture.as_ref().cloned();
+            let mut x = pen_x + (glyph.x_offset + glyph.bearing_x).get() as f32 * width_scale;
+            let mut top = text_height
+                + (metrics.descender.get() as f32
+                    - (glyph.y_offset + glyph.bearing_y).get() as f32)
+                    * height_scale;
+
+            if self.config.custom_block_glyphs {
+                if let Some(block) = &info.block_key {
+                    texture.replace(
+                        gl_state
+                            .glyph_cache
+                            .borrow_mut()
+                            .cached_block(*block, &metrics)
+                            .context("cached_block")?,
+                    );
+                    // Custom glyphs are rendered relative to the cell
+                    // top left, rather than the baseline
+                    x = pen_x;
+                    top = 0.;
+                }
+            }
+
+            if let Some(texture) = texture {
+                let mut coords = texture.texture_coords();
+                let width = texture.coords.size.width as f32 * width_scale;
+                let mut height = texture.coords.size.height as f32 * height_scale;
+                let mut y = pos_y + top;
+                if y < row_top && height > 0. {
+                    let clipped = (row_top - y).min(height);
+                    coords.origin.y += coords.size.height * clipped / height;
+                    coords.size.height -= coords.size.height * clipped / height;
+                    height -= clipped;
+                    y = row_top;
+                }
+
+                if height > 0. {
+                    let mut quad = layers.allocate(1).context("layers.allocate(1)")?;
+                    quad.set_position(gl_x + x, y, gl_x + x + width, y + height);
+                    quad.set_fg_color(glyph_color);
+                    quad.set_alt_color_and_mix_value(fg_color_alt, fg_color_mix);
+                    quad.set_texture(coords);
+                    quad.set_hsv(if glyph.brightness_adjust != 1.0 {
+                        let hsv = hsv.unwrap_or_else(|| HsbTransform::default());
+                        Some(HsbTransform {
+                            brightness: hsv.brightness * glyph.brightness_adjust,
+                            ..hsv
+                        })
+                    } else {
+                        hsv
+                    });
+                    quad.set_has_color(glyph.has_color);
+                }
+            }
+
+            pen_x += glyph.x_advance.get() as f32 * width_scale;
+        }
+
+        Ok(())
+    }
+
     fn build_line_element_shape(
         &self,
         params: LineToElementParams,
@@ -857,13 +1038,21 @@ impl crate::TermWindow {
 
             let style_params = last_style.as_ref().expect("we just set it up").clone();
 
-            let glyph_info = self.cached_cluster_shape(
-                style_params.style,
-                &cluster,
-                &gl_state,
-                None,
-                &self.render_metrics,
-            )?;
+            let glyph_info = match cluster.attrs.scaled_text() {
+                Some(scaled) => self.scaled_cluster_shape(
+                    style_params.style,
+                    &cluster,
+                    &gl_state,
+                    scaled.size.font_scale() as f64,
+                )?,
+                None => self.cached_cluster_shape(
+                    style_params.style,
+                    &cluster,
+                    &gl_state,
+                    None,
+                    &self.render_metrics,
+                )?,
+            };
             let pixel_width = glyph_info
                 .iter()
                 .map(|info| info.glyph.x_advance.get() as f32)
@@ -1,9 +1,25 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use config::keyassignment::ExportFormat;
 use mux::pane::PaneId;
+use termwiz_funcs::export::export_lines;
 use termwiz_funcs::lines_to_escapes;
 use wezterm_client::client::Client;
+use wezterm_term::color::ColorPalette;
 use wezterm_term::{ScrollbackOrVisibleRowIndex, StableRowIndex};
 
+#[derive(Copy, Clone, Debug, ValueEnum, Default, PartialEq, Eq)]
+enum TextFormat {
+    /// Unattributed text
+    #[default]
+    Text,
+    /// A standalone HTML document
+    Html,
+    /// A standalone SVG image
+    Svg,
+    /// Text with escape sequences that color and style it
+    Ansi,
+}
+
 #[derive(Debug, Parser, Clone)]
 pub struct GetText {
     /// Specify the target pane.
@@ -30,8 +46,16 @@ pub struct GetText {
 
     /// Include escape sequences that color and style the text.
     /// If omitted, unattributed text will be returned.
-    #[arg(long)]
+    /// This is equivalent to `--format ansi`.
+    #[arg(long, conflicts_with = "format")]
     escapes: bool,
+
+    /// The format of the output.
+    /// The html and svg formats produce standalone documents that
+    /// preserve the colors, attributes and hyperlinks of the text,
+    /// using the colors from your configuration.
+    #[arg(long, value_enum, default_value_t)]
+    format: TextFormat,
 }
 
 impl GetText {
@@ -81,12 +105,27 @@ impl GetText {
             .0
             .into_iter()
             .map(|(_idx, line)| line)
-            .collect();
+            .collect::<Vec<_>>();
 
-        if self.escapes {
-            println!("{}", lines_to_escapes(lines)?);
+        let format = if self.escapes {
+            TextFormat::Ansi
         } else {
-            lines.iter().for_each(|line| println!("{}", line.as_str()));
+            self.format
+        };
+
+        match format {
+            TextFormat::Text => lines.iter().for_each(|line| println!("{}", line.as_str())),
+            TextFormat::Ansi => println!("{}", lines_to_escapes(lines)?),
+            TextFormat::Html | TextFormat::Svg => {
+                let config = config::configuration();
+                let palette: ColorPalette = config.resolved_palette.clone().into();
+                let format = if format == TextFormat::Html {
+                    ExportFormat::Html
+                } else {
+                    ExportFormat::Svg
+                };
+                print!("{}", export_lines(lines, &palette, format)?);
+            }
         }
         Ok(())
     }